@@ -2,11 +2,16 @@ use std::sync::Arc;
 
 use eth_trie::{EthTrie, Trie};
 use ethereum_types::{H256, U256};
+use runtime::error::RuntimeError;
+use runtime::state::{Backend, State};
 use types::account::{Account, AccountData};
 use types::block::BlockNumber;
 use types::bytes::Bytes;
-use utils::crypto::to_address;
+use types::transaction::DeploymentData;
+use utils::crypto::{contract_address, contract_address_with_salt};
 
+use crate::code::CodeStorage;
+use crate::contract_storage::ContractStorage;
 use crate::helpers::{deserialize, serialize};
 use crate::{
     error::{ChainError, Result},
@@ -16,12 +21,18 @@ use crate::{
 #[derive(Debug)]
 pub(crate) struct AccountStorage {
     pub(crate) trie: EthTrie<Storage>,
+    pub(crate) code: CodeStorage,
+    pub(crate) contract_storage: ContractStorage,
+    storage: Arc<Storage>,
 }
 
 impl AccountStorage {
     pub(crate) fn new(storage: Arc<Storage>) -> Self {
         Self {
             trie: EthTrie::new(Arc::clone(&storage)),
+            code: CodeStorage::new(Arc::clone(&storage)),
+            contract_storage: ContractStorage::new(Arc::clone(&storage)),
+            storage,
         }
     }
 
@@ -42,16 +53,43 @@ impl AccountStorage {
         Ok(should_add)
     }
 
-    pub fn add_contract_account(&mut self, key: &Account, data: Bytes) -> Result<Account> {
-        let nonce = self.get_account(key)?.nonce;
-        let serialized = bincode::serialize(&(key, nonce))?;
-        let account = to_address(&serialized);
-        let account_data = AccountData::new(Some(data));
+    /// Add a contract account, deriving the address using CREATE (sender and nonce)
+    /// or CREATE2 (sender, salt and code) when a salt is present.
+    /// Any balance already sent to the derived address, and any storage written
+    /// by the constructor, is preserved.
+    pub(crate) fn add_contract_account(
+        &mut self,
+        key: &Account,
+        nonce: U256,
+        deployment: DeploymentData,
+    ) -> Result<Account> {
+        let account = AccountStorage::derive_contract_address(key, nonce, &deployment);
+        let mut account_data = self
+            .get_account(&account)
+            .unwrap_or_else(|_| AccountData::new(None));
+
+        if account_data.code_hash.is_some() {
+            return Err(ChainError::ContractAlreadyExists(account.to_string()));
+        }
+
+        account_data.code_hash = Some(self.code.insert(&deployment.code)?);
         self.add_account(&account, &account_data)?;
 
         Ok(account)
     }
 
+    /// Derive the address of a contract before it is deployed
+    pub(crate) fn derive_contract_address(
+        key: &Account,
+        nonce: U256,
+        deployment: &DeploymentData,
+    ) -> Account {
+        match deployment.salt {
+            Some(salt) => contract_address_with_salt(key, &salt, &deployment.code),
+            None => contract_address(key, nonce),
+        }
+    }
+
     pub(crate) fn add_account(&mut self, key: &Account, data: &AccountData) -> Result<()> {
         self.upsert(key, data)
     }
@@ -66,6 +104,33 @@ impl AccountStorage {
         deserialize(account)
     }
 
+    /// Get an account from the state trie as it was at the given state root.
+    /// Nodes are never removed from storage, so historical roots stay readable.
+    pub(crate) fn get_account_by_root(&self, key: &Account, root: H256) -> Result<AccountData> {
+        get_account_by_root(&self.storage, key, root)
+    }
+
+    /// Get the nodes of the state trie at the given root on the path to an account.
+    /// The proof of a missing account proves its absence.
+    pub(crate) fn get_proof_by_root(&self, key: &Account, root: H256) -> Result<Vec<Bytes>> {
+        let mut trie = EthTrie::from(Arc::clone(&self.storage), root.0.into())
+            .map_err(|e| ChainError::StorageNotFound(format!("state root {}: {}", root, e)))?;
+        let proof = trie
+            .get_proof(key.as_ref())
+            .map_err(|e| ChainError::StorageNotFound(format!("{:?}: {}", key, e)))?;
+
+        Ok(proof.into_iter().map(Bytes::from).collect())
+    }
+
+    /// Resolve the code of a contract account using its code hash.
+    pub(crate) fn get_code(&self, account_data: &AccountData, key: &Account) -> Result<Bytes> {
+        let code_hash = account_data
+            .code_hash
+            .ok_or_else(|| ChainError::NotAContractAccount(key.to_string()))?;
+
+        self.code.get(&code_hash)
+    }
+
     pub(crate) fn add_account_balance(&mut self, key: &Account, amount: U256) -> Result<()> {
         let mut account_data = self.get_account(key)?;
         account_data.balance += amount;
@@ -115,6 +180,67 @@ impl AccountStorage {
         Ok(balance)
     }
 
+    /// Commit the state trie and return a read only view of it for the runtime
+    pub(crate) fn snapshot(&mut self) -> Result<AccountSnapshot> {
+        Ok(AccountSnapshot {
+            root: self.root_hash()?,
+            code: CodeStorage::new(Arc::clone(&self.storage)),
+            contract_storage: ContractStorage::new(Arc::clone(&self.storage)),
+            storage: Arc::clone(&self.storage),
+        })
+    }
+
+    /// Apply the changes made by a successful contract execution
+    pub(crate) fn apply_state(&mut self, state: &State) -> Result<()> {
+        for (key, balance) in state.balances() {
+            let mut account_data = self
+                .get_account(key)
+                .unwrap_or_else(|_| AccountData::new(None));
+            account_data.balance = *balance;
+            self.upsert(key, &account_data)?;
+        }
+
+        for (key, nonce) in state.nonces() {
+            let mut account_data = self
+                .get_account(key)
+                .unwrap_or_else(|_| AccountData::new(None));
+            account_data.nonce = *nonce;
+            self.upsert(key, &account_data)?;
+        }
+
+        // a contract being deployed doesn't have an account yet, the account is
+        // created with its storage and the code is added once it's deployed
+        for (key, changes) in state.storage() {
+            let mut account_data = self
+                .get_account(key)
+                .unwrap_or_else(|_| AccountData::new(None));
+            let storage_root = self
+                .contract_storage
+                .update(account_data.storage_root, changes)?;
+            account_data.storage_root = Some(storage_root);
+            self.upsert(key, &account_data)?;
+        }
+
+        // contracts created by other contracts and contracts replacing their code
+        for (key, code) in state.created().iter().chain(state.upgraded()) {
+            let mut account_data = self
+                .get_account(key)
+                .unwrap_or_else(|_| AccountData::new(None));
+            account_data.code_hash = Some(self.code.insert(code)?);
+            self.upsert(key, &account_data)?;
+        }
+
+        // destroyed contracts keep their account, without code or storage
+        for key in state.destroyed() {
+            let mut account_data = self.get_account(key)?;
+            account_data.code_hash = None;
+            account_data.storage_root = None;
+            self.upsert(key, &account_data)?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn root_hash(&mut self) -> Result<H256> {
         let root_hash = self
             .trie
@@ -125,6 +251,79 @@ impl AccountStorage {
     }
 }
 
+fn get_account_by_root(storage: &Arc<Storage>, key: &Account, root: H256) -> Result<AccountData> {
+    let not_found = || ChainError::AccountNotFound(format!("Account {:?} not found", key));
+    let trie = EthTrie::from(Arc::clone(storage), root.0.into()).map_err(|_| not_found())?;
+    let account = &trie
+        .get(key.as_ref())
+        .map_err(|_| not_found())?
+        .ok_or_else(|| ChainError::StorageNotFound(Storage::key_string(key)))?;
+
+    deserialize(account)
+}
+
+/// The accounts at a committed state root, read by contracts during execution
+#[derive(Debug)]
+pub(crate) struct AccountSnapshot {
+    root: H256,
+    code: CodeStorage,
+    contract_storage: ContractStorage,
+    storage: Arc<Storage>,
+}
+
+impl AccountSnapshot {
+    fn get_account(&self, key: &Account) -> Option<AccountData> {
+        get_account_by_root(&self.storage, key, self.root).ok()
+    }
+}
+
+impl Backend for AccountSnapshot {
+    fn code(&self, address: &Account) -> runtime::error::Result<Option<Bytes>> {
+        match self
+            .get_account(address)
+            .and_then(|account| account.code_hash)
+        {
+            Some(code_hash) => Ok(Some(
+                self.code
+                    .get(&code_hash)
+                    .map_err(|e| RuntimeError::StateError(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn code_by_hash(&self, code_hash: &H256) -> runtime::error::Result<Option<Bytes>> {
+        Ok(self.code.get(code_hash).ok())
+    }
+
+    fn nonce(&self, address: &Account) -> runtime::error::Result<U256> {
+        Ok(self
+            .get_account(address)
+            .map(|account| account.nonce)
+            .unwrap_or_default())
+    }
+
+    fn balance(&self, address: &Account) -> runtime::error::Result<U256> {
+        Ok(self
+            .get_account(address)
+            .map(|account| account.balance)
+            .unwrap_or_default())
+    }
+
+    fn storage(&self, address: &Account, key: &str) -> runtime::error::Result<Option<String>> {
+        match self
+            .get_account(address)
+            .and_then(|account| account.storage_root)
+        {
+            Some(storage_root) => self
+                .contract_storage
+                .get(Some(storage_root), key)
+                .map_err(|e| RuntimeError::StateError(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ethereum_types::H160;
@@ -173,6 +372,102 @@ mod tests {
         let (_, _) = add_account(&mut account_storage);
     }
 
+    #[test]
+    fn identical_contracts_share_code() {
+        let mut account_storage = new_account_storage();
+        let (_, deployer_1) = add_account(&mut account_storage);
+        let (_, deployer_2) = add_account(&mut account_storage);
+        let code: Bytes = vec![0, 1].into();
+        let deployment = DeploymentData::new(code.clone(), vec![], None);
+        let contract_1 = account_storage
+            .add_contract_account(&deployer_1, U256::from(1), deployment.clone())
+            .unwrap();
+        let contract_2 = account_storage
+            .add_contract_account(&deployer_2, U256::from(1), deployment)
+            .unwrap();
+        let account_data_1 = account_storage.get_account(&contract_1).unwrap();
+        let account_data_2 = account_storage.get_account(&contract_2).unwrap();
+
+        assert_ne!(contract_1, contract_2);
+        assert_eq!(account_data_1.code_hash, account_data_2.code_hash);
+        assert_eq!(
+            account_storage
+                .get_code(&account_data_1, &contract_1)
+                .unwrap(),
+            code
+        );
+    }
+
+    #[test]
+    fn contract_addresses_are_derived_from_the_nonce() {
+        let mut account_storage = new_account_storage();
+        let (_, deployer) = add_account(&mut account_storage);
+        let deployment = DeploymentData::new(vec![0, 1].into(), vec![], None);
+        let contract_1 = account_storage
+            .add_contract_account(&deployer, U256::from(1), deployment.clone())
+            .unwrap();
+        let contract_2 = account_storage
+            .add_contract_account(&deployer, U256::from(2), deployment)
+            .unwrap();
+
+        assert_eq!(contract_1, contract_address(&deployer, U256::from(1)));
+        assert_eq!(contract_2, contract_address(&deployer, U256::from(2)));
+    }
+
+    #[test]
+    fn contract_addresses_are_derived_from_the_salt() {
+        let mut account_storage = new_account_storage();
+        let (_, deployer) = add_account(&mut account_storage);
+        let code: Bytes = vec![0, 1, 2].into();
+        let salt = H256::random();
+        let deployment = DeploymentData::new(code.clone(), vec![], Some(salt));
+        let contract = account_storage
+            .add_contract_account(&deployer, U256::from(1), deployment.clone())
+            .unwrap();
+
+        assert_eq!(
+            contract,
+            contract_address_with_salt(&deployer, &salt, &code)
+        );
+
+        // the same salt and code can't be deployed twice, regardless of the nonce
+        let response = account_storage.add_contract_account(&deployer, U256::from(2), deployment);
+        assert!(matches!(
+            response,
+            Err(ChainError::ContractAlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn it_gets_an_account_by_root() {
+        let mut account_storage = new_account_storage();
+        let (account_data, id) = add_account(&mut account_storage);
+        let root_hash = account_storage.root_hash().unwrap();
+        account_storage
+            .add_account_balance(&id, U256::from(1))
+            .unwrap();
+        account_storage.root_hash().unwrap();
+
+        let retrieved = account_storage.get_account_by_root(&id, root_hash).unwrap();
+        assert_eq!(retrieved, account_data);
+    }
+
+    #[test]
+    fn it_proves_an_account() {
+        let mut account_storage = new_account_storage();
+        let (account_data, id) = add_account(&mut account_storage);
+        let root_hash = account_storage.root_hash().unwrap();
+        let proof = account_storage.get_proof_by_root(&id, root_hash).unwrap();
+        let proof = proof.iter().map(|node| node.to_vec()).collect();
+
+        let value = account_storage
+            .trie
+            .verify_proof(root_hash.0.into(), id.as_ref(), proof)
+            .unwrap()
+            .unwrap();
+        assert_eq!(deserialize::<AccountData>(&value).unwrap(), account_data);
+    }
+
     #[test]
     fn root_hash_changes() {
         let mut account_storage = new_account_storage();
@@ -5,21 +5,39 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 use std::collections::VecDeque;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::account::AccountStorage;
+use crate::config::Config;
 use crate::error::{ChainError, Result};
 use crate::storage::Storage;
 use crate::transaction::TransactionStorage;
 use crate::world_state::WorldState;
-use ethereum_types::{H256, U64};
+use ethereum_types::{H256, U256, U64};
+use runtime::context::{Context, DebugOutput};
+use runtime::contract::{Execution, RECEIVE};
+use runtime::state::{Event, State};
 use tokio::sync::Mutex;
-use types::account::Account;
-use types::block::{Block, BlockNumber};
+use types::account::{Account, AccountData};
+use types::block::{Block, BlockMetadata, BlockNumber};
 use types::bytes::Bytes;
+use types::helpers::to_hex;
+use types::interface::ContractInterface;
+use types::proof::{AccountProof, StorageProof};
+use types::storage::StorageKeys;
+use types::trace::{DebugMessage, TransactionTrace};
 use types::transaction::{
-    SignedTransaction, Transaction, TransactionKind, TransactionReceipt, TransactionRequest,
+    Log, SignedTransaction, Transaction, TransactionKind, TransactionReceipt, TransactionRequest,
 };
+use utils::crypto::hash;
+
+/// The most storage keys returned in a page
+pub(crate) const MAX_STORAGE_KEYS: usize = 1_000;
+
+/// The deployed contract, events, created contracts and gas used of a transaction
+type Executed = (Option<Account>, Vec<Event>, Vec<Account>, u64);
 
 // TODO(ddimaria): store blocks in a patricia merkle trie
 #[derive(Debug)]
@@ -28,6 +46,11 @@ pub(crate) struct BlockChain {
     pub(crate) blocks: Vec<Block>,
     pub(crate) transactions: Arc<Mutex<TransactionStorage>>,
     pub(crate) world_state: WorldState,
+    pub(crate) config: Config,
+    /// The messages printed by contracts in the transaction being processed
+    pub(crate) debug: DebugOutput,
+    /// The timestamp of the block being built, while its transactions are processed
+    block_timestamp: Option<u64>,
 }
 
 impl BlockChain {
@@ -37,9 +60,23 @@ impl BlockChain {
             blocks: vec![Block::genesis()?],
             transactions: Arc::new(Mutex::new(TransactionStorage::new())),
             world_state: WorldState::new(),
+            config: Config::default(),
+            debug: DebugOutput::default(),
+            block_timestamp: None,
         })
     }
 
+    /// Use the config for the transactions that follow.
+    /// The stack limit is set on the contract engine, which can only be done
+    /// before any contract runs.
+    pub(crate) fn set_config(&mut self, config: Config) -> Result<()> {
+        runtime::limits::set_max_wasm_stack(config.limits.max_wasm_stack)
+            .map_err(|e| ChainError::InvalidConfig(e.to_string()))?;
+        self.config = config;
+
+        Ok(())
+    }
+
     pub(crate) fn get_current_block(&self) -> Result<Block> {
         let block = self
             .blocks
@@ -50,11 +87,12 @@ impl BlockChain {
     }
 
     pub(crate) fn get_block_by_number(&self, block_number: U64) -> Result<Block> {
-        let index = (block_number - 1).as_usize();
+        // the genesis block is block 0, so the block number is the index
+        let index = block_number.as_usize();
         let block = self
             .blocks
             .get(index)
-            .ok_or_else(|| ChainError::BlockNotFound("current block".into()))?;
+            .ok_or_else(|| ChainError::BlockNotFound(block_number.to_string()))?;
 
         Ok(block.to_owned())
     }
@@ -69,26 +107,178 @@ impl BlockChain {
         }
     }
 
+    /// Resolve an account at the given block.
+    /// The current block reads from the live state trie, prior blocks read
+    /// from the state trie at the block's state root.
+    fn get_account_by_block(
+        &self,
+        address: &Account,
+        block_number: &BlockNumber,
+    ) -> Result<AccountData> {
+        let current_block = self.get_current_block()?;
+
+        if **block_number == current_block.number {
+            self.accounts.get_account(address)
+        } else {
+            let block = self.get_block_by_number(**block_number)?;
+            self.accounts.get_account_by_root(address, block.state_root)
+        }
+    }
+
+    /// Resolve the code of a contract account at the given block.
+    pub(crate) fn get_code_by_block(
+        &self,
+        address: &Account,
+        block_number: &BlockNumber,
+    ) -> Result<Bytes> {
+        let account_data = self.get_account_by_block(address, block_number)?;
+
+        self.accounts.get_code(&account_data, address)
+    }
+
+    /// Resolve the interface of a contract's code at the given block.
+    pub(crate) fn get_interface_by_block(
+        &self,
+        address: &Account,
+        block_number: &BlockNumber,
+    ) -> Result<ContractInterface> {
+        let code_hash = self
+            .get_account_by_block(address, block_number)?
+            .code_hash
+            .ok_or_else(|| ChainError::NotAContractAccount(address.to_string()))?;
+
+        self.accounts.code.get_interface(&code_hash)
+    }
+
+    /// Read a value from the storage of a contract at the given block.
+    /// Accounts that never wrote to storage have no values.
+    pub(crate) fn get_storage_by_block(
+        &self,
+        address: &Account,
+        key: &str,
+        block_number: &BlockNumber,
+    ) -> Result<Option<String>> {
+        let account_data = self.get_account_by_block(address, block_number)?;
+
+        self.accounts
+            .contract_storage
+            .get(account_data.storage_root, key)
+    }
+
+    /// List the storage keys of a contract at the given block, in pages of at most
+    /// `MAX_STORAGE_KEYS` keys, starting at `start` if given.
+    pub(crate) fn get_storage_keys_by_block(
+        &self,
+        address: &Account,
+        block_number: &BlockNumber,
+        start: Option<&str>,
+        limit: usize,
+    ) -> Result<StorageKeys> {
+        let account_data = self.get_account_by_block(address, block_number)?;
+
+        self.accounts.contract_storage.keys(
+            account_data.storage_root,
+            start,
+            limit.min(MAX_STORAGE_KEYS),
+        )
+    }
+
+    /// Prove an account and the given keys of its storage against the state root
+    /// of the given block.
+    pub(crate) fn get_proof_by_block(
+        &self,
+        address: &Account,
+        keys: &[String],
+        block_number: &BlockNumber,
+    ) -> Result<AccountProof> {
+        let state_root = self.get_block_by_number(**block_number)?.state_root;
+        let account_proof = self.accounts.get_proof_by_root(address, state_root)?;
+        let account_data = match self.accounts.get_account_by_root(address, state_root) {
+            Err(ChainError::StorageNotFound(_)) => AccountData::new(None),
+            account_data => account_data?,
+        };
+        let contract_storage = &self.accounts.contract_storage;
+        let storage_proof = keys
+            .iter()
+            .map(|key| {
+                let root = account_data.storage_root;
+                let value = contract_storage.get(root, key)?;
+                let proof = contract_storage.proof(root, key)?;
+
+                Ok(StorageProof::new(key, value, proof))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(AccountProof {
+            address: *address,
+            balance: account_data.balance,
+            nonce: account_data.nonce,
+            code_hash: account_data.code_hash,
+            storage_hash: account_data.storage_root,
+            account_proof,
+            storage_proof,
+        })
+    }
+
     pub(crate) fn new_block(
         &mut self,
         transactions: Vec<Transaction>,
+        receipts_root: H256,
         state_trie: H256,
+        gas_used: u64,
     ) -> Result<Block> {
         let current_block = self.get_current_block()?;
         let number = current_block.number + 1_u64;
         let parent_hash = current_block.block_hash()?;
-        let block = Block::new(number, parent_hash, transactions, state_trie)?;
+        let timestamp = match self.block_timestamp.take() {
+            Some(timestamp) => timestamp,
+            None => self.next_timestamp()?,
+        };
+        let metadata = BlockMetadata {
+            author: self.config.author,
+            timestamp: timestamp.into(),
+            gas_limit: self.config.gas_limit.into(),
+            gas_used: gas_used.into(),
+            extra_data: self.config.extra_data.clone(),
+        };
+        let block = Block::new(
+            number,
+            parent_hash,
+            transactions,
+            receipts_root,
+            state_trie,
+            metadata,
+        )?;
 
         self.blocks.push(block);
 
         self.get_block_by_number(number)
     }
 
+    /// The timestamp of the next block, which is never before its parent's
+    fn next_timestamp(&self) -> Result<u64> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        Ok(now.max(self.get_current_block()?.timestamp.as_u64()))
+    }
+
     pub(crate) async fn send_transaction(
         &mut self,
         transaction_request: TransactionRequest,
     ) -> Result<H256> {
         let mut transaction: Transaction = transaction_request.try_into()?;
+
+        // a transaction that can't fit in a block would never be processed
+        if transaction.gas > U256::from(self.config.gas_limit) {
+            return Err(ChainError::ExceedsBlockGasLimit(
+                transaction.gas.to_string(),
+                self.config.gas_limit.to_string(),
+            ));
+        }
+
         let account = self.accounts.get_account(&transaction.from)?;
         let nonce = transaction.nonce.unwrap_or_else(|| account.nonce + 1_u64);
 
@@ -119,7 +309,7 @@ impl BlockChain {
         // Bulk drain the current queue to fit into the new block
         // This is not safe as we lose transactions if a panic occurs
         // or if the program is halted
-        let transactions = self
+        let mut transactions = self
             .transactions
             .lock()
             .await
@@ -129,14 +319,29 @@ impl BlockChain {
 
         if !transactions.is_empty() {
             let mut receipts: Vec<TransactionReceipt> = vec![];
+            let mut traces: Vec<TransactionTrace> = vec![];
             let mut processed: Vec<Transaction> = vec![];
 
+            let mut gas_used = 0_u64;
+            let gas_limit = U256::from(self.config.gas_limit);
+
             tracing::info!("Processing {} transactions", transactions.len());
 
-            for mut transaction in transactions.into_iter() {
+            // the block's timestamp is fixed while its transactions are processed
+            self.block_timestamp = Some(self.next_timestamp()?);
+
+            while let Some(mut transaction) = transactions.pop_front() {
+                // leave the transactions that don't fit for the next block
+                if U256::from(gas_used) + transaction.gas > gas_limit {
+                    transactions.push_front(transaction);
+                    break;
+                }
+
                 match self.process_transaction(&mut transaction) {
-                    Ok((transaction, transaction_receipt)) => {
+                    Ok((transaction, transaction_receipt, transaction_trace)) => {
+                        gas_used += transaction_receipt.gas_used.as_u64();
                         receipts.push(transaction_receipt);
+                        traces.push(transaction_trace);
                         processed.push(transaction.to_owned());
                     }
                     Err(error) => {
@@ -164,14 +369,35 @@ impl BlockChain {
                 }
             }
 
+            // return the transactions left for the next block to the front of the mempool
+            let mut storage = self.transactions.lock().await;
+
+            for transaction in transactions.into_iter().rev() {
+                storage.mempool.push_front(transaction);
+            }
+
+            drop(storage);
+
             // update world state
             let state_trie = self.accounts.root_hash()?;
             self.world_state.update_state_trie(state_trie);
 
             tracing::info!("World State: state_trie {:?}", state_trie);
 
+            // index the logs of the receipts before committing to them in the receipts trie
+            let mut log_index = 0;
+
+            for (transaction_index, receipt) in receipts.iter_mut().enumerate() {
+                for log in receipt.logs.iter_mut() {
+                    log.log_index = Some(log_index.into());
+                    log.transaction_index = Some(to_hex(transaction_index));
+                    log_index += 1;
+                }
+            }
+
+            let receipts_root = TransactionReceipt::root_hash(&receipts)?;
             let num_processed = processed.len();
-            let block = self.new_block(processed, state_trie)?;
+            let block = self.new_block(processed, receipts_root, state_trie, gas_used)?;
 
             tracing::info!(
                 "Created block {} with {} transactions",
@@ -179,11 +405,16 @@ impl BlockChain {
                 num_processed
             );
 
-            // now add the block number and hash to the receipts
+            // now add the block number and hash to the receipts and their logs
             for mut receipt in receipts.into_iter() {
                 receipt.block_number = Some(BlockNumber(block.number));
                 receipt.block_hash = block.hash;
 
+                for log in receipt.logs.iter_mut() {
+                    log.block_hash = block.hash;
+                    log.block_number = Some(block.number);
+                }
+
                 self.transactions
                     .clone()
                     .lock()
@@ -192,6 +423,15 @@ impl BlockChain {
                     .insert(receipt.transaction_hash, receipt);
             }
 
+            for trace in traces {
+                self.transactions
+                    .clone()
+                    .lock()
+                    .await
+                    .traces
+                    .insert(trace.transaction_hash, trace);
+            }
+
             let storage = self.transactions.lock().await;
 
             tracing::info!(
@@ -204,12 +444,10 @@ impl BlockChain {
         Ok(())
     }
 
-    // TODO(ddimaria): handle TransactionKind::ContractExecution
     pub(crate) fn process_transaction<'a>(
         &mut self,
         transaction: &'a mut Transaction,
-    ) -> Result<(&'a mut Transaction, TransactionReceipt)> {
-        let mut contract_address: Option<Account> = None;
+    ) -> Result<(&'a mut Transaction, TransactionReceipt, TransactionTrace)> {
         let transaction_hash = transaction.transaction_hash()?;
 
         // ignore transactions without a nonce
@@ -224,39 +462,61 @@ impl BlockChain {
             // TODO(ddimaria): remove this copy
             let kind = transaction.to_owned().kind()?;
 
-            match kind {
-                TransactionKind::Regular(from, to, value) => {
-                    self.accounts.transfer(&from, &to, value)
-                }
-                TransactionKind::ContractDeployment(from, data) => {
-                    contract_address = self.accounts.add_contract_account(&from, data).ok();
-                    Ok(())
+            // failed contract executions are still included in the block
+            let gas = transaction.gas.min(U256::from(u64::MAX)).as_u64();
+            let executed = self.execute_transaction(kind, nonce, gas);
+            let debug = self.take_debug_output(Some(transaction_hash));
+            let (contract_address, events, created, gas_used, status, error) = match executed {
+                Ok((contract_address, events, created, gas_used)) => (
+                    contract_address,
+                    events,
+                    created,
+                    gas_used,
+                    U64::one(),
+                    None,
+                ),
+                // failed executions don't report the gas they used, so they use all of it
+                Err(ChainError::RuntimeError(address, error)) => {
+                    tracing::warn!("Contract execution failed at {}: {}", address, error);
+                    (None, vec![], vec![], gas, U64::zero(), Some(error))
                 }
-                TransactionKind::ContractExecution(_from, to, data) => {
-                    let code = self
-                        .accounts
-                        .get_account(&to)?
-                        .code_hash
-                        .ok_or_else(|| ChainError::NotAContractAccount(to.to_string()))?;
-                    let (function, params): (&str, Vec<&str>) = bincode::deserialize(&data)?;
-
-                    // call the function in the contract
-                    runtime::contract::call_function(&code, function, &params)
-                        .map_err(|e| ChainError::RuntimeError(to.to_string(), e.to_string()))
+                // so does a deployment colliding with an existing contract
+                Err(error @ ChainError::ContractAlreadyExists(_)) => {
+                    tracing::warn!("Contract deployment failed: {}", error);
+                    (
+                        None,
+                        vec![],
+                        vec![],
+                        gas,
+                        U64::zero(),
+                        Some(error.to_string()),
+                    )
                 }
-            }?;
+                Err(error) => return Err(error),
+            };
 
             // update the nonce
             self.accounts.update_nonce(&transaction.from, nonce)?;
 
+            let logs = events
+                .iter()
+                .enumerate()
+                .map(|(index, event)| to_log(event, transaction_hash, index))
+                .collect::<Result<Vec<_>>>()?;
+
             let transaction_receipt = TransactionReceipt {
                 block_hash: None,
                 block_number: None,
                 contract_address,
                 transaction_hash,
+                status,
+                gas_used: gas_used.into(),
+                logs,
+                error,
             };
+            let transaction_trace = TransactionTrace::new(transaction_hash, debug, created);
 
-            return Ok((transaction, transaction_receipt));
+            return Ok((transaction, transaction_receipt, transaction_trace));
         }
 
         Err(ChainError::MissingTransactionNonce(
@@ -264,6 +524,218 @@ impl BlockChain {
         ))
     }
 
+    /// Apply the transaction to the state, returning the address of a deployed
+    /// contract, the events emitted by contracts, the addresses of the
+    /// contracts created by other contracts and the gas used by contracts.
+    fn execute_transaction(
+        &mut self,
+        kind: TransactionKind,
+        nonce: U256,
+        gas: u64,
+    ) -> Result<Executed> {
+        match kind {
+            TransactionKind::Regular(from, to, value) => {
+                // plain transfers to contracts run the contract's `receive` function
+                if self.accounts.get_account(&to)?.code_hash.is_some() {
+                    let execution = self.call_contract(from, to, value, RECEIVE, &[], gas)?;
+                    let created = self.apply_execution(from, &execution, gas)?;
+
+                    let events = execution.state.events().to_vec();
+
+                    return Ok((None, events, created, execution.gas_used));
+                }
+
+                self.accounts.transfer(&from, &to, value)?;
+                Ok((None, vec![], vec![], 0))
+            }
+            TransactionKind::ContractDeployment(from, deployment) => {
+                let address = AccountStorage::derive_contract_address(&from, nonce, &deployment);
+                let args = deployment
+                    .args
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+
+                // the constructor must not write to the storage of an existing contract
+                if let Ok(AccountData {
+                    code_hash: Some(_), ..
+                }) = self.accounts.get_account(&address)
+                {
+                    return Err(ChainError::ContractAlreadyExists(address.to_string()));
+                }
+
+                let runtime_error = |e: runtime::error::RuntimeError| {
+                    ChainError::RuntimeError(address.to_string(), e.to_string())
+                };
+
+                // invalid code is rejected before anything runs, and a declared
+                // interface must match the exports
+                runtime::validate::validate(&deployment.code).map_err(runtime_error)?;
+
+                let context = self.contract_context(from, address, gas)?;
+                let interface = runtime::contract::interface(
+                    &deployment.code,
+                    context,
+                    deployment.interface.clone(),
+                )
+                .map_err(runtime_error)?;
+
+                // run the constructor before the account is created,
+                // so a trapped constructor leaves no trace of the contract
+                let context = self.contract_context(from, address, gas)?;
+                let execution = runtime::contract::deploy(&deployment.code, context, &args)
+                    .map_err(runtime_error)?;
+
+                let created = self.apply_execution(from, &execution, gas)?;
+
+                let code_hash = H256::from(hash(&deployment.code));
+                let declared = deployment.interface.is_some();
+                let address = self
+                    .accounts
+                    .add_contract_account(&from, nonce, deployment)?;
+                self.accounts
+                    .code
+                    .insert_interface(&code_hash, &interface, declared)?;
+
+                let events = execution.state.events().to_vec();
+
+                Ok((Some(address), events, created, execution.gas_used))
+            }
+            TransactionKind::ContractExecution(from, to, value, data) => {
+                let (function, params): (&str, Vec<&str>) = bincode::deserialize(&data)?;
+                let execution = self.call_contract(from, to, value, function, &params, gas)?;
+
+                // only apply the changes once the whole call has succeeded
+                let created = self.apply_execution(from, &execution, gas)?;
+
+                Ok((
+                    None,
+                    execution.state.events().to_vec(),
+                    created,
+                    execution.gas_used,
+                ))
+            }
+        }
+    }
+
+    /// Apply the changes of a successful execution, deriving the interface of
+    /// the code of each contract created or upgraded during the execution
+    /// unless the code already has one, with the gas of the transaction.
+    /// Returns the addresses of the created contracts.
+    fn apply_execution(
+        &mut self,
+        from: Account,
+        execution: &Execution,
+        gas: u64,
+    ) -> Result<Vec<Account>> {
+        let state = &execution.state;
+        let mut interfaces = vec![];
+
+        // derive the interfaces before changing anything, in case one fails
+        for (address, code) in state.created().iter().chain(state.upgraded()) {
+            let code_hash = H256::from(hash(code));
+
+            if self.accounts.code.has_interface(&code_hash)? {
+                continue;
+            }
+
+            let context = self.contract_context(from, *address, gas)?;
+            let interface = runtime::contract::interface(code, context, None)
+                .map_err(|e| ChainError::RuntimeError(address.to_string(), e.to_string()))?;
+            interfaces.push((code_hash, interface));
+        }
+
+        self.accounts.apply_state(state)?;
+
+        for (code_hash, interface) in interfaces.iter() {
+            self.accounts
+                .code
+                .insert_interface(code_hash, interface, false)?;
+        }
+
+        Ok(state.created().keys().cloned().collect())
+    }
+
+    /// Call a contract function without applying any changes to the state,
+    /// returning the values returned by the function as typed pairs.
+    pub(crate) fn call(
+        &mut self,
+        from: Account,
+        to: Account,
+        data: &Bytes,
+        gas: u64,
+    ) -> Result<Vec<String>> {
+        let (function, params): (&str, Vec<&str>) = bincode::deserialize(data)?;
+        let execution = self.call_contract(from, to, U256::zero(), function, &params, gas);
+        self.take_debug_output(None);
+
+        Ok(execution?.result)
+    }
+
+    /// Take the messages printed by contracts since the last transaction,
+    /// logging each one
+    fn take_debug_output(&mut self, transaction_hash: Option<H256>) -> Vec<DebugMessage> {
+        let debug = match self.debug.lock() {
+            Ok(mut debug) => std::mem::take(&mut *debug),
+            Err(_) => return vec![],
+        };
+
+        for DebugMessage { address, message } in debug.iter() {
+            tracing::info!(
+                "Contract {:?} printed in transaction {:?}: {}",
+                address,
+                transaction_hash,
+                message
+            );
+        }
+
+        debug
+    }
+
+    /// Call a function of a contract, sending the value to the contract before the call
+    fn call_contract(
+        &mut self,
+        from: Account,
+        to: Account,
+        value: U256,
+        function: &str,
+        params: &[&str],
+        gas: u64,
+    ) -> Result<Execution> {
+        let account_data = self.accounts.get_account(&to)?;
+        let code = self.accounts.get_code(&account_data, &to)?;
+        let mut context = self.contract_context(from, to, gas)?;
+        let runtime_error = |e: runtime::error::RuntimeError| {
+            ChainError::RuntimeError(to.to_string(), e.to_string())
+        };
+
+        // the value is part of the call's state, so it's returned if the call reverts
+        context.value = value;
+        context
+            .state
+            .transfer(&from, &to, value)
+            .map_err(runtime_error)?;
+
+        runtime::contract::call_function(&code, context, function, params).map_err(runtime_error)
+    }
+
+    /// The context of a top level contract call, reading from the current state
+    fn contract_context(&mut self, caller: Account, address: Account, gas: u64) -> Result<Context> {
+        let state = State::new(Arc::new(self.accounts.snapshot()?));
+        let mut context = Context::new(caller, address, gas, state);
+
+        // transactions are executed in the block being built
+        context.block_number = self.get_current_block()?.number.as_u64() + 1;
+        context.block_timestamp = match self.block_timestamp {
+            Some(timestamp) => timestamp,
+            None => self.next_timestamp()?,
+        };
+        context.limits = self.config.limits;
+        context.debug = self.config.debug.then(|| self.debug.clone());
+
+        Ok(context)
+    }
+
     pub(crate) async fn get_transaction_receipt(
         &mut self,
         transaction_hash: H256,
@@ -278,18 +750,60 @@ impl BlockChain {
     }
 }
 
+/// Convert an event emitted by a contract into a log.
+/// Topics that are addresses are left padded to 32 bytes, and the other topics,
+/// such as the event name, are hashed.
+/// The data holds the bincode encoded values of the event.
+fn to_log(event: &Event, transaction_hash: H256, index: usize) -> Result<Log> {
+    let topics = event
+        .topics
+        .iter()
+        .map(|topic| match Account::from_str(topic) {
+            Ok(account) => H256::from(account),
+            Err(_) => H256::from(hash(topic.as_bytes())),
+        })
+        .collect();
+
+    Ok(Log {
+        address: event.address,
+        block_hash: None,
+        block_number: None,
+        data: bincode::serialize(&event.data)?.into(),
+        log_index: None,
+        log_type: None,
+        removed: Some(false),
+        topics,
+        transaction_hash: Some(transaction_hash),
+        transaction_index: None,
+        transaction_log_index: Some(index.into()),
+    })
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use ethereum_types::U256;
-    use types::account::AccountData;
-    use utils::crypto::keypair;
+    use types::interface::{EventDefinition, Param};
+    use types::transaction::DeploymentData;
+    use utils::crypto::{contract_address, hash, keypair};
 
     use super::*;
-    use crate::{
-        helpers::tests::{setup, ACCOUNT_1, STORAGE},
-        transaction,
+    use crate::config::GAS_LIMIT;
+    use crate::helpers::tests::{
+        amm_contract, erc1155_contract, erc20_contract, erc721_contract, forwarder_contract,
+        multisig_contract, setup, timelock_contract, ACCOUNT_1, ACCOUNT_2, ACCOUNT_3, STORAGE,
     };
 
+    const ERC20_ARGS: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
+    const ERC1155_ARGS: &[&str] = &["String", "https://rust.assets/{id}.json"];
+    const ERC721_ARGS: &[&str] = &[
+        "String",
+        "Rust Punks",
+        "String",
+        "RPUNK",
+        "String",
+        "https://rust.punks/",
+    ];
+    pub(crate) const GAS: u64 = 10_000_000;
+
     pub(crate) fn new_blockchain() -> BlockChain {
         BlockChain::new((*STORAGE).clone()).unwrap()
     }
@@ -297,22 +811,251 @@ pub(crate) mod tests {
     pub(crate) async fn new_transaction(
         to: Account,
         blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Transaction {
+        new_transaction_from(*ACCOUNT_1, to, blockchain).await
+    }
+
+    pub(crate) async fn new_transaction_from(
+        from: Account,
+        to: Account,
+        blockchain: Arc<Mutex<BlockChain>>,
     ) -> Transaction {
         let nonce = blockchain
             .lock()
             .await
             .accounts
-            .get_account(&ACCOUNT_1)
+            .get_account(&from)
             .unwrap_or(AccountData::new(None))
             .nonce
             + 1;
 
-        let transaction =
-            Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce), None).unwrap();
+        Transaction::new(from, Some(to), U256::from(10), Some(nonce), None).unwrap()
+    }
+
+    pub(crate) async fn new_deployment(
+        code: Bytes,
+        args: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Transaction {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        let deployment = DeploymentData::new(code, args, None);
+        let mut transaction = new_transaction(Account::random(), blockchain).await;
+        transaction.to = None;
+        transaction.data = Some(deployment.encode().unwrap());
+        transaction.gas = U256::from(GAS);
+        transaction
+    }
 
+    pub(crate) async fn new_contract_call(
+        to: Account,
+        function: &str,
+        params: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Transaction {
+        new_contract_call_from(*ACCOUNT_1, to, function, params, blockchain).await
+    }
+
+    pub(crate) async fn new_contract_call_from(
+        from: Account,
+        to: Account,
+        function: &str,
+        params: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Transaction {
+        let mut transaction = new_transaction_from(from, to, blockchain).await;
+        transaction.value = U256::zero();
+        transaction.data = Some(bincode::serialize(&(function, params)).unwrap().into());
+        transaction.gas = U256::from(GAS);
         transaction
     }
 
+    pub(crate) async fn send_and_process(
+        transaction: Transaction,
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> TransactionReceipt {
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        get_receipt(blockchain, transaction_hash).await
+    }
+
+    /// Call a contract function without sending a transaction
+    pub(crate) async fn call(
+        to: Account,
+        function: &str,
+        params: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Result<Vec<String>> {
+        let data = bincode::serialize(&(function, params)).unwrap().into();
+        blockchain.lock().await.call(*ACCOUNT_1, to, &data, GAS)
+    }
+
+    /// Deploy a contract owned by ACCOUNT_1
+    pub(crate) async fn deploy_contract(
+        code: Bytes,
+        args: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Account {
+        let deployment = new_deployment(code, args, blockchain.clone()).await;
+
+        send_and_process(deployment, blockchain)
+            .await
+            .contract_address
+            .unwrap()
+    }
+
+    pub(crate) async fn deploy_erc20(blockchain: Arc<Mutex<BlockChain>>) -> Account {
+        deploy_contract(erc20_contract(), ERC20_ARGS, blockchain).await
+    }
+
+    pub(crate) async fn deploy_erc721(blockchain: Arc<Mutex<BlockChain>>) -> Account {
+        deploy_contract(erc721_contract(), ERC721_ARGS, blockchain).await
+    }
+
+    /// Deploy an erc1155 contract, minting 100 fungible tokens of id 1 and
+    /// the non-fungible token of id 2 to ACCOUNT_1
+    pub(crate) async fn deploy_erc1155(blockchain: Arc<Mutex<BlockChain>>) -> Account {
+        let contract = deploy_contract(erc1155_contract(), ERC1155_ARGS, blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+
+        for (function, params) in [
+            ("mint", vec!["String", &account_1, "U64", "1", "U64", "100"]),
+            ("mint-non-fungible", vec!["String", &account_1, "U64", "2"]),
+        ] {
+            let transaction =
+                new_contract_call(contract, function, &params, blockchain.clone()).await;
+            let receipt = send_and_process(transaction, blockchain.clone()).await;
+            assert_eq!(receipt.status, U64::one());
+        }
+
+        contract
+    }
+
+    async fn erc1155_balances(
+        contract: Account,
+        accounts: &[&Account],
+        ids: &str,
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Vec<String> {
+        let accounts = accounts
+            .iter()
+            .map(|account| to_hex(**account))
+            .collect::<Vec<_>>();
+        let accounts = serde_json::to_string(&accounts).unwrap();
+        let params = ["List<String>", &accounts, "List<U64>", ids];
+
+        call(contract, "balance-of-batch", &params, blockchain)
+            .await
+            .unwrap()
+    }
+
+    /// Deploy a multisig wallet requiring 2 confirmations from ACCOUNT_1, ACCOUNT_2
+    /// and ACCOUNT_3, funding the wallet and the other owners
+    pub(crate) async fn deploy_multisig(blockchain: Arc<Mutex<BlockChain>>) -> Account {
+        let owners = [*ACCOUNT_1, *ACCOUNT_2, *ACCOUNT_3].map(to_hex);
+        let owners = serde_json::to_string(&owners).unwrap();
+        let args = ["List<String>", &owners, "U64", "2"];
+        let contract = deploy_contract(multisig_contract(), &args, blockchain.clone()).await;
+
+        for account in [contract, *ACCOUNT_2, *ACCOUNT_3] {
+            fund(account, blockchain.clone()).await;
+        }
+
+        contract
+    }
+
+    /// Send a multisig transaction from an owner, returning the receipt status
+    async fn multisig_send(
+        from: Account,
+        contract: Account,
+        function: &str,
+        params: &[&str],
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> U64 {
+        let transaction =
+            new_contract_call_from(from, contract, function, params, blockchain.clone()).await;
+
+        send_and_process(transaction, blockchain).await.status
+    }
+
+    /// Deploy an amm pool with a fee of 0.3%, minting 1,000,000 tokens to ACCOUNT_1,
+    /// which adds 10,000 coin and 40,000 tokens of liquidity
+    pub(crate) async fn deploy_amm(blockchain: Arc<Mutex<BlockChain>>) -> Account {
+        let contract = deploy_contract(amm_contract(), &["U64", "30"], blockchain.clone()).await;
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "1000000"];
+        let liquidity = ["U64", "40000", "U64", "0"];
+
+        assert_eq!(
+            send_with_value(contract, "mint", &mint, 0, blockchain.clone()).await,
+            U64::one()
+        );
+        assert_eq!(
+            send_with_value(contract, "add-liquidity", &liquidity, 10_000, blockchain).await,
+            U64::one()
+        );
+
+        contract
+    }
+
+    /// Send a contract call from ACCOUNT_1 with value, returning the receipt status
+    async fn send_with_value(
+        contract: Account,
+        function: &str,
+        params: &[&str],
+        value: u64,
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> U64 {
+        let mut transaction =
+            new_contract_call(contract, function, params, blockchain.clone()).await;
+        transaction.value = U256::from(value);
+
+        send_and_process(transaction, blockchain).await.status
+    }
+
+    /// Send enough value to an account for it to send transactions
+    /// Send 10 from ACCOUNT_1, with enough gas for contracts to receive it
+    pub(crate) async fn fund(account: Account, blockchain: Arc<Mutex<BlockChain>>) {
+        let mut transaction = new_transaction(account, blockchain.clone()).await;
+        transaction.gas = U256::from(GAS);
+
+        send_and_process(transaction, blockchain).await;
+    }
+
+    async fn erc20_balance(
+        contract: Account,
+        account: &Account,
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Vec<String> {
+        call(
+            contract,
+            "balance-of",
+            &["String", &to_hex(*account)],
+            blockchain,
+        )
+        .await
+        .unwrap()
+    }
+
+    pub(crate) async fn get_receipt(
+        blockchain: Arc<Mutex<BlockChain>>,
+        transaction_hash: H256,
+    ) -> TransactionReceipt {
+        process_transactions(blockchain.clone()).await;
+
+        blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash)
+            .unwrap()
+    }
+
     pub(crate) async fn process_transactions(blockchain: Arc<Mutex<BlockChain>>) {
         blockchain
             .lock()
@@ -325,7 +1068,7 @@ pub(crate) mod tests {
     pub(crate) async fn assert_receipt(blockchain: Arc<Mutex<BlockChain>>, transaction_hash: H256) {
         process_transactions(blockchain.clone()).await;
 
-        let receipt = blockchain
+        blockchain
             .lock()
             .await
             .transactions
@@ -335,6 +1078,17 @@ pub(crate) mod tests {
             .unwrap();
     }
 
+    /// The interface of a contract's code at the latest block
+    pub(crate) async fn get_interface(
+        blockchain: Arc<Mutex<BlockChain>>,
+        contract: &Account,
+    ) -> Result<ContractInterface> {
+        let blockchain = blockchain.lock().await;
+        let block_number = blockchain.parse_block_number("latest")?;
+
+        blockchain.get_interface_by_block(contract, &block_number)
+    }
+
     pub(crate) async fn get_balance(blockchain: Arc<Mutex<BlockChain>>, account: &Account) -> U256 {
         blockchain
             .lock()
@@ -355,16 +1109,1432 @@ pub(crate) mod tests {
         let (blockchain, _, _) = setup().await;
         let block_number = blockchain.lock().await.get_current_block().unwrap().number;
         let transaction = new_transaction(Account::random(), blockchain.clone()).await;
-        let response = blockchain
-            .lock()
-            .await
-            .new_block(vec![transaction], H256::zero());
+        let response =
+            blockchain
+                .lock()
+                .await
+                .new_block(vec![transaction], H256::zero(), H256::zero(), 0);
         assert!(response.is_ok());
 
         let new_block_number = blockchain.lock().await.get_current_block().unwrap().number;
         assert_eq!(new_block_number, block_number + 1);
     }
 
+    #[tokio::test]
+    async fn blocks_are_filled_in_by_the_producer() {
+        let (blockchain, _, _) = setup().await;
+        let author = Account::random();
+        blockchain.lock().await.config.author = author;
+        blockchain.lock().await.config.extra_data = "rust-blockchain".into();
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let parent = blockchain.lock().await.get_current_block().unwrap();
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "10"];
+        let mint = new_contract_call(contract, "mint", &mint, blockchain.clone()).await;
+        let receipt = send_and_process(mint, blockchain.clone()).await;
+        let block = blockchain.lock().await.get_current_block().unwrap();
+
+        assert_eq!(block.author, author);
+        assert_eq!(block.extra_data, "rust-blockchain".as_bytes());
+        assert_eq!(block.gas_limit, U64::from(GAS_LIMIT));
+        assert_eq!(block.gas_used, receipt.gas_used);
+        assert!(!block.gas_used.is_zero());
+        assert!(block.timestamp >= parent.timestamp);
+        assert!(!block.timestamp.is_zero());
+        assert_eq!(block.difficulty, U256::zero());
+        assert!(!block.size.is_zero());
+
+        let mut tampered = block.clone();
+        tampered.timestamp += U64::one();
+        assert_ne!(tampered.compute_hash().unwrap(), block.hash.unwrap());
+    }
+
+    #[tokio::test]
+    async fn blocks_are_limited_by_their_gas() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        blockchain.lock().await.config.gas_limit = GAS;
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "10"];
+        let mint_1 = new_contract_call(contract, "mint", &mint, blockchain.clone()).await;
+        let mut mint_2 = mint_1.clone();
+        mint_2.nonce = mint_1.nonce.map(|nonce| nonce + 1);
+        let mut too_much_gas = mint_1.clone();
+        too_much_gas.gas = U256::from(GAS + 1);
+        let mut blockchain_lock = blockchain.lock().await;
+        let sent = blockchain_lock.send_transaction(too_much_gas.into()).await;
+        let hash_1 = blockchain_lock
+            .send_transaction(mint_1.into())
+            .await
+            .unwrap();
+        let hash_2 = blockchain_lock
+            .send_transaction(mint_2.into())
+            .await
+            .unwrap();
+        drop(blockchain_lock);
+
+        assert!(matches!(sent, Err(ChainError::ExceedsBlockGasLimit(_, _))));
+
+        // the second mint doesn't fit in the gas left in the block
+        let receipt_1 = get_receipt(blockchain.clone(), hash_1).await;
+        let pending = blockchain
+            .lock()
+            .await
+            .get_transaction_receipt(hash_2)
+            .await;
+        assert!(pending.is_err());
+
+        let receipt_2 = get_receipt(blockchain.clone(), hash_2).await;
+        let block_1 = *receipt_1.block_number.unwrap();
+        assert_eq!(*receipt_2.block_number.unwrap(), block_1 + 1);
+    }
+
+    #[tokio::test]
+    async fn blocks_commit_to_their_receipts() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "10"];
+        let mint = new_contract_call(contract, "mint", &mint, blockchain.clone()).await;
+        let receipt = send_and_process(mint, blockchain.clone()).await;
+        let block = blockchain
+            .lock()
+            .await
+            .get_block_by_number(*receipt.block_number.clone().unwrap())
+            .unwrap();
+
+        assert!(!receipt.logs.is_empty());
+        assert_eq!(receipt.logs[0].block_hash, block.hash);
+        assert_eq!(
+            block.receipts_root,
+            TransactionReceipt::root_hash(std::slice::from_ref(&receipt)).unwrap()
+        );
+
+        let mut tampered = receipt;
+        tampered.status = U64::zero();
+        assert_ne!(
+            block.receipts_root,
+            TransactionReceipt::root_hash(&[tampered]).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_code_by_block() {
+        let (blockchain, _, _) = setup().await;
+        let code = erc20_contract();
+        let transaction = new_deployment(erc20_contract(), ERC20_ARGS, blockchain.clone()).await;
+        let nonce = transaction.nonce.unwrap();
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let receipt = get_receipt(blockchain.clone(), transaction_hash).await;
+        let blockchain = blockchain.lock().await;
+        let contract_address = receipt.contract_address.unwrap();
+        let block_number = receipt.block_number.unwrap();
+        assert_eq!(
+            contract_address,
+            utils::crypto::contract_address(&ACCOUNT_1, nonce)
+        );
+
+        let code_at_block = blockchain
+            .get_code_by_block(&contract_address, &block_number)
+            .unwrap();
+        let code_before_deployment =
+            blockchain.get_code_by_block(&contract_address, &BlockNumber(*block_number - 1));
+
+        assert_eq!(code_at_block, code);
+        assert!(code_before_deployment.is_err());
+    }
+
+    #[tokio::test]
+    async fn a_trapped_constructor_fails_the_deployment() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_deployment(
+            erc20_contract(),
+            &["String", "", "String", ""],
+            blockchain.clone(),
+        )
+        .await;
+        let nonce = transaction.nonce.unwrap();
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let receipt = get_receipt(blockchain.clone(), transaction_hash).await;
+        let contract_address = utils::crypto::contract_address(&ACCOUNT_1, nonce);
+        let blockchain = blockchain.lock().await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(receipt.contract_address, None);
+        assert!(receipt.error.is_some());
+        assert!(blockchain.accounts.get_account(&contract_address).is_err());
+        assert_eq!(
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce,
+            nonce
+        );
+    }
+
+    #[tokio::test]
+    async fn a_salted_deployment_cannot_collide_with_a_contract() {
+        let (blockchain, _, _) = setup().await;
+        let salt = H256::random();
+        let args = ERC20_ARGS.iter().map(|arg| arg.to_string()).collect();
+        let deployment = DeploymentData::new(erc20_contract(), args, Some(salt));
+        let mut receipts = vec![];
+        let mut nonce = U256::zero();
+
+        for _ in 0..2 {
+            let mut transaction = new_deployment(erc20_contract(), &[], blockchain.clone()).await;
+            transaction.data = Some(deployment.encode().unwrap());
+            nonce = transaction.nonce.unwrap();
+            receipts.push(send_and_process(transaction, blockchain.clone()).await);
+        }
+
+        assert_eq!(receipts[0].status, U64::one());
+
+        // the collision is included in the block, using the gas and the nonce
+        assert_eq!(receipts[1].status, U64::zero());
+        assert_eq!(receipts[1].contract_address, None);
+        assert_eq!(receipts[1].gas_used, U64::from(GAS));
+        assert!(receipts[1]
+            .error
+            .as_ref()
+            .unwrap()
+            .ends_with("already exists"));
+        assert_eq!(
+            blockchain
+                .lock()
+                .await
+                .accounts
+                .get_account(&ACCOUNT_1)
+                .unwrap()
+                .nonce,
+            nonce
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_code_is_rejected_at_deployment() {
+        let (blockchain, _, _) = setup().await;
+        let malformed = new_deployment(vec![0, 1].into(), &[], blockchain.clone()).await;
+        let malformed = send_and_process(malformed, blockchain.clone()).await;
+        let oversized = vec![0; runtime::validate::MAX_CODE_SIZE + 1];
+        let oversized = new_deployment(oversized.into(), &[], blockchain.clone()).await;
+        let oversized = send_and_process(oversized, blockchain).await;
+
+        for receipt in [&malformed, &oversized] {
+            assert_eq!(receipt.status, U64::zero());
+            assert_eq!(receipt.contract_address, None);
+        }
+
+        assert!(malformed
+            .error
+            .unwrap()
+            .starts_with("Invalid contract code"));
+        assert!(oversized.error.unwrap().contains("over the limit"));
+    }
+
+    #[tokio::test]
+    async fn plain_transfers_run_the_receive_function() {
+        let (blockchain, _, _) = setup().await;
+        let multisig = deploy_multisig(blockchain.clone()).await;
+        let erc20 = deploy_erc20(blockchain.clone()).await;
+
+        let mut deposit = new_transaction(multisig, blockchain.clone()).await;
+        deposit.gas = U256::from(GAS);
+        let receipt = send_and_process(deposit, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics[0], H256::from(hash(b"Deposit")));
+        assert_eq!(
+            get_balance(blockchain.clone(), &multisig).await,
+            U256::from(20)
+        );
+
+        // contracts without a `receive` function reject plain transfers
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+        let mut transfer = new_transaction(erc20, blockchain.clone()).await;
+        transfer.gas = U256::from(GAS);
+        let receipt = send_and_process(transfer, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::zero());
+        assert!(receipt.error.unwrap().contains("does not accept value"));
+        assert_eq!(get_balance(blockchain.clone(), &erc20).await, U256::zero());
+        assert_eq!(get_balance(blockchain, &ACCOUNT_1).await, balance);
+    }
+
+    #[tokio::test]
+    async fn contracts_are_limited_by_the_config() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let mut config = Config::default();
+        config.limits.memory_pages = 1;
+        blockchain.lock().await.set_config(config).unwrap();
+
+        let mint = new_contract_call(contract, "mint", &[], blockchain.clone()).await;
+        let receipt = send_and_process(mint, blockchain).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert!(receipt
+            .error
+            .unwrap()
+            .starts_with("Resource limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn debug_output_is_added_to_the_trace_in_debug_mode() {
+        let (blockchain, _, _) = setup().await;
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        let trace = |receipt: TransactionReceipt| {
+            let blockchain = blockchain.clone();
+            async move {
+                blockchain
+                    .lock()
+                    .await
+                    .transactions
+                    .lock()
+                    .await
+                    .get_transaction_trace(&receipt.transaction_hash)
+                    .unwrap()
+                    .debug
+            }
+        };
+
+        let fail = new_contract_call(forwarder, "fail", &[], blockchain.clone()).await;
+        let receipt = send_and_process(fail, blockchain.clone()).await;
+        assert!(trace(receipt).await.is_empty());
+
+        let config = Config {
+            debug: true,
+            ..Config::default()
+        };
+        blockchain.lock().await.set_config(config).unwrap();
+
+        // the output of a reverted call is kept
+        let fail = new_contract_call(forwarder, "fail", &[], blockchain.clone()).await;
+        let receipt = send_and_process(fail, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(
+            trace(receipt).await,
+            [DebugMessage::new(forwarder, "failing")]
+        );
+    }
+
+    #[tokio::test]
+    async fn contracts_create_contracts() {
+        let (blockchain, _, _) = setup().await;
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        deploy_erc20(blockchain.clone()).await;
+        let code_hash = format!("{:?}", H256::from(hash(&erc20_contract())));
+        let args = serde_json::to_string(ERC20_ARGS).unwrap();
+        let params = ["String", &code_hash, "List<String>", &args, "String", ""];
+        let create = new_contract_call(forwarder, "create", &params, blockchain.clone()).await;
+        let receipt = send_and_process(create, blockchain.clone()).await;
+        let address = contract_address(&forwarder, U256::one());
+        assert_eq!(receipt.status, U64::one());
+
+        let trace = blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .get_transaction_trace(&receipt.transaction_hash)
+            .unwrap();
+        assert_eq!(trace.created, [address]);
+
+        assert_eq!(
+            call(address, "name", &[], blockchain.clone())
+                .await
+                .unwrap(),
+            ["String", "Rust Coin"]
+        );
+        assert!(get_interface(blockchain.clone(), &address)
+            .await
+            .unwrap()
+            .function("transfer")
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn upgrades_and_self_destructs_are_kept_in_the_code_history() {
+        let (blockchain, _, _) = setup().await;
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        deploy_erc20(blockchain.clone()).await;
+        let code_hash = format!("{:?}", H256::from(hash(&erc20_contract())));
+        let code_at = |contract: Account, block: u64| {
+            let blockchain = blockchain.clone();
+            async move {
+                blockchain
+                    .lock()
+                    .await
+                    .get_code_by_block(&contract, &BlockNumber(block.into()))
+            }
+        };
+
+        let upgrade = new_contract_call(
+            forwarder,
+            "upgrade",
+            &["String", &code_hash],
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(upgrade, blockchain.clone()).await;
+        let upgraded_at = blockchain
+            .lock()
+            .await
+            .get_current_block()
+            .unwrap()
+            .number
+            .as_u64();
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics.len(), 2);
+        assert_eq!(
+            code_at(forwarder, upgraded_at).await.unwrap(),
+            erc20_contract()
+        );
+        assert_eq!(
+            code_at(forwarder, upgraded_at - 1).await.unwrap(),
+            forwarder_contract()
+        );
+        assert!(get_interface(blockchain.clone(), &forwarder)
+            .await
+            .unwrap()
+            .function("transfer")
+            .is_some());
+
+        // a destroyed contract sends its balance to the beneficiary
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        fund(forwarder, blockchain.clone()).await;
+        let beneficiary = Account::random();
+        let destroy = new_contract_call(
+            forwarder,
+            "destroy",
+            &["String", &to_hex(beneficiary)],
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(destroy, blockchain.clone()).await;
+        let destroyed_at = blockchain
+            .lock()
+            .await
+            .get_current_block()
+            .unwrap()
+            .number
+            .as_u64();
+        let account = blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&forwarder)
+            .unwrap();
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(account.code_hash, None);
+        assert_eq!(account.storage_root, None);
+        assert_eq!(account.balance, U256::zero());
+        assert_eq!(
+            get_balance(blockchain.clone(), &beneficiary).await,
+            U256::from(10)
+        );
+        assert!(code_at(forwarder, destroyed_at).await.is_err());
+        assert_eq!(
+            code_at(forwarder, destroyed_at - 1).await.unwrap(),
+            forwarder_contract()
+        );
+
+        // the interface goes with the code
+        let interface_at = |block: u64| {
+            let blockchain = blockchain.clone();
+            async move {
+                blockchain
+                    .lock()
+                    .await
+                    .get_interface_by_block(&forwarder, &BlockNumber(block.into()))
+            }
+        };
+        assert!(interface_at(destroyed_at).await.is_err());
+        assert!(interface_at(destroyed_at - 1)
+            .await
+            .unwrap()
+            .function("forward")
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn upgrades_keep_the_declared_interface_of_the_code() {
+        let (blockchain, _, _) = setup().await;
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        let timelock = deploy_contract(timelock_contract(), &[], blockchain.clone()).await;
+        let derived = get_interface(blockchain.clone(), &timelock).await.unwrap();
+        let declared = ContractInterface::new(
+            derived.functions,
+            vec![EventDefinition::new(
+                "Withdrawal",
+                vec![Param::new("account", "string")],
+                vec![Param::new("amount", "u64")],
+            )],
+        );
+
+        // declaring the interface of code replaces the one derived from its exports
+        let mut transaction = new_deployment(timelock_contract(), &[], blockchain.clone()).await;
+        let deployment =
+            DeploymentData::new(timelock_contract(), vec![], None).with_interface(declared.clone());
+        transaction.data = Some(deployment.encode().unwrap());
+        assert_eq!(
+            send_and_process(transaction, blockchain.clone())
+                .await
+                .status,
+            U64::one()
+        );
+
+        let code_hash = format!("{:?}", H256::from(hash(&timelock_contract())));
+        let upgrade = new_contract_call(
+            forwarder,
+            "upgrade",
+            &["String", &code_hash],
+            blockchain.clone(),
+        )
+        .await;
+        assert_eq!(
+            send_and_process(upgrade, blockchain.clone()).await.status,
+            U64::one()
+        );
+        assert_eq!(
+            get_interface(blockchain.clone(), &forwarder).await.unwrap(),
+            declared
+        );
+        assert_eq!(
+            get_interface(blockchain, &timelock).await.unwrap(),
+            declared
+        );
+    }
+
+    #[tokio::test]
+    async fn contracts_store_their_interface() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let interface = get_interface(blockchain.clone(), &contract).await.unwrap();
+        let declared = ContractInterface::new(
+            interface.functions.clone(),
+            vec![EventDefinition::new(
+                "Transfer",
+                vec![Param::new("from", "string"), Param::new("to", "string")],
+                vec![Param::new("amount", "u64")],
+            )],
+        );
+        let mut mismatched = declared.clone();
+        mismatched.functions.pop();
+
+        let deploy_with = |interface: ContractInterface| {
+            let blockchain = blockchain.clone();
+            async move {
+                let args = ERC20_ARGS.iter().map(|arg| arg.to_string()).collect();
+                let deployment =
+                    DeploymentData::new(erc20_contract(), args, None).with_interface(interface);
+                let mut transaction =
+                    new_deployment(erc20_contract(), &[], blockchain.clone()).await;
+                transaction.data = Some(deployment.encode().unwrap());
+                send_and_process(transaction, blockchain).await
+            }
+        };
+        let receipt = deploy_with(declared.clone()).await;
+        let mismatched_receipt = deploy_with(mismatched).await;
+        let stored = get_interface(blockchain.clone(), &receipt.contract_address.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(interface.functions.len(), 11);
+        assert!(interface.function("balance-of").is_some());
+        assert_eq!(stored, declared);
+        assert_eq!(mismatched_receipt.status, U64::zero());
+        assert_eq!(mismatched_receipt.contract_address, None);
+    }
+
+    #[tokio::test]
+    async fn the_constructor_cannot_be_called_after_deployment() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_deployment(erc20_contract(), ERC20_ARGS, blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let receipt = get_receipt(blockchain.clone(), transaction_hash).await;
+        let contract_address = receipt.contract_address.unwrap();
+        let transaction = new_contract_call(
+            contract_address,
+            "construct",
+            ERC20_ARGS,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+    }
+
+    #[tokio::test]
+    async fn contracts_call_other_contracts() {
+        let (blockchain, _, _) = setup().await;
+        let deployment = new_deployment(forwarder_contract(), &[], blockchain.clone()).await;
+        let forwarder_1 = send_and_process(deployment, blockchain.clone())
+            .await
+            .contract_address
+            .unwrap();
+        let deployment = new_deployment(forwarder_contract(), &[], blockchain.clone()).await;
+        let forwarder_2 = send_and_process(deployment, blockchain.clone())
+            .await
+            .contract_address
+            .unwrap();
+
+        // fund the first forwarder
+        let mut transaction = new_transaction(forwarder_1, blockchain.clone()).await;
+        transaction.value = U256::from(100);
+        transaction.gas = U256::from(GAS);
+        send_and_process(transaction, blockchain.clone()).await;
+
+        let forwarder_2_hex = to_hex(forwarder_2);
+        let params = [
+            "String",
+            &forwarder_2_hex,
+            "String",
+            "echo",
+            "List<String>",
+            r#"["String","hi"]"#,
+            "U64",
+            "10",
+            "U64",
+            "1000000",
+        ];
+        let transaction =
+            new_contract_call(forwarder_1, "forward", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(
+            get_balance(blockchain.clone(), &forwarder_1).await,
+            U256::from(90)
+        );
+        assert_eq!(
+            get_balance(blockchain.clone(), &forwarder_2).await,
+            U256::from(10)
+        );
+
+        // a reverted callee reverts the caller, leaving the balances untouched
+        let params = [
+            "String",
+            &forwarder_2_hex,
+            "String",
+            "fail",
+            "List<String>",
+            "[]",
+            "U64",
+            "10",
+            "U64",
+            "1000000",
+        ];
+        let transaction =
+            new_contract_call(forwarder_1, "forward", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(
+            get_balance(blockchain.clone(), &forwarder_1).await,
+            U256::from(90)
+        );
+        assert_eq!(
+            get_balance(blockchain.clone(), &forwarder_2).await,
+            U256::from(10)
+        );
+    }
+
+    #[tokio::test]
+    async fn erc20_mints_and_transfers_tokens() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+
+        let params = ["String", &account_1, "U64", "100"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs.len(), 1);
+
+        let log = &receipt.logs[0];
+        let data: Vec<String> = bincode::deserialize(&log.data).unwrap();
+        assert_eq!(log.address, contract);
+        assert_eq!(log.block_number, receipt.block_number.map(|number| *number));
+        assert_eq!(log.topics[0], H256::from(hash(b"Transfer")));
+        assert_eq!(log.topics[1], H256::zero());
+        assert_eq!(log.topics[2], H256::from(*ACCOUNT_1));
+        assert_eq!(data, ["100"]);
+
+        let params = ["String", &account_2, "U64", "40"];
+        let transaction =
+            new_contract_call(contract, "transfer", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics[2], H256::from(*ACCOUNT_2));
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_1, blockchain.clone()).await,
+            ["U64", "60"]
+        );
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_2, blockchain.clone()).await,
+            ["U64", "40"]
+        );
+        assert_eq!(
+            call(contract, "total-supply", &[], blockchain.clone())
+                .await
+                .unwrap(),
+            ["U64", "100"]
+        );
+        assert_eq!(
+            call(contract, "owner", &[], blockchain).await.unwrap(),
+            ["String", &account_1]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc20_mint_is_restricted_to_the_owner() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+
+        fund(*ACCOUNT_2, blockchain.clone()).await;
+
+        let account_2 = to_hex(*ACCOUNT_2);
+        let params = ["String", &account_2, "U64", "100"];
+        let transaction =
+            new_contract_call_from(*ACCOUNT_2, contract, "mint", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert!(receipt.logs.is_empty());
+        assert_eq!(
+            call(contract, "total-supply", &[], blockchain)
+                .await
+                .unwrap(),
+            ["U64", "0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc20_rejects_transfers_exceeding_the_balance() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+
+        let params = ["String", &account_1, "U64", "10"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        send_and_process(transaction, blockchain.clone()).await;
+
+        let params = ["String", &account_2, "U64", "11"];
+        let response = call(contract, "transfer", &params, blockchain.clone()).await;
+        assert!(
+            matches!(response, Err(ChainError::RuntimeError(_, error)) if error.contains("insufficient balance"))
+        );
+
+        let transaction =
+            new_contract_call(contract, "transfer", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_1, blockchain.clone()).await,
+            ["U64", "10"]
+        );
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_2, blockchain).await,
+            ["U64", "0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc20_transfers_from_an_allowance() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+        let account_3 = to_hex(*ACCOUNT_3);
+
+        fund(*ACCOUNT_2, blockchain.clone()).await;
+
+        let params = ["String", &account_1, "U64", "100"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        send_and_process(transaction, blockchain.clone()).await;
+
+        let params = ["String", &account_2, "U64", "30"];
+        let transaction = new_contract_call(contract, "approve", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        assert_eq!(receipt.logs[0].topics[0], H256::from(hash(b"Approval")));
+
+        // the spender moves tokens from the owner's balance
+        let params = ["String", &account_1, "String", &account_3, "U64", "25"];
+        let transaction = new_contract_call_from(
+            *ACCOUNT_2,
+            contract,
+            "transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_3, blockchain.clone()).await,
+            ["U64", "25"]
+        );
+        assert_eq!(
+            call(
+                contract,
+                "allowance",
+                &["String", &account_1, "String", &account_2],
+                blockchain.clone()
+            )
+            .await
+            .unwrap(),
+            ["U64", "5"]
+        );
+
+        // the rest of the allowance can't cover another transfer
+        let params = ["String", &account_1, "String", &account_3, "U64", "10"];
+        let transaction = new_contract_call_from(
+            *ACCOUNT_2,
+            contract,
+            "transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(
+            erc20_balance(contract, &ACCOUNT_1, blockchain).await,
+            ["U64", "75"]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc721_mints_and_transfers_tokens() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc721(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+
+        let params = ["String", &account_1, "U64", "1"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics[0], H256::from(hash(b"Transfer")));
+        assert_eq!(receipt.logs[0].topics[2], H256::from(*ACCOUNT_1));
+
+        // a token can only be minted once
+        let receipt = send_and_process(
+            new_contract_call(contract, "mint", &params, blockchain.clone()).await,
+            blockchain.clone(),
+        )
+        .await;
+        assert_eq!(receipt.status, U64::zero());
+
+        let params = ["String", &account_1, "String", &account_2, "U64", "1"];
+        let transaction =
+            new_contract_call(contract, "transfer-from", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics[1], H256::from(*ACCOUNT_1));
+        assert_eq!(receipt.logs[0].topics[2], H256::from(*ACCOUNT_2));
+        assert_eq!(
+            call(contract, "owner-of", &["U64", "1"], blockchain.clone())
+                .await
+                .unwrap(),
+            ["String", &account_2]
+        );
+        assert_eq!(
+            call(
+                contract,
+                "balance-of",
+                &["String", &account_1],
+                blockchain.clone()
+            )
+            .await
+            .unwrap(),
+            ["U64", "0"]
+        );
+        assert_eq!(
+            call(
+                contract,
+                "balance-of",
+                &["String", &account_2],
+                blockchain.clone()
+            )
+            .await
+            .unwrap(),
+            ["U64", "1"]
+        );
+        assert_eq!(
+            call(contract, "token-uri", &["U64", "1"], blockchain.clone())
+                .await
+                .unwrap(),
+            ["String", "https://rust.punks/1"]
+        );
+        assert!(call(contract, "owner-of", &["U64", "2"], blockchain)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn erc721_transfers_require_approval() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc721(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+        let account_3 = to_hex(*ACCOUNT_3);
+
+        fund(*ACCOUNT_2, blockchain.clone()).await;
+
+        let params = ["String", &account_2, "U64", "7"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        send_and_process(transaction, blockchain.clone()).await;
+
+        // the contract owner doesn't own the token
+        let params = ["String", &account_2, "String", &account_3, "U64", "7"];
+        let transaction =
+            new_contract_call(contract, "transfer-from", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::zero());
+
+        let params = ["String", &account_1, "Bool", "true"];
+        let transaction = new_contract_call_from(
+            *ACCOUNT_2,
+            contract,
+            "set-approval-for-all",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        assert_eq!(
+            receipt.logs[0].topics[0],
+            H256::from(hash(b"ApprovalForAll"))
+        );
+        assert_eq!(
+            call(
+                contract,
+                "is-approved-for-all",
+                &["String", &account_2, "String", &account_1],
+                blockchain.clone()
+            )
+            .await
+            .unwrap(),
+            ["Bool", "true"]
+        );
+
+        // the operator can now move the token
+        let params = ["String", &account_2, "String", &account_3, "U64", "7"];
+        let transaction =
+            new_contract_call(contract, "transfer-from", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(
+            call(contract, "owner-of", &["U64", "7"], blockchain)
+                .await
+                .unwrap(),
+            ["String", &account_3]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc721_safe_transfers_check_the_recipient() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc721(blockchain.clone()).await;
+        let forwarder = deploy_contract(forwarder_contract(), &[], blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+        let forwarder = to_hex(forwarder);
+
+        let params = ["String", &account_1, "U64", "1"];
+        let transaction = new_contract_call(contract, "mint", &params, blockchain.clone()).await;
+        send_and_process(transaction, blockchain.clone()).await;
+
+        // the forwarder doesn't export `on-erc721-received`
+        let params = ["String", &account_1, "String", &forwarder, "U64", "1"];
+        let transaction =
+            new_contract_call(contract, "safe-transfer-from", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::zero());
+
+        let params = ["String", &account_1, "String", &account_2, "U64", "1"];
+        let transaction =
+            new_contract_call(contract, "safe-transfer-from", &params, blockchain.clone()).await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(
+            call(contract, "owner-of", &["U64", "1"], blockchain)
+                .await
+                .unwrap(),
+            ["String", &account_2]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc1155_transfers_a_batch() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc1155(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+
+        assert_eq!(
+            call(
+                contract,
+                "is-non-fungible",
+                &["U64", "2"],
+                blockchain.clone()
+            )
+            .await
+            .unwrap(),
+            ["Bool", "true"]
+        );
+
+        let params = [
+            "String",
+            &account_1,
+            "String",
+            &account_2,
+            "List<U64>",
+            "[1,2]",
+            "List<U64>",
+            "[40,1]",
+        ];
+        let transaction = new_contract_call(
+            contract,
+            "safe-batch-transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        let data: Vec<String> = bincode::deserialize(&receipt.logs[0].data).unwrap();
+
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(
+            receipt.logs[0].topics[0],
+            H256::from(hash(b"TransferBatch"))
+        );
+        assert_eq!(data, ["[1,2]", "[40,1]"]);
+        assert_eq!(
+            erc1155_balances(
+                contract,
+                &[&ACCOUNT_1, &ACCOUNT_1, &ACCOUNT_2, &ACCOUNT_2],
+                "[1,2,1,2]",
+                blockchain.clone()
+            )
+            .await,
+            ["List<U64>", "[60,0,40,1]"]
+        );
+
+        // the non-fungible token is no longer owned by ACCOUNT_1, so the whole batch fails
+        let params = [
+            "String",
+            &account_1,
+            "String",
+            &account_2,
+            "List<U64>",
+            "[1,2]",
+            "List<U64>",
+            "[10,1]",
+        ];
+        let transaction = new_contract_call(
+            contract,
+            "safe-batch-transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(
+            erc1155_balances(contract, &[&ACCOUNT_1, &ACCOUNT_2], "[1,1]", blockchain).await,
+            ["List<U64>", "[60,40]"]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc1155_non_fungible_tokens_have_a_single_supply() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc1155(blockchain.clone()).await;
+        let account_2 = to_hex(*ACCOUNT_2);
+
+        for (function, params) in [
+            ("mint", vec!["String", &account_2, "U64", "2", "U64", "1"]),
+            ("mint-non-fungible", vec!["String", &account_2, "U64", "2"]),
+            ("mint-non-fungible", vec!["String", &account_2, "U64", "1"]),
+        ] {
+            let transaction =
+                new_contract_call(contract, function, &params, blockchain.clone()).await;
+            let receipt = send_and_process(transaction, blockchain.clone()).await;
+            assert_eq!(receipt.status, U64::zero());
+        }
+
+        assert_eq!(
+            call(contract, "total-supply", &["U64", "2"], blockchain)
+                .await
+                .unwrap(),
+            ["U64", "1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn erc1155_operators_transfer_tokens() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc1155(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_2 = to_hex(*ACCOUNT_2);
+        let account_3 = to_hex(*ACCOUNT_3);
+        let params = [
+            "String", &account_1, "String", &account_3, "U64", "1", "U64", "5",
+        ];
+
+        fund(*ACCOUNT_2, blockchain.clone()).await;
+
+        let transaction = new_contract_call_from(
+            *ACCOUNT_2,
+            contract,
+            "safe-transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+        assert_eq!(receipt.status, U64::zero());
+
+        let approval = ["String", &account_2, "Bool", "true"];
+        let transaction = new_contract_call(
+            contract,
+            "set-approval-for-all",
+            &approval,
+            blockchain.clone(),
+        )
+        .await;
+        send_and_process(transaction, blockchain.clone()).await;
+
+        let transaction = new_contract_call_from(
+            *ACCOUNT_2,
+            contract,
+            "safe-transfer-from",
+            &params,
+            blockchain.clone(),
+        )
+        .await;
+        let receipt = send_and_process(transaction, blockchain.clone()).await;
+
+        // the operator is the second topic
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs[0].topics[1], H256::from(*ACCOUNT_2));
+        assert_eq!(
+            erc1155_balances(contract, &[&ACCOUNT_1, &ACCOUNT_3], "[1,1]", blockchain).await,
+            ["List<U64>", "[95,5]"]
+        );
+    }
+
+    #[tokio::test]
+    async fn multisig_executes_a_confirmed_proposal() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_multisig(blockchain.clone()).await;
+        let recipient = Account::random();
+        let submit = [
+            "String",
+            &to_hex(recipient),
+            "U64",
+            "7",
+            "String",
+            "",
+            "List<String>",
+            "[]",
+        ];
+        let send = |from, function, params| {
+            multisig_send(from, contract, function, params, blockchain.clone())
+        };
+
+        assert_eq!(send(*ACCOUNT_1, "submit", &submit).await, U64::one());
+
+        // the submitter's confirmation doesn't reach the threshold
+        assert_eq!(
+            send(*ACCOUNT_1, "execute", &["U64", "0"]).await,
+            U64::zero()
+        );
+        assert_eq!(send(*ACCOUNT_2, "confirm", &["U64", "0"]).await, U64::one());
+        assert_eq!(
+            call(contract, "confirmations", &["U64", "0"], blockchain.clone())
+                .await
+                .unwrap(),
+            ["U64", "2"]
+        );
+        assert_eq!(send(*ACCOUNT_2, "execute", &["U64", "0"]).await, U64::one());
+        assert_eq!(get_balance(blockchain.clone(), &recipient).await, 7.into());
+        assert_eq!(get_balance(blockchain.clone(), &contract).await, 3.into());
+
+        // a proposal is executed once
+        assert_eq!(
+            send(*ACCOUNT_3, "execute", &["U64", "0"]).await,
+            U64::zero()
+        );
+        assert_eq!(
+            call(contract, "proposal", &["U64", "0"], blockchain)
+                .await
+                .unwrap(),
+            [
+                "List<String>",
+                &format!(r#"["{}","7","","true"]"#, to_hex(recipient))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn multisig_owners_revoke_confirmations() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_multisig(blockchain.clone()).await;
+        let outsider = Account::random();
+        let submit = [
+            "String",
+            &to_hex(outsider),
+            "U64",
+            "5",
+            "String",
+            "",
+            "List<String>",
+            "[]",
+        ];
+        let send = |from, function, params| {
+            multisig_send(from, contract, function, params, blockchain.clone())
+        };
+
+        fund(outsider, blockchain.clone()).await;
+
+        assert_eq!(send(outsider, "submit", &submit).await, U64::zero());
+        assert_eq!(send(*ACCOUNT_1, "submit", &submit).await, U64::one());
+        assert_eq!(send(outsider, "confirm", &["U64", "0"]).await, U64::zero());
+        assert_eq!(send(*ACCOUNT_2, "confirm", &["U64", "0"]).await, U64::one());
+        assert_eq!(
+            send(*ACCOUNT_1, "revoke-confirmation", &["U64", "0"]).await,
+            U64::one()
+        );
+        assert_eq!(
+            send(*ACCOUNT_1, "revoke-confirmation", &["U64", "0"]).await,
+            U64::zero()
+        );
+        assert_eq!(
+            send(*ACCOUNT_2, "execute", &["U64", "0"]).await,
+            U64::zero()
+        );
+        assert_eq!(
+            call(contract, "confirmations", &["U64", "0"], blockchain)
+                .await
+                .unwrap(),
+            ["U64", "1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn multisig_changes_its_configuration_through_proposals() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_multisig(blockchain.clone()).await;
+        let account_3 = to_hex(*ACCOUNT_3);
+        let submit = [
+            "String",
+            &to_hex(contract),
+            "U64",
+            "0",
+            "String",
+            "remove-owner",
+            "List<String>",
+            &format!(r#"["String","{}"]"#, account_3),
+        ];
+        let send = |from, function, params| {
+            multisig_send(from, contract, function, params, blockchain.clone())
+        };
+
+        // only the wallet itself can change the owners
+        let remove_owner = ["String", &account_3];
+        assert_eq!(
+            send(*ACCOUNT_1, "remove-owner", &remove_owner).await,
+            U64::zero()
+        );
+        assert_eq!(send(*ACCOUNT_1, "submit", &submit).await, U64::one());
+        assert_eq!(send(*ACCOUNT_3, "confirm", &["U64", "0"]).await, U64::one());
+        assert_eq!(send(*ACCOUNT_1, "execute", &["U64", "0"]).await, U64::one());
+        assert_eq!(
+            call(contract, "owners", &[], blockchain.clone())
+                .await
+                .unwrap(),
+            [
+                "List<String>",
+                &serde_json::to_string(&[*ACCOUNT_1, *ACCOUNT_2].map(to_hex)).unwrap()
+            ]
+        );
+
+        // the removed owner's confirmation no longer counts
+        assert_eq!(
+            call(contract, "confirmations", &["U64", "0"], blockchain)
+                .await
+                .unwrap(),
+            ["U64", "1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn amm_swaps_along_the_constant_product() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_amm(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_1 = ["String", &account_1];
+        let send = |function, params, value| {
+            send_with_value(contract, function, params, value, blockchain.clone())
+        };
+        let query = |function, params| call(contract, function, params, blockchain.clone());
+
+        // 1,000 coin in with a 0.3% fee: 997 * 40,000 / (10,000 + 997) = 3,626.44
+        assert_eq!(
+            query("quote-coin-for-token", &["U64", "1000"])
+                .await
+                .unwrap(),
+            ["U64", "3626"]
+        );
+
+        // the slippage limit reverts the swap and returns the coin
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+        assert_eq!(
+            send("swap-coin-for-token", &["U64", "3627"], 1000).await,
+            U64::zero()
+        );
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+
+        assert_eq!(
+            send("swap-coin-for-token", &["U64", "3626"], 1000).await,
+            U64::one()
+        );
+        assert_eq!(
+            query("reserves", &[]).await.unwrap(),
+            ["List<U64>", "[11000,36374]"]
+        );
+        assert_eq!(
+            query("token-balance-of", &account_1).await.unwrap(),
+            ["U64", "963626"]
+        );
+
+        // swapping back returns less coin, the fee stays in the pool
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+        assert_eq!(
+            send("swap-token-for-coin", &["U64", "3626", "U64", "994"], 0).await,
+            U64::one()
+        );
+        assert_eq!(
+            get_balance(blockchain.clone(), &ACCOUNT_1).await,
+            balance + 994
+        );
+        assert_eq!(
+            query("reserves", &[]).await.unwrap(),
+            ["List<U64>", "[10006,40000]"]
+        );
+        assert_eq!(get_balance(blockchain, &contract).await, 10_006.into());
+    }
+
+    #[tokio::test]
+    async fn amm_mints_and_redeems_liquidity_shares() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_amm(blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_1 = ["String", &account_1];
+        let send = |function, params, value| {
+            send_with_value(contract, function, params, value, blockchain.clone())
+        };
+        let query = |function, params| call(contract, function, params, blockchain.clone());
+
+        // the first deposit mints sqrt(10,000 * 40,000) shares
+        assert_eq!(query("total-shares", &[]).await.unwrap(), ["U64", "20000"]);
+
+        // later deposits must match the reserve ratio
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+        assert_eq!(
+            send("add-liquidity", &["U64", "3999", "U64", "0"], 1000).await,
+            U64::zero()
+        );
+        assert_eq!(
+            send("add-liquidity", &["U64", "4000", "U64", "2001"], 1000).await,
+            U64::zero()
+        );
+        assert_eq!(
+            send("add-liquidity", &["U64", "4000", "U64", "2000"], 1000).await,
+            U64::one()
+        );
+        assert_eq!(
+            query("shares-of", &account_1).await.unwrap(),
+            ["U64", "22000"]
+        );
+        assert_eq!(
+            query("reserves", &[]).await.unwrap(),
+            ["List<U64>", "[11000,44000]"]
+        );
+
+        let remove = ["U64", "2000", "U64", "1000", "U64", "4001"];
+        assert_eq!(send("remove-liquidity", &remove, 0).await, U64::zero());
+
+        let remove = ["U64", "2000", "U64", "1000", "U64", "4000"];
+        assert_eq!(send("remove-liquidity", &remove, 0).await, U64::one());
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+        assert_eq!(
+            query("token-balance-of", &account_1).await.unwrap(),
+            ["U64", "960000"]
+        );
+
+        // shares can't be redeemed twice
+        let remove = ["U64", "20001", "U64", "0", "U64", "0"];
+        assert_eq!(send("remove-liquidity", &remove, 0).await, U64::zero());
+    }
+
+    #[tokio::test]
+    async fn sdk_contracts_read_the_call_context() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_contract(timelock_contract(), &[], blockchain.clone()).await;
+        let account_1 = to_hex(*ACCOUNT_1);
+        let account_1 = ["String", &account_1];
+        let send = |function, params, value| {
+            send_with_value(contract, function, params, value, blockchain.clone())
+        };
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+
+        // deployed in block 1, so the deposit in block 2 unlocks at block 4
+        assert_eq!(send("deposit", &["U64", "2"], 100).await, U64::one());
+        assert_eq!(
+            call(contract, "unlock-of", &account_1, blockchain.clone())
+                .await
+                .unwrap(),
+            ["U64", "4"]
+        );
+        assert_eq!(
+            call(contract, "deposit-of", &account_1, blockchain.clone())
+                .await
+                .unwrap(),
+            ["U64", "100"]
+        );
+
+        assert_eq!(send("withdraw", &[], 0).await, U64::zero());
+        assert_eq!(send("withdraw", &[], 0).await, U64::one());
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+        assert_eq!(get_balance(blockchain, &contract).await, U256::zero());
+    }
+
     #[tokio::test]
     async fn sends_a_transaction() {
         let (blockchain, _, _) = setup().await;
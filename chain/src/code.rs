@@ -0,0 +1,182 @@
+//! # Code
+//!
+//! Contract code is stored once in a content-addressed store, keyed by the
+//! keccak256 hash of the code.
+//! Accounts only hold the 32 byte code hash, so identical contracts share storage.
+//! The interface of the code is stored next to it under the same hash, so an
+//! account's interface follows its code through upgrades and self-destructs.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+
+use eth_trie::DB;
+use ethereum_types::H256;
+use serde::{Deserialize, Serialize};
+use types::bytes::Bytes;
+use types::interface::ContractInterface;
+use utils::crypto::hash;
+
+use crate::error::{ChainError, Result};
+use crate::helpers::{deserialize, serialize};
+use crate::storage::Storage;
+
+const PREFIX: &[u8] = b"code:";
+const INTERFACE_PREFIX: &[u8] = b"interface:";
+
+/// An interface, and whether it was declared at deployment or derived from the exports
+#[derive(Serialize, Deserialize)]
+struct StoredInterface {
+    declared: bool,
+    interface: ContractInterface,
+}
+
+#[derive(Debug)]
+pub(crate) struct CodeStorage {
+    storage: Arc<Storage>,
+}
+
+impl CodeStorage {
+    pub(crate) fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Store the code under its hash, skipping the write if it already exists.
+    pub(crate) fn insert(&self, code: &Bytes) -> Result<H256> {
+        let code_hash: H256 = hash(code).into();
+        let key = CodeStorage::key(&code_hash);
+
+        if self.storage.get(&key)?.is_none() {
+            self.storage.insert(&key, code.to_vec())?;
+        }
+
+        Ok(code_hash)
+    }
+
+    pub(crate) fn get(&self, code_hash: &H256) -> Result<Bytes> {
+        let code = self
+            .storage
+            .get(&CodeStorage::key(code_hash))?
+            .ok_or_else(|| ChainError::CodeNotFound(code_hash.to_string()))?;
+
+        Ok(code.into())
+    }
+
+    /// Store the interface of the code under its hash.
+    /// Identical code shares an interface: a declared interface replaces one
+    /// derived from the exports, but the first declared interface is kept.
+    pub(crate) fn insert_interface(
+        &self,
+        code_hash: &H256,
+        interface: &ContractInterface,
+        declared: bool,
+    ) -> Result<()> {
+        let key = CodeStorage::interface_key(code_hash);
+        let replace = match self.storage.get(&key)? {
+            Some(stored) => declared && !deserialize::<StoredInterface>(&stored)?.declared,
+            None => true,
+        };
+
+        if replace {
+            let stored = StoredInterface {
+                declared,
+                interface: interface.to_owned(),
+            };
+            self.storage.insert(&key, serialize(&stored)?)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn get_interface(&self, code_hash: &H256) -> Result<ContractInterface> {
+        let stored = self
+            .storage
+            .get(&CodeStorage::interface_key(code_hash))?
+            .ok_or_else(|| ChainError::InterfaceNotFound(code_hash.to_string()))?;
+
+        Ok(deserialize::<StoredInterface>(&stored)?.interface)
+    }
+
+    pub(crate) fn has_interface(&self, code_hash: &H256) -> Result<bool> {
+        Ok(self
+            .storage
+            .get(&CodeStorage::interface_key(code_hash))?
+            .is_some())
+    }
+
+    fn interface_key(code_hash: &H256) -> Vec<u8> {
+        [INTERFACE_PREFIX, code_hash.as_bytes()].concat()
+    }
+
+    fn key(code_hash: &H256) -> Vec<u8> {
+        [PREFIX, code_hash.as_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::STORAGE;
+    use types::interface::{FunctionSignature, Param};
+
+    #[test]
+    fn it_inserts_and_gets_code() {
+        let code_storage = CodeStorage::new((*STORAGE).clone());
+        let code: Bytes = vec![0, 1, 2, 3].into();
+        let code_hash = code_storage.insert(&code).unwrap();
+
+        assert_eq!(code_hash, H256::from(hash(&code)));
+        assert_eq!(code_storage.get(&code_hash).unwrap(), code);
+    }
+
+    #[test]
+    fn identical_code_shares_a_hash() {
+        let code_storage = CodeStorage::new((*STORAGE).clone());
+        let code: Bytes = vec![4, 5, 6].into();
+        let code_hash_1 = code_storage.insert(&code).unwrap();
+        let code_hash_2 = code_storage.insert(&code).unwrap();
+
+        assert_eq!(code_hash_1, code_hash_2);
+    }
+
+    #[test]
+    fn it_inserts_and_gets_an_interface() {
+        let code_storage = CodeStorage::new((*STORAGE).clone());
+        let code_hash = H256::random();
+        let function = |param| {
+            FunctionSignature::new("balance-of", vec![Param::new(param, "string")], Some("u64"))
+        };
+        let derived = ContractInterface::new(vec![function("p0")], vec![]);
+        let declared = ContractInterface::new(vec![function("account")], vec![]);
+        let redeclared = ContractInterface::new(vec![function("owner")], vec![]);
+
+        code_storage
+            .insert_interface(&code_hash, &derived, false)
+            .unwrap();
+        assert_eq!(code_storage.get_interface(&code_hash).unwrap(), derived);
+
+        // a declaration replaces the derived interface, but not another declaration
+        for interface in [&declared, &redeclared] {
+            code_storage
+                .insert_interface(&code_hash, interface, true)
+                .unwrap();
+        }
+        code_storage
+            .insert_interface(&code_hash, &derived, false)
+            .unwrap();
+        assert_eq!(code_storage.get_interface(&code_hash).unwrap(), declared);
+
+        assert!(matches!(
+            code_storage.get_interface(&H256::random()),
+            Err(ChainError::InterfaceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn it_errors_on_missing_code() {
+        let code_storage = CodeStorage::new((*STORAGE).clone());
+        let response = code_storage.get(&H256::random());
+
+        assert!(matches!(response, Err(ChainError::CodeNotFound(_))));
+    }
+}
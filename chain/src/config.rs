@@ -0,0 +1,146 @@
+//! # Config
+//!
+//! Settings for running the chain, read from environment variables.
+//! Every setting has a default, so none of the variables are required.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+
+use ethereum_types::Address;
+use runtime::limits::Limits;
+use types::bytes::Bytes;
+
+use crate::error::{ChainError, Result};
+
+/// The default most gas the transactions of a block can use
+pub(crate) const GAS_LIMIT: u64 = 30_000_000;
+
+/// The most bytes of extra data in a block
+pub(crate) const MAX_EXTRA_DATA: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Config {
+    /// The resources a contract can hold during a call
+    pub(crate) limits: Limits,
+
+    /// Whether to log the messages contracts print and add them to transaction traces
+    pub(crate) debug: bool,
+
+    /// The most gas the transactions of a block can use
+    pub(crate) gas_limit: u64,
+
+    /// The data added to every block, at most 32 bytes
+    pub(crate) extra_data: Bytes,
+
+    /// The author of the blocks, the address of the node's keys
+    pub(crate) author: Address,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            limits: Limits::default(),
+            debug: false,
+            gas_limit: GAS_LIMIT,
+            extra_data: Bytes::new(),
+            author: Address::zero(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the config, e.g. `MAX_MEMORY_PAGES=2048 cargo run`
+    pub(crate) fn from_env() -> Result<Self> {
+        // variables that aren't unicode are skipped, rather than panicking
+        let vars = env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+
+        Self::from_vars(vars)
+    }
+
+    /// Read the config from variables, which are usually the environment
+    pub(crate) fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let vars = vars.into_iter().collect::<HashMap<_, _>>();
+        let defaults = Limits::default();
+        let limits = Limits {
+            memory_pages: var(&vars, "MAX_MEMORY_PAGES", defaults.memory_pages)?,
+            table_elements: var(&vars, "MAX_TABLE_ELEMENTS", defaults.table_elements)?,
+            instances: var(&vars, "MAX_INSTANCES", defaults.instances)?,
+            max_wasm_stack: var(&vars, "MAX_WASM_STACK", defaults.max_wasm_stack)?,
+        };
+
+        let debug = var(&vars, "CONTRACT_DEBUG", false)?;
+        let gas_limit = var(&vars, "BLOCK_GAS_LIMIT", GAS_LIMIT)?;
+        let extra_data = var(&vars, "BLOCK_EXTRA_DATA", String::new())?;
+
+        if extra_data.len() > MAX_EXTRA_DATA {
+            return Err(ChainError::InvalidConfig(format!(
+                "BLOCK_EXTRA_DATA={}",
+                extra_data
+            )));
+        }
+
+        Ok(Self {
+            limits,
+            debug,
+            gas_limit,
+            extra_data: extra_data.into(),
+            ..Self::default()
+        })
+    }
+}
+
+fn var<T: FromStr>(vars: &HashMap<String, String>, name: &str, default: T) -> Result<T> {
+    match vars.get(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| ChainError::InvalidConfig(format!("{}={}", name, value))),
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_reads_limits_from_variables() {
+        let config = Config::from_vars(vars(&[
+            ("MAX_TABLE_ELEMENTS", "100"),
+            ("CONTRACT_DEBUG", "true"),
+            ("BLOCK_EXTRA_DATA", "rust-blockchain"),
+        ]))
+        .unwrap();
+
+        assert_eq!(config.limits.table_elements, 100);
+        assert_eq!(config.limits.memory_pages, Limits::default().memory_pages);
+        assert!(config.debug);
+        assert_eq!(config.gas_limit, GAS_LIMIT);
+        assert_eq!(config.extra_data, "rust-blockchain".as_bytes());
+        assert_eq!(Config::from_vars(vec![]).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn it_rejects_invalid_variables() {
+        let extra_data = "x".repeat(MAX_EXTRA_DATA + 1);
+
+        assert!(matches!(
+            Config::from_vars(vars(&[("MAX_INSTANCES", "many")])),
+            Err(ChainError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            Config::from_vars(vars(&[("BLOCK_EXTRA_DATA", &extra_data)])),
+            Err(ChainError::InvalidConfig(_))
+        ));
+    }
+}
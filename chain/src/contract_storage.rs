@@ -0,0 +1,407 @@
+//! # Contract Storage
+//!
+//! Each contract has its own storage trie, holding the values the contract
+//! writes through the `set` host function.
+//! The contract account holds the root of its storage trie, so every state root
+//! also commits to the storage of all contracts.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use eth_trie::{EthTrie, Trie, DB};
+use ethereum_types::H256;
+use rlp::Rlp;
+use types::bytes::Bytes;
+use types::storage::StorageKeys;
+
+use crate::error::{ChainError, Result};
+use crate::storage::Storage;
+
+#[derive(Debug)]
+pub(crate) struct ContractStorage {
+    storage: Arc<Storage>,
+}
+
+impl ContractStorage {
+    pub(crate) fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Get a value from the storage trie at the given root.
+    /// A contract that never wrote to storage doesn't have a root.
+    pub(crate) fn get(&self, root: Option<H256>, key: &str) -> Result<Option<String>> {
+        let value = self
+            .trie(root)?
+            .get(key.as_bytes())
+            .map_err(|e| ChainError::StorageNotFound(format!("{}: {}", key, e)))?;
+
+        value
+            .map(|value| {
+                String::from_utf8(value).map_err(|e| ChainError::DeserializeError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Get the nodes of the storage trie at the given root on the path to the key.
+    /// A contract that never wrote to storage doesn't have a trie to prove against.
+    pub(crate) fn proof(&self, root: Option<H256>, key: &str) -> Result<Vec<Bytes>> {
+        let proof = match root {
+            Some(_) => self
+                .trie(root)?
+                .get_proof(key.as_bytes())
+                .map_err(|e| ChainError::StorageNotFound(format!("{}: {}", key, e)))?,
+            None => vec![],
+        };
+
+        Ok(proof.into_iter().map(Bytes::from).collect())
+    }
+
+    /// Write the changes to the storage trie at the given root, returning the new root.
+    /// Nodes are never removed, so the storage at previous roots stays readable.
+    pub(crate) fn update(
+        &self,
+        root: Option<H256>,
+        changes: &BTreeMap<String, String>,
+    ) -> Result<H256> {
+        let mut trie = self.trie(root)?;
+
+        for (key, value) in changes {
+            trie.insert(key.as_bytes(), value.as_bytes())
+                .map_err(|_| ChainError::StoragePutError(key.to_owned()))?;
+        }
+
+        let root_hash = trie
+            .root_hash()
+            .map_err(|e| ChainError::CannotCreateRootHash(format!("storage_trie: {}", e)))?;
+
+        Ok(H256::from_slice(root_hash.as_bytes()))
+    }
+
+    /// List up to `limit` keys of the storage trie at the given root, in key order,
+    /// starting at `start` if given.
+    /// The walk skips the subtrees before `start`, so a page only reads the
+    /// nodes on the path to `start` and the nodes of the keys it lists.
+    pub(crate) fn keys(
+        &self,
+        root: Option<H256>,
+        start: Option<&str>,
+        limit: usize,
+    ) -> Result<StorageKeys> {
+        let mut nibbles = vec![];
+        let mut keys = vec![];
+
+        if let Some(root) = root {
+            let start = start.map(|start| to_nibbles(start.as_bytes()));
+            let seek = start.as_deref().map_or(Seek::All, Seek::From);
+            let node = self.node(root.as_bytes())?;
+
+            self.collect_keys(&node, seek, &mut nibbles, &mut keys, limit + 1)?;
+        }
+
+        let mut keys = keys
+            .into_iter()
+            .map(|key| String::from_utf8(from_nibbles(&key)))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| ChainError::DeserializeError(e.to_string()))?;
+        let next_key = (keys.len() > limit).then(|| keys.remove(limit));
+
+        Ok(StorageKeys::new(keys, next_key))
+    }
+
+    /// Collect the keys below an RLP encoded node in key order, up to `limit` keys.
+    /// `nibbles` is the path to the node.
+    fn collect_keys(
+        &self,
+        node: &[u8],
+        seek: Seek,
+        nibbles: &mut Vec<u8>,
+        keys: &mut Vec<Vec<u8>>,
+        limit: usize,
+    ) -> Result<()> {
+        let rlp = Rlp::new(node);
+
+        // the root of an empty trie
+        if rlp.is_empty() {
+            return Ok(());
+        }
+
+        match rlp.item_count().map_err(decode_error)? {
+            // a leaf or an extension, told apart by the flag of their compact path
+            2 => {
+                let path = rlp
+                    .at(0)
+                    .and_then(|path| path.data())
+                    .map_err(decode_error)?;
+                let (path, is_leaf) = from_compact(path);
+                let seek = seek.below(&path);
+                let length = nibbles.len();
+
+                nibbles.extend(&path);
+
+                match (is_leaf, seek) {
+                    (_, Seek::Skip) => {}
+                    (true, Seek::All) => keys.push(nibbles.clone()),
+                    // a leaf on the path to `start` is before it
+                    (true, Seek::From(_)) => {}
+                    (false, seek) => {
+                        let child = self.child(&rlp, 1)?;
+                        self.collect_keys(&child, seek, nibbles, keys, limit)?;
+                    }
+                }
+
+                nibbles.truncate(length);
+            }
+            // a branch, with a child per nibble and a value for the key ending at the branch
+            17 => {
+                let has_value = !rlp.at(16).map_err(decode_error)?.is_empty();
+
+                // the key ending at the branch is the start or after it
+                if has_value && matches!(seek, Seek::All | Seek::From([])) {
+                    keys.push(nibbles.clone());
+                }
+
+                for nibble in 0..16 {
+                    if keys.len() >= limit {
+                        break;
+                    }
+
+                    let seek = seek.below(&[nibble]);
+
+                    if matches!(seek, Seek::Skip)
+                        || rlp.at(nibble as usize).map_err(decode_error)?.is_empty()
+                    {
+                        continue;
+                    }
+
+                    let child = self.child(&rlp, nibble as usize)?;
+
+                    nibbles.push(nibble);
+                    self.collect_keys(&child, seek, nibbles, keys, limit)?;
+                    nibbles.pop();
+                }
+            }
+            _ => return Err(decode_error(rlp::DecoderError::RlpIncorrectListLen)),
+        }
+
+        Ok(())
+    }
+
+    /// The encoding of a child node, which is inlined if it's under 32 bytes
+    fn child(&self, rlp: &Rlp, index: usize) -> Result<Vec<u8>> {
+        let child = rlp.at(index).map_err(decode_error)?;
+
+        match child.is_list() {
+            true => Ok(child.as_raw().to_vec()),
+            false => self.node(child.data().map_err(decode_error)?),
+        }
+    }
+
+    fn node(&self, hash: &[u8]) -> Result<Vec<u8>> {
+        self.storage.get(hash)?.ok_or_else(|| {
+            ChainError::StorageNotFound(format!("storage node {}", H256::from_slice(hash)))
+        })
+    }
+
+    fn trie(&self, root: Option<H256>) -> Result<EthTrie<Storage>> {
+        match root {
+            Some(root) => EthTrie::from(Arc::clone(&self.storage), root.0.into())
+                .map_err(|e| ChainError::StorageNotFound(format!("storage root {}: {}", root, e))),
+            None => Ok(EthTrie::new(Arc::clone(&self.storage))),
+        }
+    }
+}
+
+/// Where the keys below a node are relative to the key a page starts at
+#[derive(Clone, Copy)]
+enum Seek<'a> {
+    /// All keys are before the start
+    Skip,
+
+    /// All keys are at or after the start
+    All,
+
+    /// The node is on the path to the start, and these are the nibbles left
+    From(&'a [u8]),
+}
+
+impl<'a> Seek<'a> {
+    /// Where the keys are after following the nibbles of a path
+    fn below(self, path: &[u8]) -> Seek<'a> {
+        let start = match self {
+            Seek::From(start) => start,
+            seek => return seek,
+        };
+        let length = path.len().min(start.len());
+
+        match path[..length].cmp(&start[..length]) {
+            Ordering::Less => Seek::Skip,
+            Ordering::Greater => Seek::All,
+            Ordering::Equal if path.len() >= start.len() => Seek::All,
+            Ordering::Equal => Seek::From(&start[length..]),
+        }
+    }
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn from_nibbles(nibbles: &[u8]) -> Vec<u8> {
+    nibbles
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or_default())
+        .collect()
+}
+
+/// Decode a hex-prefix encoded path, returning its nibbles and whether it's the path of a leaf
+fn from_compact(compact: &[u8]) -> (Vec<u8>, bool) {
+    let mut nibbles = to_nibbles(compact);
+    let flag = nibbles.first().copied().unwrap_or_default();
+    let padding = if flag & 1 == 1 { 1 } else { 2 };
+
+    nibbles.drain(..padding.min(nibbles.len()));
+
+    (nibbles, flag & 2 == 2)
+}
+
+fn decode_error(error: rlp::DecoderError) -> ChainError {
+    ChainError::DeserializeError(format!("storage node: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::STORAGE;
+
+    fn changes(values: &[(&str, &str)]) -> BTreeMap<String, String> {
+        values
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_updates_and_gets_values() {
+        let contract_storage = ContractStorage::new((*STORAGE).clone());
+        let root = contract_storage
+            .update(
+                None,
+                &changes(&[("name", "Rust Coin"), ("total_supply", "10")]),
+            )
+            .unwrap();
+
+        assert_eq!(
+            contract_storage.get(Some(root), "name").unwrap(),
+            Some("Rust Coin".into())
+        );
+        assert_eq!(contract_storage.get(Some(root), "symbol").unwrap(), None);
+        assert_eq!(contract_storage.get(None, "name").unwrap(), None);
+    }
+
+    #[test]
+    fn it_lists_keys_in_pages() {
+        let contract_storage = ContractStorage::new((*STORAGE).clone());
+        let root = contract_storage
+            .update(
+                None,
+                &changes(&[("b", "2"), ("a", "1"), ("c", "3"), ("balance:0x1", "4")]),
+            )
+            .unwrap();
+
+        let page = contract_storage.keys(Some(root), None, 2).unwrap();
+        assert_eq!(page.keys, ["a", "b"]);
+        assert_eq!(page.next_key.as_deref(), Some("balance:0x1"));
+
+        let page = contract_storage
+            .keys(Some(root), page.next_key.as_deref(), 2)
+            .unwrap();
+        assert_eq!(page.keys, ["balance:0x1", "c"]);
+        assert_eq!(page.next_key, None);
+
+        assert!(contract_storage
+            .keys(None, None, 2)
+            .unwrap()
+            .keys
+            .is_empty());
+    }
+
+    #[test]
+    fn pages_start_at_any_key() {
+        let contract_storage = ContractStorage::new((*STORAGE).clone());
+        let mut keys = (0..100)
+            .map(|index| format!("balance:0x{:x}", index * 7919))
+            .chain(["", "b", "ba", "balance", "z"].map(String::from))
+            .collect::<Vec<_>>();
+        let values = keys.iter().map(|key| (key.clone(), "1".into())).collect();
+        let root = contract_storage.update(None, &values).unwrap();
+        keys.sort();
+
+        let mut listed = vec![];
+        let mut start = None;
+
+        loop {
+            let page = contract_storage
+                .keys(Some(root), start.as_deref(), 7)
+                .unwrap();
+            listed.extend(page.keys);
+
+            match page.next_key {
+                Some(next_key) => start = Some(next_key),
+                None => break,
+            }
+        }
+
+        assert_eq!(listed, keys);
+
+        let page = contract_storage.keys(Some(root), Some("bal"), 2).unwrap();
+        assert_eq!(page.keys, ["balance", "balance:0x0"]);
+        let page = contract_storage.keys(Some(root), Some("y"), 2).unwrap();
+        assert_eq!(page.keys, ["z"]);
+        assert_eq!(page.next_key, None);
+    }
+
+    #[test]
+    fn only_the_page_with_a_non_utf8_key_fails() {
+        let mut trie = EthTrie::new((*STORAGE).clone());
+        trie.insert(b"a\xff", b"1").unwrap();
+        trie.insert(b"b", b"2").unwrap();
+        trie.insert(b"c", b"3").unwrap();
+        let root = H256::from_slice(trie.root_hash().unwrap().as_bytes());
+        let contract_storage = ContractStorage::new((*STORAGE).clone());
+
+        assert!(matches!(
+            contract_storage.keys(Some(root), None, 2),
+            Err(ChainError::DeserializeError(_))
+        ));
+
+        let page = contract_storage.keys(Some(root), Some("b"), 2).unwrap();
+        assert_eq!(page.keys, ["b", "c"]);
+    }
+
+    #[test]
+    fn previous_roots_stay_readable() {
+        let contract_storage = ContractStorage::new((*STORAGE).clone());
+        let root_1 = contract_storage
+            .update(None, &changes(&[("total_supply", "10")]))
+            .unwrap();
+        let root_2 = contract_storage
+            .update(Some(root_1), &changes(&[("total_supply", "20")]))
+            .unwrap();
+
+        assert_ne!(root_1, root_2);
+        assert_eq!(
+            contract_storage.get(Some(root_1), "total_supply").unwrap(),
+            Some("10".into())
+        );
+        assert_eq!(
+            contract_storage.get(Some(root_2), "total_supply").unwrap(),
+            Some("20".into())
+        );
+    }
+}
@@ -30,18 +30,33 @@ pub enum ChainError {
     #[error("Could not create root hash for : {0}")]
     CannotCreateRootHash(String),
 
+    #[error("Code {0} not found")]
+    CodeNotFound(String),
+
+    #[error("Contract {0} already exists")]
+    ContractAlreadyExists(String),
+
     #[error("Error encoding/decoding: {0}")]
     EncodingDecodingError(String),
 
     #[error("Could not deserialize: {0}")]
     DeserializeError(String),
 
+    #[error("Transaction gas {0} exceeds the block gas limit {1}")]
+    ExceedsBlockGasLimit(String, String),
+
+    #[error("No interface found for contract {0}")]
+    InterfaceNotFound(String),
+
     #[error("Interal Error: {0}")]
     InternalError(String),
 
     #[error("Invalid block number {0}")]
     InvalidBlockNumber(String),
 
+    #[error("Invalid config {0}")]
+    InvalidConfig(String),
+
     #[error("JsonRpsee Error: {0}")]
     JsonRpseeError(String),
 
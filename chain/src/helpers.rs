@@ -39,6 +39,7 @@ pub mod tests {
     use rocksdb::{DBCommon, SingleThreaded};
     use tokio::sync::Mutex;
     use types::account::{Account, AccountData};
+    use types::bytes::Bytes;
     use types::transaction::Transaction;
 
     use crate::{blockchain::BlockChain, server::serve, storage::Storage};
@@ -61,6 +62,48 @@ pub mod tests {
         serve(ADDRESS, blockchain).await.unwrap()
     }
 
+    pub(crate) fn amm_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/amm_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn erc20_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn erc1155_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc1155_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn erc721_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc721_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn multisig_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/multisig_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn timelock_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/timelock_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
+    pub(crate) fn forwarder_contract() -> Bytes {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/forwarder_wit.wasm")
+            .to_vec()
+            .into()
+    }
+
     pub(crate) fn client() -> HttpClient {
         let url = format!("http://{}", ADDRESS);
         HttpClientBuilder::default().build(url).unwrap()
@@ -32,6 +32,7 @@ pub(crate) fn add_keys() -> Result<()> {
     Ok(())
 }
 
+#[allow(dead_code)]
 pub(crate) fn get_private_key() -> Result<SecretKey> {
     let key = read(PRIVATE_KEY_PATH).expect("Could not read private key");
     SecretKey::from_slice(&key).map_err(|e| ChainError::InternalError(e.to_string()))
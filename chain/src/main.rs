@@ -7,6 +7,9 @@
 
 mod account;
 mod blockchain;
+mod code;
+mod config;
+mod contract_storage;
 mod error;
 mod helpers;
 mod keys;
@@ -20,6 +23,7 @@ mod world_state;
 // use std::sync::Arc;
 
 // use blockchain::BlockChain;
+use config::Config;
 use error::Result;
 use server::serve;
 // use storage::Storage;
@@ -33,6 +37,8 @@ async fn main() -> Result<()> {
     // let _server = serve("127.0.0.1:8545", Arc::new(Mutex::new(blockchain))).await?;
 
     let (blockchain, _, _) = crate::helpers::tests::setup().await;
+    blockchain.lock().await.set_config(Config::from_env()?)?;
+
     let _server = serve("127.0.0.1:8545", blockchain).await?;
 
     // create a future that never resolves
@@ -13,6 +13,7 @@ use types::{
     transaction::TransactionRequest,
 };
 
+use crate::blockchain::MAX_STORAGE_KEYS;
 use crate::{error::Result, server::Context};
 
 pub(crate) fn eth_block_number(module: &mut RpcModule<Context>) -> Result<()> {
@@ -163,13 +164,56 @@ pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Re
     Ok(())
 }
 
+/// The trace of a processed transaction, including the messages printed by
+/// contracts when the node runs in debug mode
+pub(crate) fn debug_trace_transaction(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "debug_traceTransaction",
+        move |params, blockchain| async move {
+            let transaction_hash = params.one::<H256>()?;
+            let transaction_trace = blockchain
+                .lock()
+                .await
+                .transactions
+                .lock()
+                .await
+                .get_transaction_trace(&transaction_hash)
+                .map_err(|e| Error::Custom(e.to_string()))?;
+
+            Ok(transaction_trace)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Call a contract function against the latest state without sending a transaction.
+/// The changes made by the call are discarded, and the returned values are typed pairs.
+pub(crate) fn eth_call(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_call", move |params, blockchain| async move {
+        let transaction_request = params.sequence().next::<TransactionRequest>()?;
+        let from = transaction_request.from.unwrap_or_default();
+        let to = transaction_request
+            .to
+            .ok_or_else(|| Error::Custom("eth_call requires a to address".into()))?;
+        let data = transaction_request.data.unwrap_or_default();
+        let gas = transaction_request.gas.min(u64::MAX.into()).as_u64();
+        let result = blockchain
+            .lock()
+            .await
+            .call(from, to, &data, gas)
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(result)
+    })?;
+
+    Ok(())
+}
+
 pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
     module.register_async_method("eth_getCode", move |params, blockchain| async move {
         let mut seq = params.sequence();
         let address = seq.next::<Account>()?;
-
-        // TODO(ddimaria): lookup code by block number
-        // let _block = seq.next::<BlockNumber>()?;
         let block = seq.next::<String>()?.clone();
         let block_number = blockchain
             .lock()
@@ -177,27 +221,121 @@ pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
             .parse_block_number(&block)
             .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
 
-        let code_hash = blockchain
+        let code = blockchain
             .lock()
             .await
-            .accounts
-            .get_account(&address)
-            .map_err(|e| Error::Custom(e.to_string()))?
-            .code_hash
-            .ok_or_else(|| {
-                JsonRpseeError::Custom(format!("missing code hash for block {:?}", block_number))
-            })?;
+            .get_code_by_block(&address, &block_number)
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(code)
+    })?;
+
+    Ok(())
+}
+
+/// A value from the storage of a contract at a block, null if the key isn't set
+pub(crate) fn eth_get_storage_at(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_getStorageAt", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let address = seq.next::<Account>()?;
+        let key = seq.next::<String>()?;
+        let block = seq.next::<String>()?;
+        let blockchain = blockchain.lock().await;
+        let block_number = blockchain
+            .parse_block_number(&block)
+            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
 
-        Ok(code_hash)
+        let value = blockchain
+            .get_storage_by_block(&address, &key, &block_number)
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(value)
     })?;
 
     Ok(())
 }
 
+/// Merkle proofs of an account and the given keys of its storage against the
+/// state root of a block
+pub(crate) fn eth_get_proof(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_getProof", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let address = seq.next::<Account>()?;
+        let keys = seq.next::<Vec<String>>()?;
+        let block = seq.next::<String>()?;
+        let blockchain = blockchain.lock().await;
+        let block_number = blockchain
+            .parse_block_number(&block)
+            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+
+        let proof = blockchain
+            .get_proof_by_block(&address, &keys, &block_number)
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(proof)
+    })?;
+
+    Ok(())
+}
+
+/// A page of the storage keys of a contract at a block, in key order.
+/// The page starts at the optional start key, and the response holds the key
+/// the next page starts at.
+pub(crate) fn debug_storage_keys(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("debug_storageKeys", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let address = seq.next::<Account>()?;
+        let block = seq.next::<String>()?;
+        let start = seq.optional_next::<String>()?;
+        let limit = seq.optional_next::<usize>()?.unwrap_or(MAX_STORAGE_KEYS);
+        let blockchain = blockchain.lock().await;
+        let block_number = blockchain
+            .parse_block_number(&block)
+            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+
+        let keys = blockchain
+            .get_storage_keys_by_block(&address, &block_number, start.as_deref(), limit)
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(keys)
+    })?;
+
+    Ok(())
+}
+
+/// The interface of a contract's code at a block
+pub(crate) fn eth_get_contract_interface(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_getContractInterface",
+        move |params, blockchain| async move {
+            let mut seq = params.sequence();
+            let address = seq.next::<Account>()?;
+            let block = seq.next::<String>()?;
+            let blockchain = blockchain.lock().await;
+            let block_number = blockchain
+                .parse_block_number(&block)
+                .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+
+            let interface = blockchain
+                .get_interface_by_block(&address, &block_number)
+                .map_err(|e| Error::Custom(e.to_string()))?;
+
+            Ok(interface)
+        },
+    )?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::helpers::tests::setup;
+    use crate::blockchain::tests::{deploy_erc20, new_contract_call, send_and_process};
+    use crate::helpers::tests::{setup, ACCOUNT_1};
+    use types::block::Block;
+    use types::interface::ContractInterface;
+    use types::proof::AccountProof;
+    use types::storage::StorageKeys;
 
     #[tokio::test]
     async fn gets_an_account_balance() {
@@ -215,4 +353,99 @@ pub mod tests {
 
         assert_eq!(response, to_hex(balance));
     }
+
+    #[tokio::test]
+    async fn gets_contract_storage() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let balance_key = format!("balance:{}", to_hex(*ACCOUNT_1));
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "10"];
+        let mint = new_contract_call(contract, "mint", &mint, blockchain.clone()).await;
+        send_and_process(mint, blockchain.clone()).await;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_storage_at(&mut module).unwrap();
+        debug_storage_keys(&mut module).unwrap();
+
+        let get = |key: &str| (contract, key.to_string(), "latest");
+        let name: Option<String> = module.call("eth_getStorageAt", get("name")).await.unwrap();
+        let balance: Option<String> = module
+            .call("eth_getStorageAt", get(&balance_key))
+            .await
+            .unwrap();
+        let unset: Option<String> = module.call("eth_getStorageAt", get("unset")).await.unwrap();
+        assert_eq!(name.as_deref(), Some("Rust Coin"));
+        assert_eq!(balance.as_deref(), Some("10"));
+        assert_eq!(unset, None);
+
+        let page: StorageKeys = module
+            .call("debug_storageKeys", (contract, "latest", None::<String>, 2))
+            .await
+            .unwrap();
+        let next: StorageKeys = module
+            .call("debug_storageKeys", (contract, "latest", page.next_key, 10))
+            .await
+            .unwrap();
+        let keys = [page.keys, next.keys].concat();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys.len(), 5);
+        assert_eq!(keys, sorted);
+        assert!(keys.contains(&balance_key));
+        assert_eq!(next.next_key, None);
+    }
+
+    #[tokio::test]
+    async fn gets_a_contract_interface() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_contract_interface(&mut module).unwrap();
+
+        let interface: ContractInterface = module
+            .call("eth_getContractInterface", (contract, "latest"))
+            .await
+            .unwrap();
+        let missing = module
+            .call::<_, ContractInterface>("eth_getContractInterface", (Account::random(), "latest"))
+            .await;
+
+        assert!(interface.function("balance-of").is_some());
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn gets_a_proof() {
+        let (blockchain, _, _) = setup().await;
+        let contract = deploy_erc20(blockchain.clone()).await;
+        let balance_key = format!("balance:{}", to_hex(*ACCOUNT_1));
+        let mint = ["String", &to_hex(*ACCOUNT_1), "U64", "10"];
+        let mint = new_contract_call(contract, "mint", &mint, blockchain.clone()).await;
+        send_and_process(mint, blockchain.clone()).await;
+        let block = blockchain.lock().await.get_current_block().unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_proof(&mut module).unwrap();
+
+        let keys = vec![balance_key.clone(), "unset".to_string()];
+        let proof: AccountProof = module
+            .call("eth_getProof", (contract, keys, "latest"))
+            .await
+            .unwrap();
+        assert_eq!(proof.storage_proof[0].value.as_deref(), Some("10"));
+        assert_eq!(proof.storage_proof[1].value, None);
+        proof.verify(&block).unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.storage_proof[0].value = Some("1000".into());
+        assert!(tampered.verify(&block).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.balance += 1.into();
+        assert!(tampered.verify(&block).is_err());
+
+        let forged = Block {
+            state_root: H256::random(),
+            ..block
+        };
+        assert!(proof.verify(&forged).is_err());
+    }
 }
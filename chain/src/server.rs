@@ -33,6 +33,9 @@ pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandl
     // generate keys if necessary
     add_keys()?;
 
+    // the node authors the blocks it produces
+    blockchain.lock().await.config.author = *ADDRESS;
+
     let addrs = addr.parse::<SocketAddr>()?;
     let server = ServerBuilder::default()
         .set_logger(Logger)
@@ -51,6 +54,12 @@ pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandl
     eth_get_transaction_receipt(&mut module)?;
     eth_get_transaction_count(&mut module)?;
     eth_get_code(&mut module)?;
+    eth_get_storage_at(&mut module)?;
+    eth_get_proof(&mut module)?;
+    debug_storage_keys(&mut module)?;
+    eth_get_contract_interface(&mut module)?;
+    debug_trace_transaction(&mut module)?;
+    eth_call(&mut module)?;
 
     let server_handle = server.start(module)?;
 
@@ -54,7 +54,7 @@ impl Storage {
         Ok(Self { db })
     }
 
-    pub(crate) fn _get_all_keys<K: AsRef<[u8]>>(&self) -> Result<Vec<Box<[u8]>>> {
+    pub(crate) fn _get_all_keys(&self) -> Result<Vec<Box<[u8]>>> {
         let value: Vec<Box<[u8]>> = self
             .db
             .iterator(rocksdb::IteratorMode::Start)
@@ -3,12 +3,14 @@ use crate::error::{ChainError, Result};
 use dashmap::DashMap;
 use ethereum_types::H256;
 use std::collections::VecDeque;
+use types::trace::TransactionTrace;
 use types::transaction::{Transaction, TransactionReceipt};
 
 #[derive(Debug)]
 pub(crate) struct TransactionStorage {
     pub(crate) mempool: VecDeque<Transaction>,
     pub(crate) receipts: DashMap<H256, TransactionReceipt>,
+    pub(crate) traces: DashMap<H256, TransactionTrace>,
 }
 
 impl TransactionStorage {
@@ -16,6 +18,7 @@ impl TransactionStorage {
         Self {
             mempool: VecDeque::new(),
             receipts: DashMap::new(),
+            traces: DashMap::new(),
         }
     }
 
@@ -35,6 +38,18 @@ impl TransactionStorage {
 
         Ok(transaction_receipt)
     }
+
+    // get the trace of the transaction
+    pub(crate) fn get_transaction_trace(&self, hash: &H256) -> Result<TransactionTrace> {
+        let transaction_trace = self
+            .traces
+            .get(hash)
+            .ok_or_else(|| ChainError::TransactionNotFound(hash.to_string()))?
+            .value()
+            .clone();
+
+        Ok(transaction_trace)
+    }
 }
 
 #[cfg(test)]
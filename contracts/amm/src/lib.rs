@@ -0,0 +1,372 @@
+//! A constant-product automated market maker, swapping native coin for tokens.
+//!
+//! The pool holds reserves of two assets: native coin, sent along with calls,
+//! and a token kept in the contract's own ledger.  Swaps keep the product of the
+//! reserves constant, less a fee that stays in the pool for liquidity providers.
+//!
+//! Liquidity providers receive shares of the pool, which are redeemed for their
+//! part of both reserves.  Every function moving assets takes a limit, so a call
+//! reverts instead of trading at a worse price than the caller expected.
+
+wit_bindgen::generate!("amm" in "../wit");
+
+#[path = "../../shared/helpers.rs"]
+mod helpers;
+
+use helpers::{address, read, write};
+
+/// Fees are in basis points, e.g. a fee of 30 is 0.3%
+const FEE_DENOMINATOR: u64 = 10_000;
+
+/// The account tokens are minted from in `Transfer` events
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+pub struct Amm;
+
+export_contract!(Amm);
+
+impl Contract for Amm {
+    fn construct(fee: u64) {
+        assert!(fee < FEE_DENOMINATOR, "the fee must be below 10000");
+
+        write("fee", fee);
+        host::set("owner", &host::caller());
+    }
+
+    fn owner() -> String {
+        host::get("owner").unwrap_or_default()
+    }
+
+    fn fee() -> u64 {
+        read("fee")
+    }
+
+    /// The coin and token reserves of the pool
+    fn reserves() -> Vec<u64> {
+        vec![read("coin_reserve"), read("token_reserve")]
+    }
+
+    fn total_shares() -> u64 {
+        read("total_shares")
+    }
+
+    fn shares_of(account: String) -> u64 {
+        read(&shares_key(&account.to_lowercase()))
+    }
+
+    fn token_balance_of(account: String) -> u64 {
+        read(&balance_key(&account.to_lowercase()))
+    }
+
+    /// The tokens a swap of `coin-in` would return at the current reserves
+    fn quote_coin_for_token(coin_in: u64) -> Result<u64, String> {
+        amount_out(coin_in, read("coin_reserve"), read("token_reserve"))
+    }
+
+    /// The coin a swap of `token-in` would return at the current reserves
+    fn quote_token_for_coin(token_in: u64) -> Result<u64, String> {
+        amount_out(token_in, read("token_reserve"), read("coin_reserve"))
+    }
+
+    fn mint(to: String, amount: u64) -> Result<(), String> {
+        non_payable()?;
+
+        if host::caller() != Self::owner() {
+            return Err("caller is not the owner".into());
+        }
+
+        let to = address(&to)?;
+        let supply = read("token_supply")
+            .checked_add(amount)
+            .ok_or("total supply overflow")?;
+
+        write("token_supply", supply);
+        credit(&to, amount);
+        host::emit(&["Transfer", ZERO_ADDRESS, &to], &[&amount.to_string()]);
+
+        Ok(())
+    }
+
+    fn transfer_token(to: String, amount: u64) -> Result<(), String> {
+        non_payable()?;
+
+        let (from, to) = (host::caller(), address(&to)?);
+
+        debit(&from, amount)?;
+        credit(&to, amount);
+        host::emit(&["Transfer", &from, &to], &[&amount.to_string()]);
+
+        Ok(())
+    }
+
+    /// Deposit the coin sent with the call and the matching amount of tokens,
+    /// at most `max-token`, returning the shares minted to the caller.
+    /// The first deposit sets the price, later deposits must match the reserve ratio.
+    fn add_liquidity(max_token: u64, min_shares: u64) -> Result<u64, String> {
+        let provider = host::caller();
+        let coin = host::value();
+        let (coin_reserve, token_reserve) = (read("coin_reserve"), read("token_reserve"));
+        let total_shares = Self::total_shares();
+
+        if coin == 0 {
+            return Err("coin must be sent to add liquidity".into());
+        }
+
+        let (token, shares) = match total_shares {
+            0 => (max_token, to_u64(sqrt(checked_mul(coin, max_token)?))?),
+            _ => {
+                // round the tokens up, so deposits never dilute the existing shares
+                let token =
+                    to_u64(checked_mul(coin, token_reserve)?.div_ceil(coin_reserve as u128))?;
+
+                if token > max_token {
+                    return Err(format!("the deposit requires {} tokens", token));
+                }
+
+                let shares = checked_mul(coin, total_shares)? / coin_reserve as u128;
+
+                (token, to_u64(shares)?)
+            }
+        };
+
+        if shares == 0 {
+            return Err("the deposit is too small".into());
+        }
+
+        if shares < min_shares {
+            return Err(format!("the deposit only mints {} shares", shares));
+        }
+
+        debit(&provider, token)?;
+        write("coin_reserve", checked_add(coin_reserve, coin)?);
+        write("token_reserve", checked_add(token_reserve, token)?);
+        write("total_shares", checked_add(total_shares, shares)?);
+        write(
+            &shares_key(&provider),
+            Self::shares_of(provider.clone()) + shares,
+        );
+        host::emit(
+            &["AddLiquidity", &provider],
+            &[&coin.to_string(), &token.to_string(), &shares.to_string()],
+        );
+
+        Ok(shares)
+    }
+
+    /// Redeem shares for their part of both reserves, returning the coin and tokens
+    fn remove_liquidity(shares: u64, min_coin: u64, min_token: u64) -> Result<Vec<u64>, String> {
+        non_payable()?;
+
+        if shares == 0 {
+            return Err("shares must be positive".into());
+        }
+
+        let provider = host::caller();
+        let (coin_reserve, token_reserve) = (read("coin_reserve"), read("token_reserve"));
+        let total_shares = Self::total_shares();
+        let provider_shares = Self::shares_of(provider.clone())
+            .checked_sub(shares)
+            .ok_or("insufficient shares")?;
+
+        // shares never exceed the total, so neither part exceeds its reserve
+        let coin = to_u64(checked_mul(shares, coin_reserve)? / total_shares as u128)?;
+        let token = to_u64(checked_mul(shares, token_reserve)? / total_shares as u128)?;
+
+        if coin < min_coin || token < min_token {
+            return Err(format!(
+                "the withdrawal only returns {} coin and {} tokens",
+                coin, token
+            ));
+        }
+
+        write(&shares_key(&provider), provider_shares);
+        write("total_shares", total_shares - shares);
+        write("coin_reserve", coin_reserve - coin);
+        write("token_reserve", token_reserve - token);
+        credit(&provider, token);
+        host::transfer(&provider, coin)?;
+        host::emit(
+            &["RemoveLiquidity", &provider],
+            &[&coin.to_string(), &token.to_string(), &shares.to_string()],
+        );
+
+        Ok(vec![coin, token])
+    }
+
+    /// Swap the coin sent with the call for at least `min-token-out` tokens
+    fn swap_coin_for_token(min_token_out: u64) -> Result<u64, String> {
+        let trader = host::caller();
+        let coin_in = host::value();
+        let (coin_reserve, token_reserve) = (read("coin_reserve"), read("token_reserve"));
+        let token_out = slippage(
+            amount_out(coin_in, coin_reserve, token_reserve)?,
+            min_token_out,
+        )?;
+
+        write("coin_reserve", checked_add(coin_reserve, coin_in)?);
+        write("token_reserve", token_reserve - token_out);
+        credit(&trader, token_out);
+        emit_swap(&trader, [coin_in, 0, 0, token_out]);
+
+        Ok(token_out)
+    }
+
+    /// Swap `token-in` tokens for at least `min-coin-out` coin
+    fn swap_token_for_coin(token_in: u64, min_coin_out: u64) -> Result<u64, String> {
+        non_payable()?;
+
+        let trader = host::caller();
+        let (coin_reserve, token_reserve) = (read("coin_reserve"), read("token_reserve"));
+        let coin_out = slippage(
+            amount_out(token_in, token_reserve, coin_reserve)?,
+            min_coin_out,
+        )?;
+
+        debit(&trader, token_in)?;
+        write("token_reserve", checked_add(token_reserve, token_in)?);
+        write("coin_reserve", coin_reserve - coin_out);
+        host::transfer(&trader, coin_out)?;
+        emit_swap(&trader, [0, token_in, coin_out, 0]);
+
+        Ok(coin_out)
+    }
+}
+
+/// The output of a swap, keeping the product of the reserves constant after
+/// the fee is taken from the input:
+///
+/// (reserve_in + amount_in * (1 - fee)) * (reserve_out - amount_out) = reserve_in * reserve_out
+fn amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64, String> {
+    if amount_in == 0 {
+        return Err("the amount must be positive".into());
+    }
+
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err("the pool has no liquidity".into());
+    }
+
+    // both terms are below 2^64 * 2^14, so neither the product nor the sum overflows
+    let amount_in = amount_in as u128 * (FEE_DENOMINATOR - Amm::fee()) as u128;
+    let amount_out = mul_div(
+        amount_in,
+        reserve_out as u128,
+        reserve_in as u128 * FEE_DENOMINATOR as u128 + amount_in,
+    );
+
+    // rounding down keeps the output below the reserve
+    to_u64(amount_out)
+}
+
+/// Ensure the output of a trade reaches the caller's minimum
+fn slippage(amount_out: u64, min_amount_out: u64) -> Result<u64, String> {
+    match amount_out >= min_amount_out {
+        true => Ok(amount_out),
+        false => Err(format!(
+            "the swap only returns {}, below the minimum of {}",
+            amount_out, min_amount_out
+        )),
+    }
+}
+
+/// Reject coin sent to functions that wouldn't account for it
+fn non_payable() -> Result<(), String> {
+    match host::value() {
+        0 => Ok(()),
+        _ => Err("the function doesn't accept coin".into()),
+    }
+}
+
+fn emit_swap(trader: &str, amounts: [u64; 4]) {
+    let amounts = amounts.map(|amount| amount.to_string());
+    let amounts = amounts.iter().map(String::as_str).collect::<Vec<_>>();
+
+    host::emit(&["Swap", trader], &amounts);
+}
+
+fn debit(account: &str, amount: u64) -> Result<(), String> {
+    let balance = read(&balance_key(account))
+        .checked_sub(amount)
+        .ok_or("insufficient token balance")?;
+
+    write(&balance_key(account), balance);
+
+    Ok(())
+}
+
+fn credit(account: &str, amount: u64) {
+    let key = balance_key(account);
+
+    // can't overflow, since no balance exceeds the total supply
+    write(&key, read(&key) + amount);
+}
+
+/// The integer square root, rounded down
+fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut root = value;
+    let mut next = value.div_ceil(2);
+
+    while next < root {
+        root = next;
+        next = (value / next + next) / 2;
+    }
+
+    root
+}
+
+/// `a * b / divisor`, rounded down, computing the product in 256 bits so it
+/// can't overflow.  The quotient must fit in 128 bits, which holds whenever
+/// `a` or `b` is at most the divisor.
+fn mul_div(a: u128, b: u128, divisor: u128) -> u128 {
+    const LOW: u128 = u64::MAX as u128;
+
+    let (a_high, a_low, b_high, b_low) = (a >> 64, a & LOW, b >> 64, b & LOW);
+    let (low_low, low_high, high_low) = (a_low * b_low, a_low * b_high, a_high * b_low);
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    let low = (middle << 64) | (low_low & LOW);
+    let high = a_high * b_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+
+    assert!(high < divisor, "the quotient overflows");
+
+    // long division of the 256 bit product, one bit at a time
+    let (mut quotient, mut remainder) = (0u128, high);
+
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+
+        if carry == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+
+    quotient
+}
+
+/// The product of two amounts, erring instead of wrapping on overflow
+fn checked_mul(a: u64, b: u64) -> Result<u128, String> {
+    (a as u128)
+        .checked_mul(b as u128)
+        .ok_or("amount overflow".into())
+}
+
+fn to_u64(value: u128) -> Result<u64, String> {
+    u64::try_from(value).map_err(|_| "amount overflow".into())
+}
+
+fn checked_add(reserve: u64, amount: u64) -> Result<u64, String> {
+    reserve.checked_add(amount).ok_or("reserve overflow".into())
+}
+
+fn balance_key(account: &str) -> String {
+    format!("balance:{}", account)
+}
+
+fn shares_key(account: &str) -> String {
+    format!("shares:{}", account)
+}
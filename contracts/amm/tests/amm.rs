@@ -0,0 +1,38 @@
+//! Run the amm in the runtime's test harness.
+//! The contract must be built first, see the runtime README.
+
+use runtime::harness::{to_hex, Harness, U256};
+
+const AMM: &[u8] = include_bytes!("./../../../target/wasm32-unknown-unknown/release/amm_wit.wasm");
+
+#[test]
+fn it_swaps_18_decimal_amounts() {
+    const ONE: u64 = 1_000_000_000_000_000_000;
+
+    let mut harness = Harness::new();
+    let amm = harness.deploy(AMM, &["U64", "30"]).unwrap();
+    let caller = to_hex(harness.caller());
+    harness.set_balance(harness.caller(), U256::from(ONE) * 2);
+
+    harness
+        .call(amm, "mint", &["String", &caller, "U64", &ONE.to_string()])
+        .unwrap();
+    harness.set_value(U256::from(ONE));
+    harness
+        .call(amm, "add-liquidity", &["U64", &ONE.to_string(), "U64", "0"])
+        .unwrap();
+
+    // 1e18 * 9970 * 1e18 overflows 128 bits: 1e18 * 9970 / 19970 = 4.9925e17
+    assert_eq!(
+        harness
+            .call(amm, "swap-coin-for-token", &["U64", "0"])
+            .unwrap(),
+        ["U64", "499248873309964947"]
+    );
+
+    harness.set_value(U256::zero());
+    assert_eq!(
+        harness.view(amm, "reserves", &[]).unwrap(),
+        ["List<U64>", "[2000000000000000000,500751126690035053]"]
+    );
+}
@@ -0,0 +1,256 @@
+//! A multi-asset token with ERC-1155 semantics.
+//!
+//! A single contract holds any number of token ids, where each id is either
+//! fungible or non-fungible (a supply of exactly one).
+//! Balances and operator approvals are kept in the contract's persistent storage.
+//! Only the deployer (the owner of the contract) can mint new tokens.
+//!
+//! A batch is applied in a single call, and an error reverts the whole call,
+//! so a batch either moves every token or none of them.
+
+// the generated export of `safe-batch-transfer-from` takes a pointer and length per list
+#![allow(clippy::too_many_arguments)]
+
+wit_bindgen::generate!("erc1155" in "../wit");
+
+#[path = "../../shared/helpers.rs"]
+mod helpers;
+
+use helpers::{address, read, write};
+
+/// The account tokens are minted from in `TransferSingle` events
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// The functions a contract must export to receive tokens
+const RECEIVER: &str = "on-erc1155-received";
+const BATCH_RECEIVER: &str = "on-erc1155-batch-received";
+
+pub struct Erc1155;
+
+export_contract!(Erc1155);
+
+impl Contract for Erc1155 {
+    fn construct(uri: String) {
+        host::set("uri", &uri);
+        host::set("owner", &host::caller());
+    }
+
+    fn owner() -> String {
+        host::get("owner").unwrap_or_default()
+    }
+
+    /// The same uri is used for every id, clients replace `{id}` with the token id
+    fn uri(_id: u64) -> String {
+        host::get("uri").unwrap_or_default()
+    }
+
+    fn total_supply(id: u64) -> u64 {
+        read(&supply_key(id))
+    }
+
+    fn is_non_fungible(id: u64) -> bool {
+        host::get(&non_fungible_key(id)).is_some()
+    }
+
+    fn balance_of(account: String, id: u64) -> u64 {
+        read(&balance_key(&account.to_lowercase(), id))
+    }
+
+    fn balance_of_batch(accounts: Vec<String>, ids: Vec<u64>) -> Result<Vec<u64>, String> {
+        if accounts.len() != ids.len() {
+            return Err("accounts and ids must have the same length".into());
+        }
+
+        Ok(accounts
+            .into_iter()
+            .zip(ids)
+            .map(|(account, id)| Self::balance_of(account, id))
+            .collect())
+    }
+
+    fn is_approved_for_all(account: String, operator: String) -> bool {
+        let key = operator_key(&account.to_lowercase(), &operator.to_lowercase());
+
+        host::get(&key).as_deref() == Some("true")
+    }
+
+    fn set_approval_for_all(operator: String, approved: bool) -> Result<(), String> {
+        let account = host::caller();
+        let operator = address(&operator)?;
+
+        if operator == account {
+            return Err("an account can't be its own operator".into());
+        }
+
+        host::set(&operator_key(&account, &operator), &approved.to_string());
+        host::emit(
+            &["ApprovalForAll", &account, &operator],
+            &[&approved.to_string()],
+        );
+
+        Ok(())
+    }
+
+    fn mint(to: String, id: u64, amount: u64) -> Result<(), String> {
+        if Self::is_non_fungible(id) {
+            return Err(format!("token {} is non-fungible", id));
+        }
+
+        mint(&to, id, amount)
+    }
+
+    /// Mint the only token of an id, which can't be minted again
+    fn mint_non_fungible(to: String, id: u64) -> Result<(), String> {
+        if Self::total_supply(id) > 0 {
+            return Err(format!("token {} already exists", id));
+        }
+
+        mint(&to, id, 1)?;
+        host::set(&non_fungible_key(id), "true");
+
+        Ok(())
+    }
+
+    fn safe_transfer_from(from: String, to: String, id: u64, amount: u64) -> Result<(), String> {
+        let (from, to) = (address(&from)?, recipient(&to)?);
+        let operator = authorize(&from)?;
+
+        move_tokens(&from, &to, id, amount)?;
+        host::emit(
+            &["TransferSingle", &operator, &from, &to],
+            &[&id.to_string(), &amount.to_string()],
+        );
+
+        if host::is_contract(&to) {
+            let params = [
+                "String",
+                &operator,
+                "String",
+                &from,
+                "U64",
+                &id.to_string(),
+                "U64",
+                &amount.to_string(),
+            ];
+
+            host::call(&to, RECEIVER, &params, 0, u64::MAX)
+                .map_err(|error| format!("{} rejected token {}: {}", to, id, error))?;
+        }
+
+        Ok(())
+    }
+
+    fn safe_batch_transfer_from(
+        from: String,
+        to: String,
+        ids: Vec<u64>,
+        amounts: Vec<u64>,
+    ) -> Result<(), String> {
+        let (from, to) = (address(&from)?, recipient(&to)?);
+        let operator = authorize(&from)?;
+
+        if ids.len() != amounts.len() {
+            return Err("ids and amounts must have the same length".into());
+        }
+
+        for (id, amount) in ids.iter().zip(&amounts) {
+            move_tokens(&from, &to, *id, *amount)?;
+        }
+
+        let (ids, amounts) = (to_json(&ids), to_json(&amounts));
+        host::emit(&["TransferBatch", &operator, &from, &to], &[&ids, &amounts]);
+
+        if host::is_contract(&to) {
+            let params = [
+                "String",
+                &operator,
+                "String",
+                &from,
+                "List<U64>",
+                &ids,
+                "List<U64>",
+                &amounts,
+            ];
+
+            host::call(&to, BATCH_RECEIVER, &params, 0, u64::MAX)
+                .map_err(|error| format!("{} rejected tokens {}: {}", to, ids, error))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn mint(to: &str, id: u64, amount: u64) -> Result<(), String> {
+    let caller = host::caller();
+
+    if caller != Erc1155::owner() {
+        return Err("caller is not the owner".into());
+    }
+
+    let to = recipient(to)?;
+    let supply = read(&supply_key(id))
+        .checked_add(amount)
+        .ok_or("total supply overflow")?;
+
+    // can't overflow, since no balance exceeds the total supply
+    write(&supply_key(id), supply);
+    write(&balance_key(&to, id), read(&balance_key(&to, id)) + amount);
+    host::emit(
+        &["TransferSingle", &caller, ZERO_ADDRESS, &to],
+        &[&id.to_string(), &amount.to_string()],
+    );
+
+    Ok(())
+}
+
+/// Ensure the caller can move the tokens of the account, returning the caller
+fn authorize(account: &str) -> Result<String, String> {
+    let caller = host::caller();
+
+    match caller == account || Erc1155::is_approved_for_all(account.into(), caller.clone()) {
+        true => Ok(caller),
+        false => Err("caller is not the owner or an approved operator".into()),
+    }
+}
+
+fn move_tokens(from: &str, to: &str, id: u64, amount: u64) -> Result<(), String> {
+    let balance = read(&balance_key(from, id))
+        .checked_sub(amount)
+        .ok_or_else(|| format!("insufficient balance for token {}", id))?;
+
+    write(&balance_key(from, id), balance);
+    write(&balance_key(to, id), read(&balance_key(to, id)) + amount);
+
+    Ok(())
+}
+
+/// Validate the address of an account receiving tokens, which can't be the zero address
+fn recipient(account: &str) -> Result<String, String> {
+    match address(account)? {
+        account if account == ZERO_ADDRESS => Err("can't transfer to the zero address".into()),
+        account => Ok(account),
+    }
+}
+
+/// Encode a list of values as the JSON array used for list params
+fn to_json(values: &[u64]) -> String {
+    let values = values.iter().map(u64::to_string).collect::<Vec<_>>();
+
+    format!("[{}]", values.join(","))
+}
+
+fn balance_key(account: &str, id: u64) -> String {
+    format!("balance:{}:{}", id, account)
+}
+
+fn supply_key(id: u64) -> String {
+    format!("supply:{}", id)
+}
+
+fn non_fungible_key(id: u64) -> String {
+    format!("non_fungible:{}", id)
+}
+
+fn operator_key(account: &str, operator: &str) -> String {
+    format!("operator:{}:{}", account, operator)
+}
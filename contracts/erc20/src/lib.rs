@@ -1,28 +1,131 @@
-#![allow(unused)]
-use std::collections::HashMap;
+//! A fungible token with ERC-20 semantics.
+//!
+//! Balances, allowances and the total supply are kept in the contract's
+//! persistent storage, with amounts stored as decimal strings.
+//! Only the deployer (the owner) can mint new tokens.
 
-wit_bindgen::generate!("erc20");
+wit_bindgen::generate!("erc20" in "../wit");
 
-pub struct Erc20;
+#[path = "../../shared/helpers.rs"]
+mod helpers;
 
-pub struct State {
-    name: String,
-    symbol: String,
-    balances: HashMap<String, u64>,
-}
+use helpers::{address, read, write};
+
+/// The account tokens are minted from in `Transfer` events
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+pub struct Erc20;
 
 export_contract!(Erc20);
 
 impl Contract for Erc20 {
     fn construct(name: String, symbol: String) {
-        println!("name {}, symbol", symbol);
+        assert!(!name.is_empty(), "name is required");
+        assert!(!symbol.is_empty(), "symbol is required");
+
+        host::set("name", &name);
+        host::set("symbol", &symbol);
+        host::set("owner", &host::caller());
+    }
+
+    fn name() -> String {
+        host::get("name").unwrap_or_default()
+    }
+
+    fn symbol() -> String {
+        host::get("symbol").unwrap_or_default()
+    }
+
+    fn owner() -> String {
+        host::get("owner").unwrap_or_default()
+    }
+
+    fn total_supply() -> u64 {
+        read("total_supply")
+    }
+
+    fn balance_of(account: String) -> u64 {
+        read(&balance_key(&account.to_lowercase()))
+    }
+
+    fn allowance(owner: String, spender: String) -> u64 {
+        read(&allowance_key(
+            &owner.to_lowercase(),
+            &spender.to_lowercase(),
+        ))
+    }
+
+    fn mint(account: String, amount: u64) -> Result<(), String> {
+        if host::caller() != Self::owner() {
+            return Err("caller is not the owner".into());
+        }
+
+        let account = address(&account)?;
+        let total_supply = read("total_supply")
+            .checked_add(amount)
+            .ok_or("total supply overflow")?;
+
+        write("total_supply", total_supply);
+        credit(&account, amount);
+        host::emit(
+            &["Transfer", ZERO_ADDRESS, &account],
+            &[&amount.to_string()],
+        );
+
+        Ok(())
+    }
+
+    fn transfer(to: String, amount: u64) -> Result<(), String> {
+        move_tokens(&host::caller(), &address(&to)?, amount)
     }
 
-    fn mint(account: String, amount: u64) {
-        println!("account {}, amount", amount);
+    fn approve(spender: String, amount: u64) -> Result<(), String> {
+        let owner = host::caller();
+        let spender = address(&spender)?;
+
+        write(&allowance_key(&owner, &spender), amount);
+        host::emit(&["Approval", &owner, &spender], &[&amount.to_string()]);
+
+        Ok(())
     }
 
-    fn transfer(to: String, amount: u64) {
-        println!("to {}, amount", amount);
+    fn transfer_from(owner: String, to: String, amount: u64) -> Result<(), String> {
+        let owner = address(&owner)?;
+        let key = allowance_key(&owner, &host::caller());
+        let allowance = read(&key)
+            .checked_sub(amount)
+            .ok_or("insufficient allowance")?;
+
+        move_tokens(&owner, &address(&to)?, amount)?;
+        write(&key, allowance);
+
+        Ok(())
     }
 }
+
+fn move_tokens(from: &str, to: &str, amount: u64) -> Result<(), String> {
+    let balance = read(&balance_key(from))
+        .checked_sub(amount)
+        .ok_or("insufficient balance")?;
+
+    write(&balance_key(from), balance);
+    credit(to, amount);
+    host::emit(&["Transfer", from, to], &[&amount.to_string()]);
+
+    Ok(())
+}
+
+fn credit(account: &str, amount: u64) {
+    let key = balance_key(account);
+
+    // can't overflow, since no balance exceeds the total supply
+    write(&key, read(&key) + amount);
+}
+
+fn balance_key(account: &str) -> String {
+    format!("balance:{}", account)
+}
+
+fn allowance_key(owner: &str, spender: &str) -> String {
+    format!("allowance:{}:{}", owner, spender)
+}
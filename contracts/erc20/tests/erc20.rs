@@ -0,0 +1,65 @@
+//! Run the erc20 in the runtime's test harness.
+//! The contract must be built first, see the runtime README.
+
+use runtime::harness::{to_hex, Account, Harness};
+
+const ERC20: &[u8] =
+    include_bytes!("./../../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+
+/// An erc20 deployed by the harness' caller, who minted 100 tokens to themselves
+fn erc20() -> (Harness, Account) {
+    let mut harness = Harness::new();
+    let erc20 = harness
+        .deploy(ERC20, &["String", "Rust Coin", "String", "RustCoin"])
+        .unwrap();
+    let owner = to_hex(harness.caller());
+    harness
+        .call(erc20, "mint", &["String", &owner, "U64", "100"])
+        .unwrap();
+
+    (harness, erc20)
+}
+
+fn balance_of(harness: &mut Harness, erc20: Account, account: &str) -> Vec<String> {
+    harness
+        .view(erc20, "balance-of", &["String", account])
+        .unwrap()
+}
+
+#[test]
+fn it_transfers_tokens() {
+    let (mut harness, erc20) = erc20();
+    let (owner, to) = (to_hex(harness.caller()), to_hex(Account::random()));
+
+    harness
+        .call(erc20, "transfer", &["String", &to, "U64", "30"])
+        .unwrap();
+
+    assert_eq!(balance_of(&mut harness, erc20, &owner), ["U64", "70"]);
+    assert_eq!(balance_of(&mut harness, erc20, &to), ["U64", "30"]);
+    assert_eq!(harness.events()[1].topics, ["Transfer", &owner, &to]);
+}
+
+#[test]
+fn spenders_transfer_within_their_allowance() {
+    let (mut harness, erc20) = erc20();
+    let owner = to_hex(harness.caller());
+    let spender = Account::random();
+    let to = to_hex(Account::random());
+
+    harness
+        .call(erc20, "approve", &["String", &to_hex(spender), "U64", "20"])
+        .unwrap();
+    harness.set_caller(spender);
+
+    let over_allowance = ["String", &owner, "String", &to, "U64", "21"];
+    assert!(harness
+        .call(erc20, "transfer-from", &over_allowance)
+        .is_err());
+
+    let within_allowance = ["String", &owner, "String", &to, "U64", "20"];
+    harness
+        .call(erc20, "transfer-from", &within_allowance)
+        .unwrap();
+    assert_eq!(balance_of(&mut harness, erc20, &to), ["U64", "20"]);
+}
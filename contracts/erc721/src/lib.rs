@@ -0,0 +1,202 @@
+//! A non-fungible token with ERC-721 semantics.
+//!
+//! The owner of each token, the number of tokens held by each account and the
+//! approvals are kept in the contract's persistent storage.
+//! Only the deployer (the owner of the contract) can mint new tokens.
+
+wit_bindgen::generate!("erc721" in "../wit");
+
+#[path = "../../shared/helpers.rs"]
+mod helpers;
+
+use helpers::{address, read, write};
+
+/// The account tokens are minted from in `Transfer` events
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// The function a contract must export to receive tokens through `safe-transfer-from`
+const RECEIVER: &str = "on-erc721-received";
+
+pub struct Erc721;
+
+export_contract!(Erc721);
+
+impl Contract for Erc721 {
+    fn construct(name: String, symbol: String, base_uri: String) {
+        assert!(!name.is_empty(), "name is required");
+        assert!(!symbol.is_empty(), "symbol is required");
+
+        host::set("name", &name);
+        host::set("symbol", &symbol);
+        host::set("base_uri", &base_uri);
+        host::set("owner", &host::caller());
+    }
+
+    fn name() -> String {
+        host::get("name").unwrap_or_default()
+    }
+
+    fn symbol() -> String {
+        host::get("symbol").unwrap_or_default()
+    }
+
+    fn owner() -> String {
+        host::get("owner").unwrap_or_default()
+    }
+
+    fn balance_of(owner: String) -> u64 {
+        read(&balance_key(&owner.to_lowercase()))
+    }
+
+    fn owner_of(token_id: u64) -> Result<String, String> {
+        host::get(&owner_key(token_id)).ok_or_else(|| format!("token {} doesn't exist", token_id))
+    }
+
+    fn token_uri(token_id: u64) -> Result<String, String> {
+        Self::owner_of(token_id)?;
+
+        Ok(format!(
+            "{}{}",
+            host::get("base_uri").unwrap_or_default(),
+            token_id
+        ))
+    }
+
+    fn get_approved(token_id: u64) -> Result<String, String> {
+        Self::owner_of(token_id)?;
+
+        Ok(host::get(&approved_key(token_id)).unwrap_or_else(|| ZERO_ADDRESS.into()))
+    }
+
+    fn is_approved_for_all(owner: String, operator: String) -> bool {
+        let key = operator_key(&owner.to_lowercase(), &operator.to_lowercase());
+
+        host::get(&key).as_deref() == Some("true")
+    }
+
+    fn mint(to: String, token_id: u64) -> Result<(), String> {
+        if host::caller() != Self::owner() {
+            return Err("caller is not the owner".into());
+        }
+
+        let to = recipient(&to)?;
+
+        if host::get(&owner_key(token_id)).is_some() {
+            return Err(format!("token {} already exists", token_id));
+        }
+
+        host::set(&owner_key(token_id), &to);
+        write(&balance_key(&to), read(&balance_key(&to)) + 1);
+        host::emit(&["Transfer", ZERO_ADDRESS, &to], &[&token_id.to_string()]);
+
+        Ok(())
+    }
+
+    fn approve(approved: String, token_id: u64) -> Result<(), String> {
+        let owner = Self::owner_of(token_id)?;
+        let caller = host::caller();
+        let approved = address(&approved)?;
+
+        if caller != owner && !Self::is_approved_for_all(owner.clone(), caller) {
+            return Err("caller is not the token owner or an approved operator".into());
+        }
+
+        host::set(&approved_key(token_id), &approved);
+        host::emit(&["Approval", &owner, &approved], &[&token_id.to_string()]);
+
+        Ok(())
+    }
+
+    fn set_approval_for_all(operator: String, approved: bool) -> Result<(), String> {
+        let owner = host::caller();
+        let operator = address(&operator)?;
+
+        if operator == owner {
+            return Err("the owner can't be its own operator".into());
+        }
+
+        host::set(&operator_key(&owner, &operator), &approved.to_string());
+        host::emit(
+            &["ApprovalForAll", &owner, &operator],
+            &[&approved.to_string()],
+        );
+
+        Ok(())
+    }
+
+    fn transfer_from(from: String, to: String, token_id: u64) -> Result<(), String> {
+        transfer(&address(&from)?, &recipient(&to)?, token_id)
+    }
+
+    /// Transfer a token, requiring a contract recipient to export `on-erc721-received`
+    /// so tokens can't get stuck in contracts that don't know about them
+    fn safe_transfer_from(from: String, to: String, token_id: u64) -> Result<(), String> {
+        let (from, to) = (address(&from)?, recipient(&to)?);
+
+        transfer(&from, &to, token_id)?;
+
+        if host::is_contract(&to) {
+            let params = [
+                "String",
+                &host::caller(),
+                "String",
+                &from,
+                "U64",
+                &token_id.to_string(),
+            ];
+
+            host::call(&to, RECEIVER, &params, 0, u64::MAX)
+                .map_err(|error| format!("{} rejected token {}: {}", to, token_id, error))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn transfer(from: &str, to: &str, token_id: u64) -> Result<(), String> {
+    let owner = Erc721::owner_of(token_id)?;
+    let caller = host::caller();
+
+    if owner != from {
+        return Err(format!("token {} isn't owned by {}", token_id, from));
+    }
+
+    let is_approved = host::get(&approved_key(token_id)).as_deref() == Some(caller.as_str());
+
+    if caller != owner && !is_approved && !Erc721::is_approved_for_all(owner, caller.clone()) {
+        return Err("caller is not the token owner or approved".into());
+    }
+
+    // approvals don't carry over to the new owner
+    host::set(&approved_key(token_id), ZERO_ADDRESS);
+    host::set(&owner_key(token_id), to);
+    write(&balance_key(from), read(&balance_key(from)) - 1);
+    write(&balance_key(to), read(&balance_key(to)) + 1);
+    host::emit(&["Transfer", from, to], &[&token_id.to_string()]);
+
+    Ok(())
+}
+
+/// Validate the address of an account receiving a token, which can't be the zero address
+fn recipient(account: &str) -> Result<String, String> {
+    match address(account)? {
+        account if account == ZERO_ADDRESS => Err("can't transfer to the zero address".into()),
+        account => Ok(account),
+    }
+}
+
+fn owner_key(token_id: u64) -> String {
+    format!("owner:{}", token_id)
+}
+
+fn balance_key(owner: &str) -> String {
+    format!("balance:{}", owner)
+}
+
+fn approved_key(token_id: u64) -> String {
+    format!("approved:{}", token_id)
+}
+
+fn operator_key(owner: &str, operator: &str) -> String {
+    format!("operator:{}:{}", owner, operator)
+}
@@ -0,0 +1,109 @@
+//! Forwards calls to other contracts, used to exercise cross-contract calls.
+//! Also exposes the crypto host functions, taking and returning bytes as hex.
+
+// the generated bindings take every param of the host `call` function
+#![allow(clippy::too_many_arguments)]
+
+wit_bindgen::generate!("forwarder" in "../wit");
+
+pub struct Forwarder;
+
+export_contract!(Forwarder);
+
+impl Contract for Forwarder {
+    /// The deployer is the admin, who can upgrade and destroy the forwarder
+    fn construct() {
+        host::set("admin", &host::caller());
+    }
+
+    /// Accept value sent without a call, so the forwarder can be funded
+    fn receive() {}
+
+    /// Replace the forwarder's code with code on the chain
+    fn upgrade(code_hash: String) -> Result<(), String> {
+        only_admin()?;
+        host::set_code(&code_hash)
+    }
+
+    /// Remove the forwarder, sending its balance to the beneficiary
+    fn destroy(beneficiary: String) -> Result<(), String> {
+        only_admin()?;
+        host::self_destruct(&beneficiary)
+    }
+
+    /// Call another contract, reverting if the callee reverts
+    fn forward(
+        address: String,
+        function: String,
+        params: Vec<String>,
+        value: u64,
+        gas: u64,
+    ) -> Vec<String> {
+        let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+        host::call(&address, &function, &params, value, gas)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Call another contract, returning the error if the callee reverts
+    fn try_forward(
+        address: String,
+        function: String,
+        params: Vec<String>,
+        value: u64,
+        gas: u64,
+    ) -> Option<String> {
+        let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+        host::call(&address, &function, &params, value, gas).err()
+    }
+
+    /// Deploy a contract from code on the chain, returning its address.
+    /// An empty salt derives the address from the forwarder's nonce.
+    fn create(code_hash: String, args: Vec<String>, salt: String) -> Result<String, String> {
+        let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+        let salt = (!salt.is_empty()).then_some(salt.as_str());
+        host::create_from_hash(&code_hash, &args, salt, u64::MAX)
+    }
+
+    /// Call itself until the call depth limit is reached, returning the deepest depth
+    fn recurse(depth: u64) -> u64 {
+        let next = (depth + 1).to_string();
+        match host::call(&host::address(), "recurse", &["U64", &next], 0, u64::MAX) {
+            Ok(result) => result[1].parse().unwrap(),
+            Err(_) => depth,
+        }
+    }
+
+    fn echo(message: String) -> String {
+        host::debug_print(&message);
+        message
+    }
+
+    fn fail() {
+        host::debug_print("failing");
+        panic!("failed");
+    }
+
+    fn hash(data: String) -> String {
+        hex::encode(host::keccak256(&decode(&data)))
+    }
+
+    fn recover(message: String, signature: String, recovery_id: u64) -> Result<String, String> {
+        let recovery_id = u8::try_from(recovery_id).map_err(|e| e.to_string())?;
+        host::ecrecover(&decode(&message), &decode(&signature), recovery_id)
+    }
+
+    fn verify_signature(message: String, signature: String, public_key: String) -> bool {
+        host::verify(&decode(&message), &decode(&signature), &decode(&public_key))
+    }
+}
+
+fn only_admin() -> Result<(), String> {
+    match host::get("admin") == Some(host::caller()) {
+        true => Ok(()),
+        false => Err("caller is not the admin".into()),
+    }
+}
+
+fn decode(bytes: &str) -> Vec<u8> {
+    hex::decode(bytes.trim_start_matches("0x")).unwrap_or_else(|error| panic!("{}", error))
+}
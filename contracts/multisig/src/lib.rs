@@ -0,0 +1,302 @@
+//! A multisig wallet requiring M of N owners to approve a proposal.
+//!
+//! The wallet holds a native balance, which anyone can deposit to with a plain
+//! transfer.  Any owner can submit a proposal, which is
+//! either a plain value transfer (an empty function) or a call to a contract.
+//! Once enough owners confirm it, any owner can execute the proposal.
+//!
+//! Owners and the threshold can only be changed by the wallet itself, so changing
+//! them takes a proposal calling `add-owner`, `remove-owner` or `change-threshold`
+//! on the wallet's own address.
+
+wit_bindgen::generate!("multisig" in "../wit");
+
+#[path = "../../shared/helpers.rs"]
+mod helpers;
+
+use helpers::{address, read, write};
+
+pub struct Multisig;
+
+export_contract!(Multisig);
+
+impl Contract for Multisig {
+    fn construct(owners: Vec<String>, threshold: u64) {
+        let owners = owners
+            .iter()
+            .map(|owner| address(owner))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        assert!(!owners.is_empty(), "owners are required");
+        assert!(
+            (1..=owners.len() as u64).contains(&threshold),
+            "the threshold must be between 1 and the number of owners"
+        );
+        assert!(
+            owners
+                .iter()
+                .enumerate()
+                .all(|(index, owner)| !owners[..index].contains(owner)),
+            "owners must be unique"
+        );
+
+        set_owners(&owners);
+        write("threshold", threshold);
+    }
+
+    /// Accept value sent without a call
+    fn receive() {
+        host::emit(&["Deposit", &host::caller()], &[&host::value().to_string()]);
+    }
+
+    fn owners() -> Vec<String> {
+        match host::get("owners") {
+            Some(owners) if !owners.is_empty() => owners.split(',').map(String::from).collect(),
+            _ => vec![],
+        }
+    }
+
+    fn threshold() -> u64 {
+        read("threshold")
+    }
+
+    fn is_owner(account: String) -> bool {
+        Self::owners().contains(&account.to_lowercase())
+    }
+
+    fn proposal_count() -> u64 {
+        read("proposal_count")
+    }
+
+    /// The proposal's recipient, value, function and whether it was executed
+    fn proposal(id: u64) -> Result<Vec<String>, String> {
+        exists(id)?;
+
+        Ok(vec![
+            host::get(&proposal_key(id, "to")).unwrap_or_default(),
+            read(&proposal_key(id, "value")).to_string(),
+            host::get(&proposal_key(id, "function")).unwrap_or_default(),
+            is_executed(id).to_string(),
+        ])
+    }
+
+    /// The number of current owners that confirmed the proposal
+    fn confirmations(id: u64) -> Result<u64, String> {
+        exists(id)?;
+
+        Ok(Self::owners()
+            .into_iter()
+            .filter(|owner| Self::is_confirmed(id, owner.into()))
+            .count() as u64)
+    }
+
+    fn is_confirmed(id: u64, owner: String) -> bool {
+        host::get(&confirmation_key(id, &owner.to_lowercase())).as_deref() == Some("true")
+    }
+
+    /// Submit a proposal, which is confirmed by the submitter.
+    /// An empty function transfers the value, otherwise the function of the
+    /// contract at `to` is called with the params and value.
+    fn submit(
+        to: String,
+        value: u64,
+        function: String,
+        params: Vec<String>,
+    ) -> Result<u64, String> {
+        let owner = only_owner()?;
+        let to = address(&to)?;
+        let id = Self::proposal_count();
+
+        host::set(&proposal_key(id, "to"), &to);
+        write(&proposal_key(id, "value"), value);
+        host::set(&proposal_key(id, "function"), &function);
+        write(&proposal_key(id, "params"), params.len() as u64);
+
+        for (index, param) in params.iter().enumerate() {
+            host::set(&proposal_key(id, &format!("param:{}", index)), param);
+        }
+
+        write("proposal_count", id + 1);
+        host::emit(&["Submission", &owner], &[&id.to_string()]);
+        confirm(id, &owner)?;
+
+        Ok(id)
+    }
+
+    fn confirm(id: u64) -> Result<(), String> {
+        let owner = only_owner()?;
+
+        if Self::is_confirmed(id, owner.clone()) {
+            return Err(format!("proposal {} is already confirmed by {}", id, owner));
+        }
+
+        confirm(id, &owner)
+    }
+
+    fn revoke_confirmation(id: u64) -> Result<(), String> {
+        let owner = only_owner()?;
+
+        pending(id)?;
+
+        if !Self::is_confirmed(id, owner.clone()) {
+            return Err(format!("proposal {} isn't confirmed by {}", id, owner));
+        }
+
+        host::set(&confirmation_key(id, &owner), "false");
+        host::emit(&["Revocation", &owner], &[&id.to_string()]);
+
+        Ok(())
+    }
+
+    fn execute(id: u64) -> Result<(), String> {
+        let owner = only_owner()?;
+
+        pending(id)?;
+
+        let confirmations = Self::confirmations(id)?;
+        let threshold = Self::threshold();
+
+        if confirmations < threshold {
+            return Err(format!(
+                "proposal {} has {} of {} confirmations",
+                id, confirmations, threshold
+            ));
+        }
+
+        // mark the proposal as executed before calling out, so the callee can't
+        // execute it again, an error reverts the flag along with everything else
+        host::set(&proposal_key(id, "executed"), "true");
+
+        let to = host::get(&proposal_key(id, "to")).unwrap_or_default();
+        let value = read(&proposal_key(id, "value"));
+        let function = host::get(&proposal_key(id, "function")).unwrap_or_default();
+
+        if function.is_empty() {
+            host::transfer(&to, value)?;
+        } else {
+            let params = (0..read(&proposal_key(id, "params")))
+                .map(|index| host::get(&proposal_key(id, &format!("param:{}", index))))
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default();
+            let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+
+            host::call(&to, &function, &params, value, u64::MAX)?;
+        }
+
+        host::emit(&["Execution", &owner], &[&id.to_string()]);
+
+        Ok(())
+    }
+
+    fn add_owner(owner: String) -> Result<(), String> {
+        only_wallet()?;
+
+        let owner = address(&owner)?;
+        let mut owners = Self::owners();
+
+        if owners.contains(&owner) {
+            return Err(format!("{} is already an owner", owner));
+        }
+
+        owners.push(owner.clone());
+        set_owners(&owners);
+        host::emit(&["OwnerAddition", &owner], &[]);
+
+        Ok(())
+    }
+
+    fn remove_owner(owner: String) -> Result<(), String> {
+        only_wallet()?;
+
+        let owner = owner.to_lowercase();
+        let mut owners = Self::owners();
+
+        if !owners.contains(&owner) {
+            return Err(format!("{} is not an owner", owner));
+        }
+
+        if owners.len() as u64 <= Self::threshold() {
+            return Err("removing the owner would make the threshold unreachable".into());
+        }
+
+        owners.retain(|existing| *existing != owner);
+        set_owners(&owners);
+        host::emit(&["OwnerRemoval", &owner], &[]);
+
+        Ok(())
+    }
+
+    fn change_threshold(threshold: u64) -> Result<(), String> {
+        only_wallet()?;
+
+        if !(1..=Self::owners().len() as u64).contains(&threshold) {
+            return Err("the threshold must be between 1 and the number of owners".into());
+        }
+
+        write("threshold", threshold);
+        host::emit(&["ThresholdChange"], &[&threshold.to_string()]);
+
+        Ok(())
+    }
+}
+
+fn confirm(id: u64, owner: &str) -> Result<(), String> {
+    pending(id)?;
+
+    host::set(&confirmation_key(id, owner), "true");
+    host::emit(&["Confirmation", owner], &[&id.to_string()]);
+
+    Ok(())
+}
+
+/// Ensure the caller is an owner, returning the caller
+fn only_owner() -> Result<String, String> {
+    let caller = host::caller();
+
+    match Multisig::is_owner(caller.clone()) {
+        true => Ok(caller),
+        false => Err("caller is not an owner".into()),
+    }
+}
+
+/// Ensure the caller is the wallet itself, through an executed proposal
+fn only_wallet() -> Result<(), String> {
+    match host::caller() == host::address() {
+        true => Ok(()),
+        false => Err("only the wallet can change its configuration".into()),
+    }
+}
+
+fn exists(id: u64) -> Result<(), String> {
+    match id < Multisig::proposal_count() {
+        true => Ok(()),
+        false => Err(format!("proposal {} doesn't exist", id)),
+    }
+}
+
+/// Ensure the proposal exists and wasn't executed yet
+fn pending(id: u64) -> Result<(), String> {
+    exists(id)?;
+
+    match is_executed(id) {
+        true => Err(format!("proposal {} was already executed", id)),
+        false => Ok(()),
+    }
+}
+
+fn is_executed(id: u64) -> bool {
+    host::get(&proposal_key(id, "executed")).as_deref() == Some("true")
+}
+
+fn set_owners(owners: &[String]) {
+    host::set("owners", &owners.join(","));
+}
+
+fn proposal_key(id: u64, field: &str) -> String {
+    format!("proposal:{}:{}", id, field)
+}
+
+fn confirmation_key(id: u64, owner: &str) -> String {
+    format!("confirmation:{}:{}", id, owner)
+}
@@ -0,0 +1,29 @@
+//! Helpers shared by the contracts written against hand-written WIT worlds.
+//! Each contract includes this file as a module, reaching the host through its
+//! own generated bindings.
+
+use super::host;
+
+/// Validate an address, normalizing it to the lowercase form the host uses
+pub(crate) fn address(account: &str) -> Result<String, String> {
+    let account = account.to_lowercase();
+    let is_hex = account.len() == 42
+        && account.starts_with("0x")
+        && account[2..].chars().all(|c| c.is_ascii_hexdigit());
+
+    match is_hex {
+        true => Ok(account),
+        false => Err(format!("invalid address {}", account)),
+    }
+}
+
+/// Read a number from storage, zero if it was never written
+pub(crate) fn read(key: &str) -> u64 {
+    host::get(key)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write(key: &str, value: u64) {
+    host::set(key, &value.to_string())
+}
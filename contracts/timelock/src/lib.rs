@@ -0,0 +1,99 @@
+//! A vault holding coin deposits until a block number.
+//!
+//! Written with the contract SDK in `proc_macros`: the WIT world is generated
+//! from the public functions of the `#[contract]` impl block, so there is no
+//! WIT file for this contract.
+
+use proc_macros::{contract, event, storage};
+
+#[storage]
+struct Storage {
+    /// The coin deposited by each account
+    deposits: Map<String, u64>,
+
+    /// The block number each account's deposit unlocks at
+    unlocks: Map<String, u64>,
+
+    total_deposits: u64,
+}
+
+#[event]
+struct Deposit {
+    #[topic]
+    account: String,
+    amount: u64,
+    unlock: u64,
+}
+
+#[event]
+struct Withdrawal {
+    #[topic]
+    account: String,
+    amount: u64,
+}
+
+pub struct Timelock;
+
+#[contract]
+impl Timelock {
+    pub fn deposit_of(account: String) -> u64 {
+        Storage::deposits(&account.to_lowercase())
+    }
+
+    pub fn unlock_of(account: String) -> u64 {
+        Storage::unlocks(&account.to_lowercase())
+    }
+
+    pub fn total_deposits() -> u64 {
+        Storage::total_deposits()
+    }
+
+    /// Deposit the coin sent with the call, locked for a number of blocks,
+    /// returning the block number it unlocks at.
+    /// Adding to a deposit can only extend its lock.
+    pub fn deposit(blocks: u64) -> Result<u64, String> {
+        let (account, amount) = (env::caller(), env::value());
+
+        if amount == 0 {
+            return Err("coin must be sent to deposit".into());
+        }
+
+        let unlock = env::block_number()
+            .saturating_add(blocks)
+            .max(Storage::unlocks(&account));
+
+        Storage::set_deposits(&account, Storage::deposits(&account) + amount);
+        Storage::set_unlocks(&account, unlock);
+        Storage::set_total_deposits(Storage::total_deposits() + amount);
+        Deposit {
+            account,
+            amount,
+            unlock,
+        }
+        .emit();
+
+        Ok(unlock)
+    }
+
+    /// Withdraw the whole deposit of the caller once it's unlocked
+    pub fn withdraw() -> Result<u64, String> {
+        let account = env::caller();
+        let amount = Storage::deposits(&account);
+        let unlock = Storage::unlocks(&account);
+
+        if amount == 0 {
+            return Err("nothing to withdraw".into());
+        }
+
+        if env::block_number() < unlock {
+            return Err(format!("the deposit is locked until block {}", unlock));
+        }
+
+        Storage::set_deposits(&account, 0);
+        Storage::set_total_deposits(Storage::total_deposits() - amount);
+        host::transfer(&account, amount)?;
+        Withdrawal { account, amount }.emit();
+
+        Ok(amount)
+    }
+}
@@ -0,0 +1,50 @@
+//! Run the timelock in the runtime's test harness.
+//! The contract must be built first, see the runtime README.
+
+use runtime::harness::{to_hex, Account, Harness, U256};
+
+const TIMELOCK: &[u8] =
+    include_bytes!("./../../../target/wasm32-unknown-unknown/release/timelock_wit.wasm");
+
+/// A timelock and a depositor holding 100 coin
+fn timelock() -> (Harness, Account, Account) {
+    let mut harness = Harness::new();
+    let timelock = harness.deploy(TIMELOCK, &[]).unwrap();
+    let depositor = Account::random();
+    harness.set_balance(depositor, U256::from(100));
+    harness.set_caller(depositor);
+
+    (harness, timelock, depositor)
+}
+
+#[test]
+fn deposits_unlock_after_a_number_of_blocks() {
+    let (mut harness, timelock, depositor) = timelock();
+
+    harness.set_value(U256::from(40));
+    let unlock = harness.call(timelock, "deposit", &["U64", "10"]).unwrap();
+    assert_eq!(unlock, ["U64", "11"]);
+    assert_eq!(harness.balance(depositor), U256::from(60));
+    assert_eq!(harness.balance(timelock), U256::from(40));
+    assert_eq!(harness.events()[0].topics, ["Deposit", &to_hex(depositor)]);
+
+    harness.set_value(U256::zero());
+    assert!(harness.call(timelock, "withdraw", &[]).is_err());
+
+    harness.advance_blocks(10);
+    let withdrawn = harness.call(timelock, "withdraw", &[]).unwrap();
+    assert_eq!(withdrawn, ["U64", "40"]);
+    assert_eq!(harness.balance(depositor), U256::from(100));
+    assert_eq!(
+        harness.view(timelock, "total-deposits", &[]).unwrap(),
+        ["U64", "0"]
+    );
+}
+
+#[test]
+fn deposits_require_coin() {
+    let (mut harness, timelock, _) = timelock();
+
+    assert!(harness.call(timelock, "deposit", &["U64", "10"]).is_err());
+    assert!(harness.events().is_empty());
+}
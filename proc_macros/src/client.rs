@@ -0,0 +1,447 @@
+use std::path::Path;
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse2, Error, Ident, LitStr, Result, Token};
+use wit_parser::{Docs, Field, Function, Resolve, Results, Type, TypeDefKind, WorldId, WorldItem};
+
+/// `"erc20" in "../contracts/wit"`, the world to generate a client for and the
+/// directory of its WIT package, relative to the crate's manifest
+struct Input {
+    world: LitStr,
+    path: LitStr,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let world = input.parse()?;
+        input.parse::<Token![in]>()?;
+        let path = input.parse()?;
+
+        Ok(Self { world, path })
+    }
+}
+
+pub fn expand(input: TokenStream2) -> Result<TokenStream2> {
+    let Input { world, path } = parse2(input)?;
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let wit_error = |e| Error::new(path.span(), format!("{:#}", e));
+    let mut resolve = Resolve::default();
+    let (package, files) = resolve
+        .push_dir(&Path::new(&root).join(path.value()))
+        .map_err(wit_error)?;
+    let world_id = resolve
+        .select_world(package, Some(&world.value()))
+        .map_err(wit_error)?;
+
+    let ident = Ident::new(&upper_camel(&world.value()), world.span());
+    let mut deploy = None;
+    let mut methods = vec![];
+    let mut signatures = vec![];
+
+    for item in resolve.worlds[world_id].exports.values() {
+        let function = match item {
+            WorldItem::Function(function) => function,
+            _ => continue,
+        };
+
+        signatures.push(signature(&resolve, function));
+
+        match function.name.as_str() {
+            "construct" => deploy = Some(function),
+            _ => methods.push(method(&resolve, function, &world)?),
+        }
+    }
+
+    let deploy = deploy_method(&resolve, deploy, &world)?;
+    let events = events(&resolve, world_id);
+
+    // changes to the WIT files rebuild the client
+    let files = files.iter().map(|file| file.display().to_string());
+
+    Ok(quote! {
+        #(const _: &str = include_str!(#files);)*
+
+        /// A client for the contract, generated from its WIT world
+        pub struct #ident<'a>(web3::client::ContractClient<'a>);
+
+        impl<'a> #ident<'a> {
+            /// A client for the contract at `address`, signing transactions with `key`
+            pub fn new(
+                web3: &'a web3::Web3,
+                address: web3::client::Address,
+                key: web3::client::SecretKey,
+            ) -> Self {
+                Self(web3::client::ContractClient::new(web3, address, key))
+            }
+
+            /// Send a value along with every transaction
+            pub fn with_value(self, value: web3::client::U256) -> Self {
+                Self(self.0.with_value(value))
+            }
+
+            /// The address of the contract
+            pub fn address(&self) -> web3::client::Address {
+                self.0.address()
+            }
+
+            /// The interface declared when deploying the contract
+            pub fn interface() -> web3::client::ContractInterface {
+                web3::client::ContractInterface::new(vec![#(#signatures),*], vec![#(#events),*])
+            }
+
+            #deploy
+
+            #(#methods)*
+        }
+    })
+}
+
+/// Whether a function returns a value that can't revert.
+/// The WIT can't tell whether such a function changes state, so it can be
+/// called with `eth_call` or sent as a transaction.
+fn returns_value(resolve: &Resolve, function: &Function) -> bool {
+    match &function.results {
+        Results::Anon(Type::Id(id)) => !matches!(resolve.types[*id].kind, TypeDefKind::Result(_)),
+        Results::Anon(_) => true,
+        Results::Named(results) => !results.is_empty(),
+    }
+}
+
+/// Functions returning a `result`, or nothing, are sent as transactions.
+/// Functions returning a value get a `call_` method reading the value with
+/// `eth_call`, and a `send_` method sending a transaction.
+fn method(resolve: &Resolve, function: &Function, world: &LitStr) -> Result<TokenStream2> {
+    let name = &function.name;
+    let ident = rust_ident(name);
+    let (inputs, params) = params(resolve, function, world)?;
+
+    if !returns_value(resolve, function) {
+        return Ok(send_method(name, &ident, &inputs, &params));
+    }
+
+    let output = match &function.results {
+        Results::Anon(ty) => rust_type(resolve, ty, world)?,
+        Results::Named(_) => {
+            return Err(Error::new(
+                world.span(),
+                format!("`{}` has named results, which aren't supported", name),
+            ))
+        }
+    };
+    let doc = format!(
+        " Call `{}` against the latest state, without a transaction",
+        name
+    );
+    let call = format_ident!("call_{}", ident.to_string().trim_start_matches("r#"));
+    let send = send_method(
+        name,
+        &format_ident!("send_{}", ident.to_string().trim_start_matches("r#")),
+        &inputs,
+        &params,
+    );
+
+    Ok(quote! {
+        #[doc = #doc]
+        pub async fn #call(&self, #(#inputs),*) -> web3::error::Result<#output> {
+            #params
+            let results = self.0.call(#name, params).await?;
+            <#output as web3::client::DecodeResult>::decode(&results)
+        }
+
+        #send
+    })
+}
+
+fn send_method(
+    name: &str,
+    ident: &Ident,
+    inputs: &[TokenStream2],
+    params: &TokenStream2,
+) -> TokenStream2 {
+    let doc = format!(" Send a transaction calling `{}`", name);
+
+    quote! {
+        #[doc = #doc]
+        pub async fn #ident(&self, #(#inputs),*) -> web3::error::Result<web3::client::PendingTransaction<'a>> {
+            #params
+            self.0.send(#name, params).await
+        }
+    }
+}
+
+/// The constructor's params become the params of `deploy`
+fn deploy_method(
+    resolve: &Resolve,
+    function: Option<&Function>,
+    world: &LitStr,
+) -> Result<TokenStream2> {
+    let (inputs, params) = match function {
+        Some(function) => params(resolve, function, world)?,
+        None => (vec![], quote! { let params = vec![]; }),
+    };
+
+    Ok(quote! {
+        /// Deploy the contract, the address of the contract is in the receipt
+        pub async fn deploy(
+            web3: &'a web3::Web3,
+            key: web3::client::SecretKey,
+            code: &[u8],
+            #(#inputs),*
+        ) -> web3::error::Result<web3::client::PendingTransaction<'a>> {
+            #params
+            let interface = Some(Self::interface());
+            web3::client::ContractClient::deploy(web3, key, code, params, interface).await
+        }
+    })
+}
+
+/// The typed inputs of a function, and the statement encoding them as `params`
+fn params(
+    resolve: &Resolve,
+    function: &Function,
+    world: &LitStr,
+) -> Result<(Vec<TokenStream2>, TokenStream2)> {
+    let mut inputs = vec![];
+    let mut args = vec![];
+
+    for (name, ty) in &function.params {
+        let arg = rust_ident(name);
+        let input = param_type(resolve, ty, world)?;
+
+        inputs.push(quote! { #arg: #input });
+        args.push(match ty {
+            Type::String | Type::Id(_) => quote! { #arg },
+            _ => quote! { &#arg },
+        });
+    }
+
+    let params = quote! {
+        let params: Vec<[String; 2]> = vec![
+            #(web3::client::EncodeParam::encode(#args)),*
+        ];
+        let params = params.concat();
+    };
+
+    Ok((inputs, params))
+}
+
+/// Strings and lists are borrowed when passed to a function
+fn param_type(resolve: &Resolve, ty: &Type, world: &LitStr) -> Result<TokenStream2> {
+    match ty {
+        Type::String => Ok(quote! { &str }),
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::List(item @ (Type::String | Type::U64)) => {
+                let item = rust_type(resolve, item, world)?;
+                Ok(quote! { &[#item] })
+            }
+            _ => rust_type(resolve, ty, world),
+        },
+        _ => rust_type(resolve, ty, world),
+    }
+}
+
+fn rust_type(resolve: &Resolve, ty: &Type, world: &LitStr) -> Result<TokenStream2> {
+    let unsupported = || {
+        Error::new(
+            world.span(),
+            "unsupported type, expected string, u64, bool, list<string> or list<u64>",
+        )
+    };
+
+    match ty {
+        Type::String => Ok(quote! { String }),
+        Type::U64 => Ok(quote! { u64 }),
+        Type::Bool => Ok(quote! { bool }),
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::List(item @ (Type::String | Type::U64)) => {
+                let item = rust_type(resolve, item, world)?;
+                Ok(quote! { Vec<#item> })
+            }
+            TypeDefKind::Option(item) => {
+                let item = rust_type(resolve, item, world)?;
+                Ok(quote! { Option<#item> })
+            }
+            TypeDefKind::Type(ty) => rust_type(resolve, ty, world),
+            _ => Err(unsupported()),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+/// The signature of a function in the contract's interface
+fn signature(resolve: &Resolve, function: &Function) -> TokenStream2 {
+    let name = &function.name;
+    let params = function.params.iter().map(|(name, ty)| {
+        let ty = wit_type(resolve, ty);
+        quote! { web3::client::Param::new(#name, #ty) }
+    });
+    let results = match &function.results {
+        Results::Anon(ty) => {
+            let ty = wit_type(resolve, ty);
+            quote! { Some(#ty) }
+        }
+        Results::Named(_) => quote! { None },
+    };
+
+    quote! {
+        web3::client::FunctionSignature::new(#name, vec![#(#params),*], #results)
+    }
+}
+
+/// The events declared in the `events` interface next to the world.
+/// Each record is an event, with the fields marked `@topic` as its topics.
+fn events(resolve: &Resolve, world_id: WorldId) -> Vec<TokenStream2> {
+    let document = &resolve.documents[resolve.worlds[world_id].document];
+    let interface = match document.interfaces.get("events") {
+        Some(interface) => &resolve.interfaces[*interface],
+        None => return vec![],
+    };
+
+    interface
+        .types
+        .iter()
+        .filter_map(|(name, id)| match &resolve.types[*id].kind {
+            TypeDefKind::Record(record) => Some((name, record)),
+            _ => None,
+        })
+        .map(|(name, record)| {
+            let name = upper_camel(name);
+            let (topics, data): (Vec<_>, Vec<_>) = record
+                .fields
+                .iter()
+                .partition(|field| has_annotation(&field.docs, "@topic"));
+            let params = |fields: Vec<&Field>| {
+                fields
+                    .into_iter()
+                    .map(|field| {
+                        let (name, ty) = (&field.name, wit_type(resolve, &field.ty));
+                        quote! { web3::client::Param::new(#name, #ty) }
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let (topics, data) = (params(topics), params(data));
+
+            quote! {
+                web3::client::EventDefinition::new(#name, vec![#(#topics),*], vec![#(#data),*])
+            }
+        })
+        .collect()
+}
+
+/// Whether a line of the docs is the annotation, e.g. `/// @topic`
+fn has_annotation(docs: &Docs, annotation: &str) -> bool {
+    docs.contents
+        .as_deref()
+        .is_some_and(|docs| docs.lines().any(|line| line.trim() == annotation))
+}
+
+/// Write a type the way the runtime does when reading a contract's exports
+fn wit_type(resolve: &Resolve, ty: &Type) -> String {
+    let id = match ty {
+        Type::Id(id) => id,
+        Type::Bool => return "bool".into(),
+        Type::U64 => return "u64".into(),
+        Type::String => return "string".into(),
+        ty => return format!("{:?}", ty).to_lowercase(),
+    };
+
+    match &resolve.types[*id].kind {
+        TypeDefKind::List(item) => format!("list<{}>", wit_type(resolve, item)),
+        TypeDefKind::Option(item) => format!("option<{}>", wit_type(resolve, item)),
+        TypeDefKind::Result(result) => match (result.ok, result.err) {
+            (Some(ok), Some(err)) => {
+                format!(
+                    "result<{}, {}>",
+                    wit_type(resolve, &ok),
+                    wit_type(resolve, &err)
+                )
+            }
+            (None, Some(err)) => format!("result<_, {}>", wit_type(resolve, &err)),
+            (Some(ok), None) => format!("result<{}>", wit_type(resolve, &ok)),
+            (None, None) => "result".into(),
+        },
+        TypeDefKind::Type(ty) => wit_type(resolve, ty),
+        // the client can't pass or return other types, so `rust_type` rejects them
+        _ => "unsupported".into(),
+    }
+}
+
+/// Convert a WIT identifier to a Rust identifier, escaping keywords
+fn rust_ident(name: &str) -> Ident {
+    let name = name.replace('-', "_");
+
+    match syn::parse_str::<Ident>(&name) {
+        Ok(ident) => ident,
+        Err(_) => format_ident!("r#{}", name),
+    }
+}
+
+fn upper_camel(name: &str) -> String {
+    name.split(['-', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_generates_a_client_from_a_world() {
+        let output = expand(quote! { "erc20" in "../contracts/wit" })
+            .unwrap()
+            .to_string();
+
+        assert!(output.contains("pub struct Erc20"));
+        assert!(output.contains("pub async fn deploy (web3 : & 'a web3 :: Web3 , key : web3 :: client :: SecretKey , code : & [u8] , name : & str , symbol : & str)"));
+        assert!(output.contains(
+            "pub async fn call_balance_of (& self , account : & str) -> web3 :: error :: Result < u64 >"
+        ));
+        assert!(output.contains("pub async fn send_balance_of (& self , account : & str)"));
+        assert!(output.contains("self . 0 . call (\"balance-of\" , params)"));
+        assert!(output.contains("self . 0 . send (\"transfer-from\" , params)"));
+        assert!(!output.contains("fn construct"));
+        assert!(output.contains("web3 :: client :: FunctionSignature :: new (\"mint\" , vec ! [web3 :: client :: Param :: new (\"account\" , \"string\") , web3 :: client :: Param :: new (\"amount\" , \"u64\")] , Some (\"result<_, string>\"))"));
+        assert!(output.contains("web3 :: client :: EventDefinition :: new (\"Transfer\" , vec ! [web3 :: client :: Param :: new (\"from\" , \"string\") , web3 :: client :: Param :: new (\"to\" , \"string\")] , vec ! [web3 :: client :: Param :: new (\"amount\" , \"u64\")])"));
+    }
+
+    #[test]
+    fn functions_returning_a_value_are_called_or_sent() {
+        let output = expand(quote! { "forwarder" in "../contracts/wit" })
+            .unwrap()
+            .to_string();
+
+        // `forward` returns a value, but changes state
+        assert!(output.contains("pub async fn call_forward"));
+        assert!(output.contains("pub async fn send_forward"));
+        assert!(output.contains("self . 0 . send (\"forward\" , params)"));
+        assert!(output.contains("self . 0 . send (\"try-forward\" , params)"));
+        assert!(output.contains("self . 0 . call (\"echo\" , params)"));
+
+        // functions returning a `result` can revert, so they're only sent
+        assert!(output.contains("pub async fn upgrade"));
+        assert!(!output.contains("fn call_upgrade"));
+    }
+
+    #[test]
+    fn it_rejects_a_missing_world() {
+        assert!(expand(quote! { "missing" in "../contracts/wit" }).is_err());
+    }
+
+    #[test]
+    fn it_converts_names() {
+        assert_eq!(upper_camel("erc1155"), "Erc1155");
+        assert_eq!(upper_camel("multi-sig"), "MultiSig");
+        assert_eq!(rust_ident("transfer-from").to_string(), "transfer_from");
+        assert_eq!(rust_ident("type").to_string(), "r#type");
+    }
+}
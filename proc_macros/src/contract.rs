@@ -0,0 +1,265 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse2, Error, FnArg, GenericArgument, ImplItem, ItemImpl, Pat, PathArguments, Result,
+    ReturnType, Type, Visibility,
+};
+
+/// The host interface every contract imports, shared with the hand-written WIT worlds
+const HOST_WIT: &str = include_str!("./../../contracts/wit/host.wit");
+
+/// The types the runtime can pass to and return from contract functions
+const SUPPORTED_TYPES: &str = "String, u64, bool, Vec<String> or Vec<u64>";
+
+pub fn expand(input: TokenStream2) -> Result<TokenStream2> {
+    let item: ItemImpl = parse2(input)?;
+    let self_ty = &item.self_ty;
+    let mut exports = vec![];
+    let mut delegates = vec![];
+
+    // only public functions are exported, private ones stay helpers
+    for impl_item in &item.items {
+        let method = match impl_item {
+            ImplItem::Method(method) if matches!(method.vis, Visibility::Public(_)) => method,
+            _ => continue,
+        };
+        let name = &method.sig.ident;
+        let output = &method.sig.output;
+        let mut params = vec![];
+        let mut inputs = vec![];
+        let mut args = vec![];
+
+        for input in &method.sig.inputs {
+            let input = match input {
+                FnArg::Typed(input) => input,
+                FnArg::Receiver(receiver) => {
+                    return Err(Error::new_spanned(
+                        receiver,
+                        "contract functions don't take self, keep state in a #[storage] struct",
+                    ))
+                }
+            };
+            let arg = match &*input.pat {
+                Pat::Ident(pat) => &pat.ident,
+                pat => return Err(Error::new_spanned(pat, "expected a parameter name")),
+            };
+
+            params.push(format!(
+                "{}: {}",
+                wit_name(&arg.to_string()),
+                wit_type(&input.ty)?
+            ));
+            inputs.push(input);
+            args.push(arg);
+        }
+
+        let results = match output {
+            ReturnType::Type(_, ty) if !is_unit(ty) => format!(" -> {}", wit_result(ty)?),
+            _ => String::new(),
+        };
+
+        exports.push(format!(
+            "  export {}: func({}){}",
+            wit_name(&name.to_string()),
+            params.join(", "),
+            results
+        ));
+        delegates.push(quote! {
+            fn #name(#(#inputs),*) #output {
+                <#self_ty>::#name(#(#args),*)
+            }
+        });
+    }
+
+    let world = world(&exports);
+
+    Ok(quote! {
+        wit_bindgen::generate!({ inline: #world });
+
+        #item
+
+        impl Contract for #self_ty {
+            #(#delegates)*
+        }
+
+        export_contract!(#self_ty);
+
+        /// The context of the current call
+        pub mod env {
+            /// The address of the account calling the contract
+            pub fn caller() -> String {
+                super::host::caller()
+            }
+
+            /// The address of the contract being executed
+            pub fn address() -> String {
+                super::host::address()
+            }
+
+            /// The value sent along with the call, which is already in the contract's balance
+            pub fn value() -> u64 {
+                super::host::value()
+            }
+
+            /// The number of the block the call is executed in
+            pub fn block_number() -> u64 {
+                super::host::block_number()
+            }
+
+            /// The time of the block the call is executed in, in seconds since the Unix epoch
+            pub fn block_timestamp() -> u64 {
+                super::host::block_timestamp()
+            }
+
+            /// Print a message for debugging, which nodes running in debug mode log
+            pub fn debug_print(message: &str) {
+                super::host::debug_print(message)
+            }
+        }
+    })
+}
+
+/// The WIT document of the contract, importing the host interface
+fn world(exports: &[String]) -> String {
+    format!(
+        "{}\ndefault world contract {{\n  import host: self.host\n\n{}\n}}\n",
+        HOST_WIT,
+        exports.join("\n")
+    )
+}
+
+/// Convert a Rust identifier to an explicit WIT identifier, so keywords such as
+/// `from` can be used as names
+fn wit_name(name: &str) -> String {
+    format!("%{}", name.trim_start_matches("r#").replace('_', "-"))
+}
+
+fn wit_type(ty: &Type) -> Result<String> {
+    let unsupported = || {
+        Error::new_spanned(
+            ty,
+            format!("unsupported type, expected {}", SUPPORTED_TYPES),
+        )
+    };
+    let (ident, args) = path_segment(ty).ok_or_else(unsupported)?;
+
+    match (ident.as_str(), args.as_slice()) {
+        ("String", []) => Ok("string".into()),
+        ("u64", []) => Ok("u64".into()),
+        ("bool", []) => Ok("bool".into()),
+        ("Vec", [item]) => match wit_type(item)?.as_str() {
+            item @ ("string" | "u64") => Ok(format!("list<{}>", item)),
+            _ => Err(unsupported()),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+/// Results can also be optional, or a `Result` with a `String` error that reverts the call
+fn wit_result(ty: &Type) -> Result<String> {
+    match path_segment(ty) {
+        Some((ident, args)) if ident == "Option" && args.len() == 1 => {
+            Ok(format!("option<{}>", wit_type(args[0])?))
+        }
+        Some((ident, args)) if ident == "Result" && args.len() == 2 => {
+            if wit_type(args[1])? != "string" {
+                return Err(Error::new_spanned(args[1], "errors must be a String"));
+            }
+
+            let ok = match is_unit(args[0]) {
+                true => "_".into(),
+                false => wit_type(args[0])?,
+            };
+
+            Ok(format!("result<{}, string>", ok))
+        }
+        _ => wit_type(ty),
+    }
+}
+
+/// The name and type arguments of the last segment of a type path, e.g. `Vec` and `[u64]`
+fn path_segment(ty: &Type) -> Option<(String, Vec<&Type>)> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    Some((segment.ident.to_string(), args))
+}
+
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn it_maps_rust_types_to_wit() {
+        let types: [(Type, &str); 5] = [
+            (parse_quote!(String), "string"),
+            (parse_quote!(Vec<u64>), "list<u64>"),
+            (parse_quote!(Option<bool>), "option<bool>"),
+            (parse_quote!(Result<(), String>), "result<_, string>"),
+            (
+                parse_quote!(Result<Vec<String>, String>),
+                "result<list<string>, string>",
+            ),
+        ];
+
+        for (ty, expected) in types {
+            assert_eq!(wit_result(&ty).unwrap(), expected);
+        }
+
+        assert!(wit_type(&parse_quote!(u32)).is_err());
+        assert!(wit_result(&parse_quote!(Result<u64, u64>)).is_err());
+    }
+
+    #[test]
+    fn it_exports_public_functions() {
+        let input = quote! {
+            impl Token {
+                pub fn transfer_from(from: String, amount: u64) -> Result<(), String> {
+                    helper(amount)
+                }
+
+                fn helper(amount: u64) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        };
+        let output = expand(input).unwrap().to_string();
+        let export =
+            "export %transfer-from: func(%from: string, %amount: u64) -> result<_, string>";
+
+        assert!(output.contains(export));
+        assert!(output.contains("import host: self.host"));
+        assert!(!output.contains("export %helper"));
+    }
+
+    #[test]
+    fn it_rejects_self() {
+        let input = quote! {
+            impl Token {
+                pub fn total_supply(&self) -> u64 {
+                    0
+                }
+            }
+        };
+
+        assert!(expand(input).is_err());
+    }
+}
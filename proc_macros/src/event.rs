@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse2, Error, Fields, ItemStruct, Result};
+
+pub fn expand(input: TokenStream2) -> Result<TokenStream2> {
+    let mut item: ItemStruct = parse2(input)?;
+    let ident = &item.ident;
+    let name = ident.to_string();
+    let mut topics = vec![];
+    let mut data = vec![];
+
+    let fields = match &mut item.fields {
+        Fields::Named(fields) => &mut fields.named,
+        Fields::Unit => return Ok(expand_unit(&item)),
+        fields => {
+            return Err(Error::new_spanned(
+                fields,
+                "events must be a struct with named fields",
+            ))
+        }
+    };
+
+    // `#[topic]` is only meaningful to this macro, so it's removed from the struct
+    for field in fields.iter_mut() {
+        let is_topic = field.attrs.iter().any(|attr| attr.path.is_ident("topic"));
+        field.attrs.retain(|attr| !attr.path.is_ident("topic"));
+
+        match is_topic {
+            true => topics.push(field.ident.clone()),
+            false => data.push(field.ident.clone()),
+        }
+    }
+
+    Ok(quote! {
+        #item
+
+        impl #ident {
+            /// Emit the event, which is added to the logs of the transaction receipt
+            pub fn emit(&self) {
+                let topics = [#name.to_string(), #(self.#topics.to_string()),*];
+                let data: Vec<String> = vec![#(self.#data.to_string()),*];
+                let topics = topics.iter().map(String::as_str).collect::<Vec<_>>();
+                let data = data.iter().map(String::as_str).collect::<Vec<_>>();
+
+                host::emit(&topics, &data);
+            }
+        }
+    })
+}
+
+/// An event without fields only has its name as a topic
+fn expand_unit(item: &ItemStruct) -> TokenStream2 {
+    let ident = &item.ident;
+    let name = ident.to_string();
+
+    quote! {
+        #item
+
+        impl #ident {
+            /// Emit the event, which is added to the logs of the transaction receipt
+            pub fn emit(&self) {
+                host::emit(&[#name], &[]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_syntax() {
+        let input: TokenStream2 = quote! {
+            struct Transfer {
+                #[topic]
+                from: String,
+                #[topic]
+                to: String,
+                amount: u64,
+            }
+        };
+        let output = expand(input).unwrap();
+        let expected = quote! {
+            struct Transfer {
+                from: String,
+                to: String,
+                amount: u64,
+            }
+
+            impl Transfer {
+                /// Emit the event, which is added to the logs of the transaction receipt
+                pub fn emit(&self) {
+                    let topics = ["Transfer".to_string(), self.from.to_string(), self.to.to_string()];
+                    let data: Vec<String> = vec![self.amount.to_string()];
+                    let topics = topics.iter().map(String::as_str).collect::<Vec<_>>();
+                    let data = data.iter().map(String::as_str).collect::<Vec<_>>();
+
+                    host::emit(&topics, &data);
+                }
+            }
+        };
+
+        assert_eq!(expected.to_string(), output.to_string());
+    }
+}
@@ -1,4 +1,8 @@
+mod client;
+mod contract;
+mod event;
 mod newtype;
+mod storage;
 
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
@@ -8,3 +12,42 @@ pub fn newtype(item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item);
     newtype::append(input).into()
 }
+
+/// Export the public functions of an impl block as the contract's WIT world.
+/// This generates the world, the bindings and the `env` module.
+#[proc_macro_attribute]
+pub fn contract(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    contract::expand(item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Keep the fields of a struct in the contract's persistent storage.
+/// Each field gets a getter and a setter, `Map<K, V>` fields take a key.
+#[proc_macro_attribute]
+pub fn storage(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    storage::expand(item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Add an `emit` function to a struct, logging its name and `#[topic]` fields
+/// as topics, and the other fields as data.
+#[proc_macro_attribute]
+pub fn event(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    event::expand(item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generate a typed client for a contract from its WIT world, e.g.
+/// `contract_client!("erc20" in "../contracts/wit")` generates an `Erc20` struct.
+/// Functions returning a `result`, or nothing, are sent as transactions. Functions
+/// returning a value get a `call_` method using `eth_call` and a `send_` method.
+/// The records of the `events` interface next to the world are declared as events.
+#[proc_macro]
+pub fn contract_client(input: TokenStream) -> TokenStream {
+    client::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -49,7 +49,7 @@ mod tests {
     #[test]
     fn valid_syntax() {
         let input: TokenStream2 = quote! { pub(crate) struct Block(SimpleBlock); };
-        let output = append(input.into());
+        let output = append(input);
         let expected = quote! {
             impl std::ops::Deref for Block {
                 type Target = SimpleBlock;
@@ -63,9 +63,9 @@ mod tests {
                 fn deref_mut(&mut self) -> &mut SimpleBlock {
                     &mut self.0
                 }
-                impl Into<SimpleBlock> for Block {
             }
 
+            impl Into<SimpleBlock> for Block {
                 fn into(self) -> SimpleBlock {
                     self.0
                 }
@@ -0,0 +1,139 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse2, Error, Fields, GenericArgument, ItemStruct, PathArguments, Result, Type};
+
+pub fn expand(input: TokenStream2) -> Result<TokenStream2> {
+    let ItemStruct {
+        attrs,
+        vis,
+        ident,
+        fields,
+        ..
+    } = parse2(input)?;
+    let fields = match fields {
+        Fields::Named(fields) => fields.named,
+        fields => {
+            return Err(Error::new_spanned(
+                fields,
+                "storage must be a struct with named fields",
+            ))
+        }
+    };
+    let mut accessors = vec![];
+
+    for field in fields {
+        let field_vis = &field.vis;
+        let name = field.ident.as_ref().expect("named field");
+        let setter = format_ident!("set_{}", name);
+        let key = name.to_string();
+        let docs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("doc"))
+            .collect::<Vec<_>>();
+
+        let accessor = match map_types(&field.ty) {
+            Some((key_ty, value_ty)) => quote! {
+                #(#docs)*
+                #field_vis fn #name(key: &#key_ty) -> #value_ty {
+                    host::get(&format!("{}:{}", #key, key))
+                        .and_then(|value| value.parse().ok())
+                        .unwrap_or_default()
+                }
+
+                #field_vis fn #setter(key: &#key_ty, value: #value_ty) {
+                    host::set(&format!("{}:{}", #key, key), &value.to_string())
+                }
+            },
+            None => {
+                let ty = &field.ty;
+
+                quote! {
+                    #(#docs)*
+                    #field_vis fn #name() -> #ty {
+                        host::get(#key)
+                            .and_then(|value| value.parse().ok())
+                            .unwrap_or_default()
+                    }
+
+                    #field_vis fn #setter(value: #ty) {
+                        host::set(#key, &value.to_string())
+                    }
+                }
+            }
+        };
+
+        accessors.push(accessor);
+    }
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #ident;
+
+        impl #ident {
+            #(#accessors)*
+        }
+    })
+}
+
+/// The key and value types of a `Map<K, V>` field
+fn map_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Map" => &args.args,
+        _ => return None,
+    };
+
+    match (args.first()?, args.last()?) {
+        (GenericArgument::Type(key), GenericArgument::Type(value)) if args.len() == 2 => {
+            Some((key, value))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_syntax() {
+        let input: TokenStream2 = quote! {
+            struct Storage {
+                total_supply: u64,
+                balances: Map<String, u64>,
+            }
+        };
+        let output = expand(input).unwrap();
+        let expected = quote! {
+            struct Storage;
+
+            impl Storage {
+                fn total_supply() -> u64 {
+                    host::get("total_supply")
+                        .and_then(|value| value.parse().ok())
+                        .unwrap_or_default()
+                }
+
+                fn set_total_supply(value: u64) {
+                    host::set("total_supply", &value.to_string())
+                }
+
+                fn balances(key: &String) -> u64 {
+                    host::get(&format!("{}:{}", "balances", key))
+                        .and_then(|value| value.parse().ok())
+                        .unwrap_or_default()
+                }
+
+                fn set_balances(key: &String, value: u64) {
+                    host::set(&format!("{}:{}", "balances", key), &value.to_string())
+                }
+            }
+        };
+
+        assert_eq!(expected.to_string(), output.to_string());
+    }
+}
@@ -0,0 +1,86 @@
+//! # Cache
+//!
+//! Compiling contract code is slow, so compilations are kept by the hash of the code.
+//! Anyone can send code to compile, so the caches are bounded, evicting the
+//! least recently used compilation when they're full.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+
+use ethereum_types::H256;
+
+/// The most compilations each cache keeps
+pub(crate) const MAX_COMPILED: usize = 256;
+
+#[derive(Debug)]
+pub(crate) struct Cache<V> {
+    capacity: usize,
+    entries: HashMap<H256, (V, u64)>,
+
+    /// Incremented on every use, so the entry with the lowest use was used least recently
+    uses: u64,
+}
+
+impl<V: Clone> Cache<V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    pub(crate) fn get(&mut self, code_hash: &H256) -> Option<V> {
+        self.uses += 1;
+
+        let (value, used) = self.entries.get_mut(code_hash)?;
+        *used = self.uses;
+
+        Some(value.clone())
+    }
+
+    pub(crate) fn insert(&mut self, code_hash: H256, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&code_hash) {
+            let least_recent = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(code_hash, _)| *code_hash);
+
+            if let Some(least_recent) = least_recent {
+                self.entries.remove(&least_recent);
+            }
+        }
+
+        self.uses += 1;
+        self.entries.insert(code_hash, (value, self.uses));
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_evicts_the_least_recently_used_entry() {
+        let (code_1, code_2, code_3) = (H256::random(), H256::random(), H256::random());
+        let mut cache = Cache::new(2);
+
+        cache.insert(code_1, 1);
+        cache.insert(code_2, 2);
+        assert_eq!(cache.get(&code_1), Some(1));
+
+        cache.insert(code_3, 3);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&code_1), Some(1));
+        assert_eq!(cache.get(&code_2), None);
+        assert_eq!(cache.get(&code_3), Some(3));
+    }
+}
@@ -0,0 +1,78 @@
+//! # Context
+//!
+//! The context a contract executes in, held in the wasmtime store.
+//! Host functions read from the context on behalf of the contract.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::{Arc, Mutex};
+
+use ethereum_types::U256;
+use types::account::Account;
+use types::trace::DebugMessage;
+
+use crate::limits::{Limiter, Limits};
+use crate::module::CallData;
+use crate::state::State;
+
+/// Collects the messages printed through `debug-print`.
+/// Nested calls share the collector, so messages are kept even if a call reverts.
+pub type DebugOutput = Arc<Mutex<Vec<DebugMessage>>>;
+
+#[derive(Debug, Clone)]
+pub struct Context {
+    /// The account calling the contract, which is the deployer during construction
+    pub caller: Account,
+
+    /// The address of the contract being executed
+    pub address: Account,
+
+    /// The value sent along with the call
+    pub value: U256,
+
+    /// The number of the block the call is executed in
+    pub block_number: u64,
+
+    /// The time of the block the call is executed in, in seconds since the Unix epoch
+    pub block_timestamp: u64,
+
+    /// The maximum amount of gas the call can consume
+    pub gas: u64,
+
+    /// The number of calls on the stack above this one
+    pub depth: usize,
+
+    /// The blockchain state, including changes made during execution
+    pub state: State,
+
+    /// The resources the contract can hold
+    pub limits: Limits,
+
+    /// Enforces the limits in the store, replaced for every call
+    pub(crate) limiter: Limiter,
+
+    /// Where printed messages go, None to ignore them
+    pub debug: Option<DebugOutput>,
+
+    /// The call data and return data of core modules
+    pub(crate) call_data: CallData,
+}
+
+impl Context {
+    pub fn new(caller: Account, address: Account, gas: u64, state: State) -> Self {
+        Self {
+            caller,
+            address,
+            value: U256::zero(),
+            block_number: 0,
+            block_timestamp: 0,
+            gas,
+            depth: 0,
+            state,
+            limits: Limits::default(),
+            limiter: Limiter::default(),
+            debug: None,
+            call_data: CallData::default(),
+        }
+    }
+}
@@ -1,97 +1,569 @@
+use std::sync::Mutex;
+
+use crate::cache::{Cache, MAX_COMPILED};
+use crate::context::Context;
 use crate::error::{Result, RuntimeError};
+use crate::host;
+use crate::limits::{max_wasm_stack, Limiter};
+use crate::module::{self, is_component};
+use crate::state::State;
+use ethereum_types::H256;
+use lazy_static::lazy_static;
+use types::helpers::to_hex;
+use types::interface::{ContractInterface, FunctionSignature, Param};
+use utils::crypto::hash;
+use wasmparser::{Parser, Payload};
 use wasmtime::{
     self,
-    component::{Component, Instance, Linker, Val},
+    component::{Component, Func, Instance, Linker, Type, Val},
     Config, Engine, Store,
 };
 use wit_component::ComponentEncoder;
 
-pub fn call_function(bytes: &[u8], function: &str, params: &[&str]) -> Result<()> {
-    let (mut store, instance) = load_contract(bytes)?;
-    let parsed: Result<Vec<Val>> = params.chunks_exact(2).map(parse_params).collect();
+lazy_static! {
+    pub(crate) static ref ENGINE: Engine = {
+        let mut config = Config::new();
 
-    tracing::info!("{} params {:?}", function, parsed);
+        Config::wasm_component_model(&mut config, true);
+        Config::consume_fuel(&mut config, true);
+
+        // NaNs have the same bits on every machine, so float results are deterministic
+        Config::cranelift_nan_canonicalization(&mut config, true);
+        Config::max_wasm_stack(&mut config, max_wasm_stack());
+
+        Engine::new(&config).expect("the engine config is valid")
+    };
+
+    /// Compiled components and the number of core instances they create,
+    /// keyed by the hash of the contract code
+    static ref COMPONENTS: Mutex<Cache<(Component, usize)>> = Mutex::new(Cache::new(MAX_COMPILED));
+}
+
+/// The exported function that is run once when the contract is deployed
+pub const CONSTRUCTOR: &str = "construct";
+
+/// The exported function that is run when value is sent to the contract
+/// without calling a function
+pub const RECEIVE: &str = "receive";
+
+/// The outcome of a successful contract call
+#[derive(Debug)]
+pub struct Execution {
+    /// The values returned by the function as typed pairs, e.g. ["U64", "10"]
+    pub result: Vec<String>,
+
+    /// The state, including the changes made during the call
+    pub state: State,
+
+    pub gas_used: u64,
+}
+
+/// Run the constructor of a contract that is being deployed.
+/// Contracts without a constructor can only be deployed without params.
+pub fn deploy(bytes: &[u8], context: Context, params: &[&str]) -> Result<Execution> {
+    execute(&ENGINE, bytes, context, CONSTRUCTOR, params)
+}
+
+pub fn call_function(
+    bytes: &[u8],
+    context: Context,
+    function: &str,
+    params: &[&str],
+) -> Result<Execution> {
+    if function == CONSTRUCTOR {
+        return Err(RuntimeError::ConstructorNotCallable);
+    }
+
+    execute(&ENGINE, bytes, context, function, params)
+}
+
+pub(crate) fn execute(
+    engine: &Engine,
+    bytes: &[u8],
+    context: Context,
+    function: &str,
+    params: &[&str],
+) -> Result<Execution> {
+    let (store, result) = run(engine, bytes, context, function, params);
+
+    Ok(Execution {
+        result: result?,
+        gas_used: store.fuel_consumed().unwrap_or_default(),
+        state: store.into_data().state,
+    })
+}
+
+/// Run a function in a new store, returning the store even if the call fails
+/// so the gas consumed can always be accounted for.
+pub(crate) fn run(
+    engine: &Engine,
+    bytes: &[u8],
+    context: Context,
+    function: &str,
+    params: &[&str],
+) -> (Store<Context>, Result<Vec<String>>) {
+    let (mut store, fueled) = new_store(engine, context);
+    let result = fueled.and_then(|_| call(&mut store, bytes, function, params));
+
+    // hitting a limit fails the call, even if the contract handled it
+    let result = match store.data().limiter.exceeded() {
+        Some(exceeded) => Err(exceeded),
+        None => result,
+    };
+
+    (store, result)
+}
+
+/// A store enforcing the limits of the context, holding its gas as fuel.
+/// The result of adding the fuel is returned with the store.
+fn new_store(engine: &Engine, context: Context) -> (Store<Context>, Result<()>) {
+    let gas = context.gas;
+    let limits = context.limits;
+    let mut store = Store::new(engine, context);
+
+    store.data_mut().limiter = Limiter::new(limits);
+    store.limiter(|context| &mut context.limiter);
+
+    let fueled = store.add_fuel(gas).map_err(RuntimeError::from);
+
+    (store, fueled)
+}
+
+fn call(
+    store: &mut Store<Context>,
+    bytes: &[u8],
+    function: &str,
+    params: &[&str],
+) -> Result<Vec<String>> {
+    if !is_component(bytes)? {
+        return module::call(store, bytes, function, params);
+    }
+
+    let instance = load_contract(store, bytes)?;
+    let func = match instance.get_func(&mut *store, function) {
+        Some(func) => func,
+        None => return missing_export(store, function, params),
+    };
 
-    let function = instance
-        .get_func(&mut store, function)
-        .ok_or_else(|| RuntimeError::ExportFunctionError(function.into()))?;
+    invoke(store, func, function, params)
+}
 
-    function
-        .call(&mut store, &parsed?, &mut [])
-        .map_err(|e| RuntimeError::CallFunctionError(e.to_string()))
+/// Calling a function the contract doesn't export is an error, except for a
+/// constructor without params and for `receive`, which rejects the value
+pub(crate) fn missing_export(
+    store: &Store<Context>,
+    function: &str,
+    params: &[&str],
+) -> Result<Vec<String>> {
+    match function {
+        CONSTRUCTOR if params.is_empty() => Ok(vec![]),
+        RECEIVE => Err(RuntimeError::ValueNotAccepted(to_hex(store.data().address))),
+        _ => Err(RuntimeError::ExportFunctionError(function.into())),
+    }
 }
 
-fn load_contract(bytes: &[u8]) -> Result<(Store<i32>, Instance)> {
-    let mut config = Config::new();
+fn load_contract(store: &mut Store<Context>, bytes: &[u8]) -> Result<Instance> {
+    let mut linker = Linker::new(store.engine());
+
+    host::add_to_linker(&mut linker)?;
 
-    Config::wasm_component_model(&mut config, true);
+    let (component, instances) = compile(store.engine(), bytes)?;
+    let instance = linker
+        .instantiate(&mut *store, &component)
+        .map_err(|e| limit_error(store, e))?;
 
-    let engine = Engine::new(&config)?;
-    let mut store = Store::new(&engine, 0);
-    let linker = Linker::new(&engine);
+    store.data_mut().limiter.instantiated(instances);
+
+    Ok(instance)
+}
+
+/// Compile the contract code into a component, reusing a previous compilation
+/// of the same code.
+/// The number of core instances the component creates is returned with it.
+pub(crate) fn compile(engine: &Engine, bytes: &[u8]) -> Result<(Component, usize)> {
+    let code_hash: H256 = hash(bytes).into();
+
+    if let Some(compiled) = COMPONENTS.lock()?.get(&code_hash) {
+        return Ok(compiled);
+    }
 
     let component_bytes = ComponentEncoder::default()
         .module(bytes)?
         .validate(true)
         .encode()?;
-    let component = Component::from_binary(&engine, &component_bytes)?;
-    let instance = linker.instantiate(&mut store, &component)?;
+    let component = Component::from_binary(engine, &component_bytes)?;
+    let compiled = (component, core_instances(&component_bytes)?);
+
+    COMPONENTS.lock()?.insert(code_hash, compiled.clone());
 
-    Ok((store, instance))
+    Ok(compiled)
+}
+
+/// The number of core modules a component instantiates
+fn core_instances(component_bytes: &[u8]) -> Result<usize> {
+    let mut instances = 0;
+
+    for payload in Parser::new(0).parse_all(component_bytes) {
+        if let Payload::InstanceSection(section) = payload? {
+            for instance in section {
+                if let wasmparser::Instance::Instantiate { .. } = instance? {
+                    instances += 1;
+                }
+            }
+        }
+    }
+
+    Ok(instances)
+}
+
+/// Report a resource limit rather than the error it caused
+fn limit_error(store: &Store<Context>, error: anyhow::Error) -> RuntimeError {
+    store
+        .data()
+        .limiter
+        .error(&error)
+        .unwrap_or_else(|| error.into())
+}
+
+fn invoke(
+    store: &mut Store<Context>,
+    func: Func,
+    function: &str,
+    params: &[&str],
+) -> Result<Vec<String>> {
+    let types = func.params(&*store);
+
+    if params.len() != types.len() * 2 {
+        return Err(RuntimeError::InvalidParams(function.into()));
+    }
+
+    let parsed = params
+        .chunks_exact(2)
+        .zip(types.iter())
+        .map(|(chunk, ty)| match ty {
+            Type::List(_) => parse_list(ty, chunk),
+            _ => parse_params(chunk),
+        })
+        .collect::<Result<Vec<Val>>>()?;
+
+    tracing::info!("{} params {:?}", function, parsed);
+
+    let mut results = vec![Val::Bool(false); func.results(&*store).len()];
+
+    let call_error = |e: anyhow::Error| {
+        RuntimeError::CallFunctionError(format!("{}: {}", function, e.root_cause()))
+    };
+
+    func.call(&mut *store, &parsed, &mut results).map_err(|e| {
+        match store.data().limiter.error(&e) {
+            Some(exceeded) => exceeded,
+            None => call_error(e),
+        }
+    })?;
+    func.post_return(&mut *store).map_err(call_error)?;
+
+    results.iter().try_fold(vec![], |mut encoded, result| {
+        encoded.extend(encode_result(result)?);
+        Ok(encoded)
+    })
 }
 
-// TODO(ddimaria): remove unwrap
 fn parse_params(chunk: &[&str]) -> Result<Val> {
     match chunk[0] {
         "String" => Ok(Val::String(chunk[1].into())),
-        "U64" => Ok(Val::U64(chunk[1].parse::<u64>().unwrap())),
+        "Bool" => {
+            Ok(Val::Bool(chunk[1].parse::<bool>().map_err(|_| {
+                RuntimeError::InvalidParamValue(chunk[1].into())
+            })?))
+        }
+        "U64" => {
+            Ok(Val::U64(chunk[1].parse::<u64>().map_err(|_| {
+                RuntimeError::InvalidParamValue(chunk[1].into())
+            })?))
+        }
+        _ => Err(RuntimeError::InvalidParamType(chunk[0].into())),
+    }
+}
+
+/// Lists are passed as a JSON array, e.g. ["List<U64>", "[1, 2]"]
+fn parse_list(ty: &Type, chunk: &[&str]) -> Result<Val> {
+    let invalid_value = || RuntimeError::InvalidParamValue(chunk[1].into());
+    let values = match chunk[0] {
+        "List<String>" => serde_json::from_str::<Vec<String>>(chunk[1])
+            .map_err(|_| invalid_value())?
+            .into_iter()
+            .map(|value| Val::String(value.into()))
+            .collect::<Vec<_>>(),
+        "List<U64>" => serde_json::from_str::<Vec<u64>>(chunk[1])
+            .map_err(|_| invalid_value())?
+            .into_iter()
+            .map(Val::U64)
+            .collect::<Vec<_>>(),
+        _ => return Err(RuntimeError::InvalidParamType(chunk[0].into())),
+    };
+
+    match ty {
+        Type::List(list) => Ok(list.new_val(values.into())?),
         _ => Err(RuntimeError::InvalidParamType(chunk[0].into())),
     }
 }
 
-// for debugging exportable functions
-fn contract_functions(bytes: &[u8]) -> Vec<String> {
-    let mut config = Config::new();
+/// Encode a returned value into typed pairs.
+/// Functions returning a `result` revert with the error when it's an `err`.
+fn encode_result(value: &Val) -> Result<Vec<String>> {
+    let encoded = match value {
+        Val::String(value) => vec!["String".into(), value.to_string()],
+        Val::Bool(value) => vec!["Bool".into(), value.to_string()],
+        Val::U64(value) => vec!["U64".into(), value.to_string()],
+        Val::List(list) => {
+            let values = list
+                .iter()
+                .map(|value| match value {
+                    Val::String(value) => Ok(serde_json::Value::from(value.to_string())),
+                    Val::U64(value) => Ok(serde_json::Value::from(*value)),
+                    _ => Err(RuntimeError::InvalidResultType(format!("{:?}", value.ty()))),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let ty = match list.ty().ty() {
+                Type::String => "List<String>",
+                Type::U64 => "List<U64>",
+                ty => return Err(RuntimeError::InvalidResultType(format!("{:?}", ty))),
+            };
+
+            vec![ty.into(), serde_json::to_string(&values)?]
+        }
+        Val::Option(option) => match option.value() {
+            Some(value) => encode_result(value)?,
+            None => vec![],
+        },
+        Val::Result(result) => match result.value() {
+            Ok(Some(value)) => encode_result(value)?,
+            Ok(None) => vec![],
+            Err(Some(Val::String(error))) => return Err(RuntimeError::Reverted(error.to_string())),
+            Err(_) => return Err(RuntimeError::Reverted("".into())),
+        },
+        _ => return Err(RuntimeError::InvalidResultType(format!("{:?}", value.ty()))),
+    };
+
+    Ok(encoded)
+}
+
+/// The names of the functions exported by the contract's core module,
+/// along with the memory and ABI helpers wit-bindgen exports.
+/// The module is only parsed, not compiled.
+fn contract_functions(bytes: &[u8]) -> Result<Vec<String>> {
     let mut exports = vec![];
 
-    Config::wasm_component_model(&mut config, true);
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::ExportSection(section) = payload? {
+            for export in section {
+                exports.push(export?.name.to_string());
+            }
+        }
+    }
 
-    if let Ok(engine) = Engine::new(&config) {
-        exports = wasmtime::Module::from_binary(&engine, bytes)
-            .unwrap()
-            .exports()
-            .map(|export| export.name().to_string())
-            .collect();
+    Ok(exports)
+}
+
+/// The signatures of the functions exported by the contract.
+/// Param names aren't part of the component, so they're named by position.
+/// The contract is instantiated within the limits and the gas of the context.
+fn exported_functions(bytes: &[u8], context: Context) -> Result<Vec<FunctionSignature>> {
+    if !is_component(bytes)? {
+        return module::exported_functions(bytes);
     }
 
-    exports
+    let (mut store, fueled) = new_store(&ENGINE, context);
+
+    fueled?;
+
+    let instance = load_contract(&mut store, bytes)?;
+    let mut functions = vec![];
+
+    // only the names that are component functions, skipping memory and the ABI helpers
+    for name in contract_functions(bytes)? {
+        if let Some(func) = instance.get_func(&mut store, &name) {
+            let params = func
+                .params(&store)
+                .iter()
+                .enumerate()
+                .map(|(index, ty)| Param::new(&format!("p{}", index), &wit_type(ty)))
+                .collect();
+            let results = func
+                .results(&store)
+                .iter()
+                .map(wit_type)
+                .collect::<Vec<_>>();
+            let results = (!results.is_empty()).then(|| results.join(", "));
+
+            functions.push(FunctionSignature::new(&name, params, results.as_deref()));
+        }
+    }
+
+    Ok(functions)
+}
+
+/// The interface of a contract that is being deployed.
+/// A declared interface must match the functions the contract exports, without a
+/// declared interface the exported functions are used.
+pub fn interface(
+    bytes: &[u8],
+    context: Context,
+    declared: Option<ContractInterface>,
+) -> Result<ContractInterface> {
+    let exported = exported_functions(bytes, context)?;
+    let declared = match declared {
+        Some(declared) => declared,
+        None => return Ok(ContractInterface::new(exported, vec![])),
+    };
+    let mismatch = |message: String| Err(RuntimeError::InterfaceMismatch(message));
+
+    for function in &declared.functions {
+        match exported.iter().find(|export| export.name == function.name) {
+            Some(export) if export.matches(function) => {}
+            Some(_) => return mismatch(format!("{} has different types", function.name)),
+            None => return mismatch(format!("{} is not exported", function.name)),
+        }
+    }
+
+    if let Some(export) = exported
+        .iter()
+        .find(|export| declared.function(&export.name).is_none())
+    {
+        return mismatch(format!("{} is not declared", export.name));
+    }
+
+    Ok(declared)
+}
+
+/// Write a component type as a WIT type
+fn wit_type(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".into(),
+        Type::S8 => "s8".into(),
+        Type::U8 => "u8".into(),
+        Type::S16 => "s16".into(),
+        Type::U16 => "u16".into(),
+        Type::S32 => "s32".into(),
+        Type::U32 => "u32".into(),
+        Type::S64 => "s64".into(),
+        Type::U64 => "u64".into(),
+        Type::Float32 => "float32".into(),
+        Type::Float64 => "float64".into(),
+        Type::Char => "char".into(),
+        Type::String => "string".into(),
+        Type::List(list) => format!("list<{}>", wit_type(&list.ty())),
+        Type::Option(option) => format!("option<{}>", wit_type(&option.ty())),
+        Type::Result(result) => match (result.ok(), result.err()) {
+            (Some(ok), Some(err)) => format!("result<{}, {}>", wit_type(&ok), wit_type(&err)),
+            (None, Some(err)) => format!("result<_, {}>", wit_type(&err)),
+            (Some(ok), None) => format!("result<{}>", wit_type(&ok)),
+            (None, None) => "result".into(),
+        },
+        Type::Record(_) => "record".into(),
+        Type::Tuple(_) => "tuple".into(),
+        Type::Variant(_) => "variant".into(),
+        Type::Enum(_) => "enum".into(),
+        Type::Union(_) => "union".into(),
+        Type::Flags(_) => "flags".into(),
+    }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::harness::MemoryBackend;
+    use std::sync::Arc;
     use test_log::test;
     use types::account::Account;
+    use types::helpers::to_hex;
+
+    pub(crate) const GAS: u64 = 10_000_000;
+
+    pub(crate) fn context(backend: MemoryBackend) -> Context {
+        let state = State::new(Arc::new(backend));
+        Context::new(Account::random(), Account::random(), GAS, state)
+    }
 
     const PARAMS_1: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
 
-    fn params_2<'a>(address: &'a String) -> [&'a str; 4] {
-        ["String", &address, "U64", "10"]
+    fn params_2(address: &str) -> [&str; 4] {
+        ["String", address, "U64", "10"]
     }
 
     #[test]
     fn it_loads_a_contract() {
         let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
-        let _loaded = load_contract(bytes).unwrap();
+        let mut store = Store::new(&ENGINE, context(MemoryBackend::default()));
+        let _loaded = load_contract(&mut store, bytes).unwrap();
     }
 
     #[test]
     fn it_calls_contract_functions() {
         let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
-        let address = Account::random().to_string();
+        let address = to_hex(Account::random());
+        let mut context = context(MemoryBackend::default());
+        let execution = deploy(bytes, context.clone(), PARAMS_1).unwrap();
+
+        // storage written by the constructor is visible to later calls
+        context.state = execution.state;
+        let execution = call_function(bytes, context.clone(), "mint", &params_2(&address)).unwrap();
+        let event = &execution.state.events()[0];
+        assert_eq!(event.address, context.address);
+        assert_eq!(event.topics[0], "Transfer");
+        assert_eq!(event.topics[2], address);
+        assert_eq!(event.data, ["10"]);
 
-        call_function(bytes, "construct", PARAMS_1).unwrap();
-        call_function(bytes, "mint", &params_2(&address)).unwrap();
+        context.state = execution.state;
+        let execution = call_function(bytes, context, "balance-of", &["String", &address]).unwrap();
+
+        assert_eq!(execution.result, ["U64", "10"]);
+    }
+
+    #[test]
+    fn it_reverts_with_the_returned_error() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let address = to_hex(Account::random());
+        let mut context = context(MemoryBackend::default());
+        let execution = deploy(bytes, context.clone(), PARAMS_1).unwrap();
+
+        // only the deployer can mint
+        context.state = execution.state;
+        context.caller = Account::random();
+        let response = call_function(bytes, context, "mint", &params_2(&address));
+
+        assert!(matches!(
+            response,
+            Err(RuntimeError::Reverted(error)) if error == "caller is not the owner"
+        ));
+    }
+
+    #[test]
+    fn it_fails_deployment_when_the_constructor_traps() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let response = deploy(
+            bytes,
+            context(MemoryBackend::default()),
+            &["String", "", "String", ""],
+        );
+
+        assert!(matches!(response, Err(RuntimeError::CallFunctionError(_))));
+    }
+
+    #[test]
+    fn it_cannot_call_the_constructor_after_deployment() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let response = call_function(
+            bytes,
+            context(MemoryBackend::default()),
+            CONSTRUCTOR,
+            PARAMS_1,
+        );
+
+        assert!(matches!(
+            response,
+            Err(RuntimeError::ConstructorNotCallable)
+        ));
     }
 
     #[test]
@@ -108,15 +580,39 @@ mod tests {
         assert_eq!(parsed, Val::U64(10));
     }
 
+    #[test]
+    fn it_parses_bool_params() {
+        assert_eq!(parse_params(&["Bool", "true"]).unwrap(), Val::Bool(true));
+        assert!(matches!(
+            parse_params(&["Bool", "yes"]),
+            Err(RuntimeError::InvalidParamValue(_))
+        ));
+    }
+
     #[test_log::test]
     fn it_retrieves_contract_function_names() {
         let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
-        let functions = contract_functions(bytes);
+        let functions = contract_functions(bytes).unwrap();
         let expected = [
             "memory",
+            "allowance",
+            "approve",
+            "balance-of",
+            "cabi_post_approve",
+            "cabi_post_name",
             "construct",
             "mint",
+            "name",
+            "owner",
+            "symbol",
+            "total-supply",
             "transfer",
+            "transfer-from",
+            "cabi_post_owner",
+            "cabi_post_symbol",
+            "cabi_post_mint",
+            "cabi_post_transfer",
+            "cabi_post_transfer-from",
             "cabi_realloc",
             "__data_end",
             "__heap_base",
@@ -124,4 +620,59 @@ mod tests {
 
         assert_eq!(functions, expected);
     }
+
+    #[test]
+    fn it_derives_the_interface_from_the_exports() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let interface = interface(bytes, context(MemoryBackend::default()), None).unwrap();
+        let balance_of = interface.function("balance-of").unwrap();
+
+        assert_eq!(interface.functions.len(), 11);
+        assert_eq!(balance_of.params, [Param::new("p0", "string")]);
+        assert_eq!(balance_of.results.as_deref(), Some("u64"));
+        assert_eq!(
+            interface.function("mint").unwrap().results.as_deref(),
+            Some("result<_, string>")
+        );
+    }
+
+    #[test]
+    fn the_interface_is_read_within_the_limits() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let mut context = context(MemoryBackend::default());
+        context.limits.memory_pages = 1;
+
+        assert!(matches!(
+            interface(bytes, context, None),
+            Err(RuntimeError::ResourceLimitExceeded(exceeded)) if exceeded == "memory of 1 pages"
+        ));
+    }
+
+    #[test]
+    fn it_checks_a_declared_interface() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        let exported = interface(bytes, context(MemoryBackend::default()), None).unwrap();
+        let check = |declared| interface(bytes, context(MemoryBackend::default()), Some(declared));
+
+        let mut missing = exported.clone();
+        missing
+            .functions
+            .push(FunctionSignature::new("burn", vec![], None));
+
+        let mut undeclared = exported.clone();
+        undeclared
+            .functions
+            .retain(|function| function.name != "mint");
+
+        let mut mistyped = exported.clone();
+        mistyped.functions[0].results = Some("string".into());
+
+        assert_eq!(check(exported.clone()).unwrap(), exported);
+        for declared in [missing, undeclared, mistyped] {
+            assert!(matches!(
+                check(declared),
+                Err(RuntimeError::InterfaceMismatch(_))
+            ));
+        }
+    }
 }
@@ -13,15 +13,75 @@ pub enum RuntimeError {
     #[error("Error invoking function {0}")]
     CallFunctionError(String),
 
+    #[error("Code not found for hash {0}")]
+    CodeNotFound(String),
+
+    #[error("Code is {0} bytes, over the limit of {1} bytes")]
+    CodeTooLarge(usize, usize),
+
+    #[error("The constructor can only be called at deployment")]
+    ConstructorNotCallable,
+
+    #[error("Contract {0} already exists")]
+    ContractAlreadyExists(String),
+
+    #[error("The engine is already configured: {0}")]
+    EngineConfigured(String),
+
     #[error("Error executing {0}")]
     ExecutionError(String),
 
     #[error("Error exporting function {0}")]
     ExportFunctionError(String),
 
+    #[error("Importing {0} is not allowed, contracts can only import the host interface")]
+    ForbiddenImport(String),
+
+    #[error("Insufficient balance for account {0}")]
+    InsufficientBalance(String),
+
+    #[error("The declared interface doesn't match the exports: {0}")]
+    InterfaceMismatch(String),
+
+    #[error("Invalid contract code: {0}")]
+    InvalidCode(String),
+
     #[error("Invalid parameter type {0}")]
     InvalidParamType(String),
 
+    #[error("Invalid parameter value {0}")]
+    InvalidParamValue(String),
+
+    #[error("Invalid parameters for function {0}")]
+    InvalidParams(String),
+
+    #[error("Invalid result type {0}")]
+    InvalidResultType(String),
+
+    #[error("Could not acquire lock: {0}")]
+    LockError(String),
+
+    #[error("Maximum call depth of {0} exceeded")]
+    MaxCallDepthExceeded(usize),
+
+    #[error("Account {0} is not a contract account")]
+    NotAContract(String),
+
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimitExceeded(String),
+
+    #[error("Reverted: {0}")]
+    Reverted(String),
+
+    #[error("Error serializing or deserializing JSON data: {0}")]
+    SerdeJsonError(String),
+
+    #[error("Error reading state: {0}")]
+    StateError(String),
+
+    #[error("Contract {0} does not accept value without a call")]
+    ValueNotAccepted(String),
+
     #[error("Wasmtime error {0}")]
     WasmtimeError(String),
 }
@@ -29,6 +89,24 @@ pub enum RuntimeError {
 /// Utility result type to be used throughout
 pub type Result<T> = std::result::Result<T, RuntimeError>;
 
+impl<T> From<std::sync::PoisonError<T>> for RuntimeError {
+    fn from(error: std::sync::PoisonError<T>) -> Self {
+        RuntimeError::LockError(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RuntimeError {
+    fn from(error: serde_json::Error) -> Self {
+        RuntimeError::SerdeJsonError(error.to_string())
+    }
+}
+
+impl From<wasmparser::BinaryReaderError> for RuntimeError {
+    fn from(error: wasmparser::BinaryReaderError) -> Self {
+        RuntimeError::InvalidCode(error.to_string())
+    }
+}
+
 impl From<anyhow::Error> for RuntimeError {
     fn from(error: anyhow::Error) -> Self {
         RuntimeError::WasmtimeError(error.to_string())
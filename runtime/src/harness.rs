@@ -0,0 +1,382 @@
+//! # Test Harness
+//!
+//! Run contracts in tests without starting a chain.
+//!
+//! `Harness` keeps the state in memory and controls the context of each call:
+//! the caller, the value sent and the block number and time.
+//! Calls that succeed change the state, like transactions on a chain, while
+//! views and calls that revert leave it untouched.
+//!
+//! ```ignore
+//! use runtime::harness::{to_hex, Account, Harness};
+//!
+//! let mut harness = Harness::new();
+//! let erc20 = harness.deploy(ERC20, &["String", "Rust Coin", "String", "RustCoin"])?;
+//! let account = to_hex(Account::random());
+//!
+//! harness.call(erc20, "mint", &["String", &account, "U64", "10"])?;
+//! assert_eq!(harness.view(erc20, "balance-of", &["String", &account])?, ["U64", "10"]);
+//! ```
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethereum_types::H256;
+pub use ethereum_types::U256;
+pub use types::account::Account;
+use types::bytes::Bytes;
+pub use types::helpers::to_hex;
+use types::trace::DebugMessage;
+use utils::crypto::hash;
+
+use crate::context::{Context, DebugOutput};
+use crate::contract::{call_function, deploy, Execution, RECEIVE};
+use crate::error::Result;
+use crate::state::{Backend, Event, State};
+use crate::validate::validate;
+
+/// The gas each call can consume unless it's changed with `set_gas`
+pub const DEFAULT_GAS: u64 = 10_000_000;
+
+/// A backend holding the state in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    pub code: HashMap<Account, Bytes>,
+    pub balances: HashMap<Account, U256>,
+    pub nonces: HashMap<Account, U256>,
+    pub storage: HashMap<(Account, String), String>,
+}
+
+impl Backend for MemoryBackend {
+    fn code(&self, address: &Account) -> Result<Option<Bytes>> {
+        Ok(self.code.get(address).cloned())
+    }
+
+    fn code_by_hash(&self, code_hash: &H256) -> Result<Option<Bytes>> {
+        Ok(self
+            .code
+            .values()
+            .find(|code| H256::from(hash(code)) == *code_hash)
+            .cloned())
+    }
+
+    fn nonce(&self, address: &Account) -> Result<U256> {
+        Ok(self.nonces.get(address).cloned().unwrap_or_default())
+    }
+
+    fn balance(&self, address: &Account) -> Result<U256> {
+        Ok(self.balances.get(address).cloned().unwrap_or_default())
+    }
+
+    fn storage(&self, address: &Account, key: &str) -> Result<Option<String>> {
+        Ok(self.storage.get(&(*address, key.to_string())).cloned())
+    }
+}
+
+/// An in-memory host for calling contracts
+#[derive(Debug)]
+pub struct Harness {
+    backend: MemoryBackend,
+    caller: Account,
+    value: U256,
+    gas: u64,
+    block_number: u64,
+    block_timestamp: u64,
+    events: Vec<Event>,
+    debug: DebugOutput,
+    gas_used: u64,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// A harness calling from a random account, at block 1 and time 0
+    pub fn new() -> Self {
+        Self {
+            backend: MemoryBackend::default(),
+            caller: Account::random(),
+            value: U256::zero(),
+            gas: DEFAULT_GAS,
+            block_number: 1,
+            block_timestamp: 0,
+            events: vec![],
+            debug: DebugOutput::default(),
+            gas_used: 0,
+        }
+    }
+
+    /// Validate the code and run its constructor, returning the address of the contract
+    pub fn deploy(&mut self, code: &[u8], params: &[&str]) -> Result<Account> {
+        validate(code)?;
+
+        let address = Account::random();
+        let execution = deploy(code, self.context(address), params)?;
+
+        self.apply(execution);
+        self.backend.code.insert(address, code.to_vec().into());
+
+        Ok(address)
+    }
+
+    /// Call a function, sending the value to the contract, and keep its changes
+    /// if it succeeds
+    pub fn call(
+        &mut self,
+        contract: Account,
+        function: &str,
+        params: &[&str],
+    ) -> Result<Vec<String>> {
+        let execution = self.execute(contract, function, params)?;
+        let result = execution.result.clone();
+
+        self.apply(execution);
+
+        Ok(result)
+    }
+
+    /// Call a function without keeping any of its changes
+    pub fn view(
+        &mut self,
+        contract: Account,
+        function: &str,
+        params: &[&str],
+    ) -> Result<Vec<String>> {
+        Ok(self.execute(contract, function, params)?.result)
+    }
+
+    /// Send the value without a call, running the `receive` function of contracts
+    pub fn transfer(&mut self, to: Account) -> Result<()> {
+        match self.backend.code.contains_key(&to) {
+            true => {
+                let execution = self.execute(to, RECEIVE, &[])?;
+                self.apply(execution);
+            }
+            false => {
+                let mut state = State::new(Arc::new(self.backend.clone()));
+                state.transfer(&self.caller, &to, self.value)?;
+                self.backend.balances.extend(state.balances());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, contract: Account, function: &str, params: &[&str]) -> Result<Execution> {
+        let mut context = self.context(contract);
+        let code = context.state.code(&contract)?;
+
+        // the value is part of the call's state, so it's returned if the call reverts
+        context.value = self.value;
+        context
+            .state
+            .transfer(&self.caller, &contract, self.value)?;
+
+        call_function(&code, context, function, params)
+    }
+
+    fn context(&self, address: Account) -> Context {
+        let state = State::new(Arc::new(self.backend.clone()));
+        let mut context = Context::new(self.caller, address, self.gas, state);
+
+        context.block_number = self.block_number;
+        context.block_timestamp = self.block_timestamp;
+        context.debug = Some(self.debug.clone());
+
+        context
+    }
+
+    fn apply(&mut self, execution: Execution) {
+        let state = execution.state;
+
+        for (account, balance) in state.balances() {
+            self.backend.balances.insert(*account, *balance);
+        }
+
+        for (account, nonce) in state.nonces() {
+            self.backend.nonces.insert(*account, *nonce);
+        }
+
+        for (contract, code) in state.created().iter().chain(state.upgraded()) {
+            self.backend.code.insert(*contract, code.to_owned());
+        }
+
+        for (contract, storage) in state.storage() {
+            for (key, value) in storage {
+                self.backend
+                    .storage
+                    .insert((*contract, key.to_owned()), value.to_owned());
+            }
+        }
+
+        for contract in state.destroyed() {
+            self.backend.code.remove(contract);
+            self.backend
+                .storage
+                .retain(|(account, _), _| account != contract);
+        }
+
+        self.events.extend_from_slice(state.events());
+        self.gas_used = execution.gas_used;
+    }
+
+    /// Make the following calls from an account
+    pub fn set_caller(&mut self, caller: Account) {
+        self.caller = caller;
+    }
+
+    pub fn caller(&self) -> Account {
+        self.caller
+    }
+
+    /// Send a value with the following calls, taken from the caller's balance
+    pub fn set_value(&mut self, value: U256) {
+        self.value = value;
+    }
+
+    /// Limit the gas of the following calls
+    pub fn set_gas(&mut self, gas: u64) {
+        self.gas = gas;
+    }
+
+    /// The gas consumed by the last call that succeeded
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    pub fn set_balance(&mut self, account: Account, balance: U256) {
+        self.backend.balances.insert(account, balance);
+    }
+
+    pub fn balance(&self, account: Account) -> U256 {
+        self.backend
+            .balances
+            .get(&account)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// A value in the persistent storage of a contract
+    pub fn storage(&self, contract: Account, key: &str) -> Option<String> {
+        self.backend.storage.get(&(contract, key.into())).cloned()
+    }
+
+    pub fn set_storage(&mut self, contract: Account, key: &str, value: &str) {
+        self.backend
+            .storage
+            .insert((contract, key.into()), value.into());
+    }
+
+    /// The events emitted by the calls that succeeded, in order
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The messages printed by contracts, including in calls that reverted
+    pub fn debug(&self) -> Vec<DebugMessage> {
+        self.debug
+            .lock()
+            .map(|debug| debug.to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    pub fn block_timestamp(&self) -> u64 {
+        self.block_timestamp
+    }
+
+    /// Move to a later block
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.block_number += blocks;
+    }
+
+    /// Move the block time forward
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.block_timestamp += seconds;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RuntimeError;
+
+    const ERC20: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+    const ERC20_PARAMS: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
+
+    #[test]
+    fn it_keeps_the_changes_of_successful_calls() {
+        let mut harness = Harness::new();
+        let erc20 = harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let account = to_hex(Account::random());
+
+        harness
+            .call(erc20, "mint", &["String", &account, "U64", "10"])
+            .unwrap();
+
+        assert_eq!(
+            harness
+                .view(erc20, "balance-of", &["String", &account])
+                .unwrap(),
+            ["U64", "10"]
+        );
+        assert_eq!(harness.events()[0].topics[0], "Transfer");
+        assert!(harness.gas_used() > 0);
+    }
+
+    #[test]
+    fn reverted_calls_change_nothing() {
+        let mut harness = Harness::new();
+        let erc20 = harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let account = to_hex(Account::random());
+        harness.set_caller(Account::random());
+
+        // only the owner can mint
+        let mint = harness.call(erc20, "mint", &["String", &account, "U64", "10"]);
+
+        assert!(matches!(
+            mint,
+            Err(RuntimeError::Reverted(error)) if error == "caller is not the owner"
+        ));
+        assert!(harness.events().is_empty());
+    }
+
+    #[test]
+    fn contracts_receive_plain_transfers() {
+        let mut harness = Harness::new();
+        let erc20 = harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let account = Account::random();
+        harness.set_balance(harness.caller(), U256::from(10));
+        harness.set_value(U256::from(4));
+
+        harness.transfer(account).unwrap();
+        assert_eq!(harness.balance(account), U256::from(4));
+        assert_eq!(harness.balance(harness.caller()), U256::from(6));
+
+        // the erc20 has no `receive` function
+        assert!(matches!(
+            harness.transfer(erc20),
+            Err(RuntimeError::ValueNotAccepted(_))
+        ));
+        assert_eq!(harness.balance(erc20), U256::zero());
+    }
+
+    #[test]
+    fn it_advances_blocks_and_time() {
+        let mut harness = Harness::new();
+        harness.advance_blocks(5);
+        harness.advance_time(60);
+
+        assert_eq!(harness.block_number(), 6);
+        assert_eq!(harness.block_timestamp(), 60);
+    }
+}
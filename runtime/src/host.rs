@@ -0,0 +1,851 @@
+//! # Host
+//!
+//! Functions provided by the blockchain to contracts.
+//! Contracts import these through the `host` interface of their WIT world.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::str::FromStr;
+
+use ethereum_types::{H256, U256};
+use types::account::Account;
+use types::bytes::Bytes;
+use types::helpers::to_hex;
+use types::trace::DebugMessage;
+use utils::crypto::{
+    contract_address, contract_address_with_salt, hash, recover_address, verify, PublicKey,
+};
+use wasmtime::component::Linker;
+use wasmtime::StoreContextMut;
+
+use crate::context::Context;
+use crate::contract::{run, CONSTRUCTOR};
+use crate::error::{Result, RuntimeError};
+use crate::limits::Limiter;
+use crate::module::CallData;
+use crate::state::{Event, State};
+use crate::validate::validate;
+
+pub(crate) const HOST: &str = "host";
+
+/// The functions of the host interface, the only imports a contract can have
+pub(crate) const FUNCTIONS: &[&str] = &[
+    "caller",
+    "address",
+    "value",
+    "block-number",
+    "block-timestamp",
+    "is-contract",
+    "get",
+    "set",
+    "emit",
+    "transfer",
+    "call",
+    "create",
+    "create-from-hash",
+    "set-code",
+    "self-destruct",
+    "keccak256",
+    "ecrecover",
+    "verify",
+    "debug-print",
+];
+
+/// The gas charged for reading a value from storage
+pub const GET_GAS: u64 = 200;
+
+/// The gas charged for writing a value to storage, plus `SET_BYTE_GAS` per byte
+/// of the key and value
+pub const SET_GAS: u64 = 5_000;
+
+pub const SET_BYTE_GAS: u64 = 20;
+
+/// The gas charged for emitting an event, plus `EMIT_BYTE_GAS` per byte of its
+/// topics and data
+pub const EMIT_GAS: u64 = 375;
+
+pub const EMIT_BYTE_GAS: u64 = 8;
+
+/// The gas charged for transferring value
+pub const TRANSFER_GAS: u64 = 2_300;
+
+/// The gas charged for creating a contract, on top of the gas used by its constructor
+pub const CREATE_GAS: u64 = 10_000;
+
+/// The gas charged for replacing the code of a contract
+pub const SET_CODE_GAS: u64 = 10_000;
+
+/// The gas charged for hashing, whatever the length of the data
+pub const KECCAK256_GAS: u64 = 100;
+
+/// The gas charged for recovering the signer of a message
+pub const ECRECOVER_GAS: u64 = 3_000;
+
+/// The gas charged for verifying a signature
+pub const VERIFY_GAS: u64 = 3_000;
+
+/// The maximum number of nested contract calls
+pub(crate) const MAX_CALL_DEPTH: usize = 32;
+
+type CallParams = (String, String, Vec<String>, u64, u64);
+
+pub(crate) fn add_to_linker(linker: &mut Linker<Context>) -> Result<()> {
+    let mut host = linker.instance(HOST)?;
+
+    host.func_wrap("caller", |store: StoreContextMut<'_, Context>, (): ()| {
+        Ok((to_hex(store.data().caller),))
+    })?;
+
+    host.func_wrap("address", |store: StoreContextMut<'_, Context>, (): ()| {
+        Ok((to_hex(store.data().address),))
+    })?;
+
+    // values are u64 across the host interface, larger values saturate
+    host.func_wrap("value", |store: StoreContextMut<'_, Context>, (): ()| {
+        Ok((u64::try_from(store.data().value).unwrap_or(u64::MAX),))
+    })?;
+
+    host.func_wrap(
+        "block-number",
+        |store: StoreContextMut<'_, Context>, (): ()| Ok((store.data().block_number,)),
+    )?;
+
+    host.func_wrap(
+        "block-timestamp",
+        |store: StoreContextMut<'_, Context>, (): ()| Ok((store.data().block_timestamp,)),
+    )?;
+
+    host.func_wrap(
+        "is-contract",
+        |store: StoreContextMut<'_, Context>, (address,): (String,)| {
+            let is_contract = match Account::from_str(&address) {
+                Ok(address) => store.data().state.is_contract(&address)?,
+                Err(_) => false,
+            };
+            Ok((is_contract,))
+        },
+    )?;
+
+    host.func_wrap(
+        "get",
+        |mut store: StoreContextMut<'_, Context>, (key,): (String,)| {
+            store.consume_fuel(GET_GAS)?;
+            let context = store.data();
+            Ok((context.state.get(&context.address, &key)?,))
+        },
+    )?;
+
+    host.func_wrap(
+        "set",
+        |mut store: StoreContextMut<'_, Context>, (key, value): (String, String)| {
+            store.consume_fuel(set_gas(&key, &value))?;
+            let context = store.data_mut();
+            context.state.set(&context.address, key, value);
+            Ok(())
+        },
+    )?;
+
+    host.func_wrap(
+        "emit",
+        |mut store: StoreContextMut<'_, Context>, (topics, data): (Vec<String>, Vec<String>)| {
+            store.consume_fuel(emit_gas(&topics, &data))?;
+            let context = store.data_mut();
+            let event = Event {
+                address: context.address,
+                topics,
+                data,
+            };
+            context.state.emit(event);
+            Ok(())
+        },
+    )?;
+
+    host.func_wrap(
+        "transfer",
+        |mut store: StoreContextMut<'_, Context>, (to, value): (String, u64)| {
+            store.consume_fuel(TRANSFER_GAS)?;
+            Ok((transfer(&mut store, &to, value).map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "call",
+        |mut store: StoreContextMut<'_, Context>, params: CallParams| {
+            Ok((call(&mut store, params)?.map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "create",
+        |mut store: StoreContextMut<'_, Context>,
+         (code, args, salt, gas): (Vec<u8>, Vec<String>, Option<String>, u64)| {
+            let address = create(&mut store, code.into(), args, salt, gas)?;
+            Ok((address.map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "create-from-hash",
+        |mut store: StoreContextMut<'_, Context>,
+         (code_hash, args, salt, gas): (String, Vec<String>, Option<String>, u64)| {
+            let code = H256::from_str(&code_hash)
+                .map_err(|_| RuntimeError::InvalidParamValue(code_hash))
+                .and_then(|code_hash| store.data().state.code_by_hash(&code_hash));
+            let address = match code {
+                Ok(code) => create(&mut store, code, args, salt, gas)?,
+                Err(error) => Err(error),
+            };
+            Ok((address.map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "set-code",
+        |mut store: StoreContextMut<'_, Context>, (code_hash,): (String,)| {
+            store.consume_fuel(SET_CODE_GAS)?;
+            Ok((set_code(&mut store, &code_hash).map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "self-destruct",
+        |mut store: StoreContextMut<'_, Context>, (beneficiary,): (String,)| {
+            Ok((self_destruct(&mut store, &beneficiary).map_err(|e| e.to_string()),))
+        },
+    )?;
+
+    host.func_wrap(
+        "keccak256",
+        |mut store: StoreContextMut<'_, Context>, (data,): (Vec<u8>,)| {
+            store.consume_fuel(KECCAK256_GAS)?;
+            Ok((hash(&data).to_vec(),))
+        },
+    )?;
+
+    host.func_wrap(
+        "ecrecover",
+        |mut store: StoreContextMut<'_, Context>,
+         (message, signature, recovery_id): (Vec<u8>, Vec<u8>, u8)| {
+            store.consume_fuel(ECRECOVER_GAS)?;
+            let address = recover_address(&message, &signature, recovery_id.into())
+                .map(to_hex)
+                .map_err(|e| e.to_string());
+            Ok((address,))
+        },
+    )?;
+
+    // malformed signatures and keys don't verify rather than trapping
+    host.func_wrap(
+        "verify",
+        |mut store: StoreContextMut<'_, Context>,
+         (message, signature, public_key): (Vec<u8>, Vec<u8>, Vec<u8>)| {
+            store.consume_fuel(VERIFY_GAS)?;
+            let verified = PublicKey::from_slice(&public_key)
+                .map(|key| verify(&message, &signature, &key).unwrap_or(false))
+                .unwrap_or(false);
+            Ok((verified,))
+        },
+    )?;
+
+    host.func_wrap(
+        "debug-print",
+        |store: StoreContextMut<'_, Context>, (message,): (String,)| {
+            let context = store.data();
+
+            if let Some(Ok(mut debug)) = context.debug.as_ref().map(|debug| debug.lock()) {
+                debug.push(DebugMessage::new(context.address, &message));
+            }
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// The gas charged for writing a value to storage
+pub(crate) fn set_gas(key: &str, value: &str) -> u64 {
+    SET_GAS + SET_BYTE_GAS * (key.len() + value.len()) as u64
+}
+
+/// The gas charged for emitting an event
+pub(crate) fn emit_gas(topics: &[String], data: &[String]) -> u64 {
+    let bytes = topics.iter().chain(data).map(String::len).sum::<usize>();
+
+    EMIT_GAS + EMIT_BYTE_GAS * bytes as u64
+}
+
+/// Transfer value from the contract to any account
+fn transfer(store: &mut StoreContextMut<'_, Context>, to: &str, value: u64) -> Result<()> {
+    let to = Account::from_str(to).map_err(|_| RuntimeError::InvalidParamValue(to.into()))?;
+    let context = store.data_mut();
+
+    context
+        .state
+        .transfer(&context.address, &to, U256::from(value))
+}
+
+/// Replace the code of the contract with code already on the chain.
+/// The call keeps running the old code, later calls run the new code.
+fn set_code(store: &mut StoreContextMut<'_, Context>, code_hash: &str) -> Result<()> {
+    let hash =
+        H256::from_str(code_hash).map_err(|_| RuntimeError::InvalidParamValue(code_hash.into()))?;
+    let context = store.data_mut();
+    let code = context.state.code_by_hash(&hash)?;
+
+    validate(&code)?;
+
+    context.state.upgrade(context.address, code);
+    context.state.emit(Event {
+        address: context.address,
+        topics: vec!["Upgraded".into(), format!("{:?}", hash)],
+        data: vec![],
+    });
+
+    Ok(())
+}
+
+/// Remove the contract and its storage, sending its balance to the beneficiary
+fn self_destruct(store: &mut StoreContextMut<'_, Context>, beneficiary: &str) -> Result<()> {
+    let beneficiary = Account::from_str(beneficiary)
+        .map_err(|_| RuntimeError::InvalidParamValue(beneficiary.into()))?;
+    let context = store.data_mut();
+    let balance = context.state.destroy(&context.address, &beneficiary)?;
+
+    context.state.emit(Event {
+        address: context.address,
+        topics: vec!["SelfDestructed".into(), to_hex(beneficiary)],
+        data: vec![balance.to_string()],
+    });
+
+    Ok(())
+}
+
+/// Call a function of another contract in a new store.
+///
+/// The callee runs against a copy of the caller's state, which replaces the
+/// caller's state only if the callee succeeds, so a revert rolls back all of
+/// the callee's changes, including the value sent.
+/// The gas used by the callee is charged to the caller, even if it reverts.
+/// The outer result traps the caller, the inner result is returned to the caller.
+fn call(
+    store: &mut StoreContextMut<'_, Context>,
+    (address, function, params, value, gas): CallParams,
+) -> anyhow::Result<Result<Vec<String>>> {
+    let remaining_gas = store.consume_fuel(0)?;
+    let context = store.data();
+
+    if context.depth >= MAX_CALL_DEPTH {
+        return Ok(Err(RuntimeError::MaxCallDepthExceeded(MAX_CALL_DEPTH)));
+    }
+
+    if function == CONSTRUCTOR {
+        return Ok(Err(RuntimeError::ConstructorNotCallable));
+    }
+
+    let callee = match Account::from_str(&address) {
+        Ok(callee) => callee,
+        Err(_) => return Ok(Err(RuntimeError::InvalidParamValue(address))),
+    };
+    let mut state = context.state.clone();
+    let value = U256::from(value);
+    let code = match state
+        .transfer(&context.address, &callee, value)
+        .and_then(|_| state.code(&callee))
+    {
+        Ok(code) => code,
+        Err(error) => return Ok(Err(error)),
+    };
+    let callee_context = nested_context(context, callee, value, gas.min(remaining_gas), state);
+    run_nested(store, &code, callee_context, &function, &params)
+}
+
+/// Deploy a contract from another contract, running its constructor in a new store
+/// the same way a function of another contract is called.
+///
+/// The address is derived from the salt and the code, or from the creating
+/// contract's nonce, which is incremented for every contract it creates.
+/// The outer result traps the creator, the inner result is returned to the creator.
+fn create(
+    store: &mut StoreContextMut<'_, Context>,
+    code: Bytes,
+    args: Vec<String>,
+    salt: Option<String>,
+    gas: u64,
+) -> anyhow::Result<Result<String>> {
+    store.consume_fuel(CREATE_GAS)?;
+
+    let remaining_gas = store.consume_fuel(0)?;
+    let context = store.data();
+
+    if context.depth >= MAX_CALL_DEPTH {
+        return Ok(Err(RuntimeError::MaxCallDepthExceeded(MAX_CALL_DEPTH)));
+    }
+
+    // invalid code is rejected before anything runs
+    if let Err(error) = validate(&code) {
+        return Ok(Err(error));
+    }
+
+    let mut state = context.state.clone();
+    let address = match salt.map(|salt| H256::from_str(&salt).map_err(|_| salt)) {
+        Some(Ok(salt)) => contract_address_with_salt(&context.address, &salt, &code),
+        Some(Err(salt)) => return Ok(Err(RuntimeError::InvalidParamValue(salt))),
+        None => match state.increment_nonce(&context.address) {
+            Ok(nonce) => contract_address(&context.address, nonce),
+            Err(error) => return Ok(Err(error)),
+        },
+    };
+
+    match state.is_contract(&address) {
+        Ok(false) => {}
+        Ok(true) => return Ok(Err(RuntimeError::ContractAlreadyExists(to_hex(address)))),
+        Err(error) => return Ok(Err(error)),
+    }
+
+    let gas = gas.min(remaining_gas);
+    let contract_context = nested_context(context, address, U256::zero(), gas, state);
+    let result = run_nested(store, &code, contract_context, CONSTRUCTOR, &args)?;
+
+    // the contract exists once its constructor has succeeded
+    if result.is_ok() {
+        store.data_mut().state.create(address, code);
+    }
+
+    Ok(result.map(|_| to_hex(address)))
+}
+
+/// The context of a call made by the contract in the context
+fn nested_context(
+    context: &Context,
+    address: Account,
+    value: U256,
+    gas: u64,
+    state: State,
+) -> Context {
+    Context {
+        caller: context.address,
+        address,
+        value,
+        block_number: context.block_number,
+        block_timestamp: context.block_timestamp,
+        gas,
+        depth: context.depth + 1,
+        state,
+        // the callee holds its resources alongside its callers
+        limits: context.limiter.remaining(),
+        limiter: Limiter::default(),
+        debug: context.debug.clone(),
+        call_data: CallData::default(),
+    }
+}
+
+/// Run a function in a new store, charging the gas it used to the caller.
+/// The callee's state replaces the caller's state only if the callee succeeds.
+fn run_nested(
+    store: &mut StoreContextMut<'_, Context>,
+    code: &[u8],
+    context: Context,
+    function: &str,
+    params: &[String],
+) -> anyhow::Result<Result<Vec<String>>> {
+    let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+    let engine = store.engine().clone();
+    let (callee_store, result) = run(&engine, code, context, function, &params);
+
+    store.consume_fuel(callee_store.fuel_consumed().unwrap_or_default())?;
+
+    if result.is_ok() {
+        store.data_mut().state = callee_store.into_data().state;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::DebugOutput;
+    use crate::contract::call_function;
+    use crate::contract::tests::{context, GAS};
+    use crate::harness::{Harness, MemoryBackend};
+    use std::sync::Arc;
+    use types::bytes::Bytes;
+    use utils::crypto::{keypair, public_key_address, sign, sign_recovery};
+
+    const FORWARDER: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/forwarder_wit.wasm");
+    const ERC20: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+    const ERC20_PARAMS: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
+
+    /// Deploy two forwarders, the first one holding a balance of 100
+    fn forwarders() -> (Context, Account) {
+        let (forwarder_1, forwarder_2) = (Account::random(), Account::random());
+        let mut backend = MemoryBackend::default();
+        backend.code.insert(forwarder_1, Bytes::from(FORWARDER));
+        backend.code.insert(forwarder_2, Bytes::from(FORWARDER));
+        backend.balances.insert(forwarder_1, U256::from(100));
+        let mut context = context(backend);
+        context.address = forwarder_1;
+
+        (context, forwarder_2)
+    }
+
+    fn call_params<'a>(
+        callee: &'a str,
+        function: &'a str,
+        params: &'a str,
+        value: &'a str,
+        gas: &'a str,
+    ) -> [&'a str; 10] {
+        [
+            "String",
+            callee,
+            "String",
+            function,
+            "List<String>",
+            params,
+            "U64",
+            value,
+            "U64",
+            gas,
+        ]
+    }
+
+    #[test]
+    fn storage_writes_and_events_cost_gas() {
+        let mut harness = Harness::new();
+        let erc20 = harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let account = to_hex(Account::random());
+
+        // the balance and the total supply are written, and a transfer is emitted
+        harness
+            .call(erc20, "mint", &["String", &account, "U64", "10"])
+            .unwrap();
+
+        assert!(harness.gas_used() > 2 * SET_GAS + EMIT_GAS);
+    }
+
+    #[test]
+    fn it_calls_another_contract() {
+        let (context, callee) = forwarders();
+        let callee = to_hex(callee);
+        let params = call_params(&callee, "echo", r#"["String","hello"]"#, "0", "1000000");
+        let execution = call_function(FORWARDER, context, "forward", &params).unwrap();
+
+        assert_eq!(execution.result, ["List<String>", r#"["String","hello"]"#]);
+    }
+
+    #[test]
+    fn it_sends_value_with_a_call() {
+        let (context, callee) = forwarders();
+        let caller = context.address;
+        let callee_hex = to_hex(callee);
+        let params = call_params(&callee_hex, "echo", r#"["String","hi"]"#, "10", "1000000");
+        let execution = call_function(FORWARDER, context, "forward", &params).unwrap();
+
+        assert_eq!(execution.state.balance(&caller).unwrap(), U256::from(90));
+        assert_eq!(execution.state.balance(&callee).unwrap(), U256::from(10));
+    }
+
+    #[test]
+    fn a_call_to_itself_keeps_the_value() {
+        let (context, _) = forwarders();
+        let caller = context.address;
+        let caller_hex = to_hex(caller);
+        let params = call_params(&caller_hex, "echo", r#"["String","hi"]"#, "10", "1000000");
+        let execution = call_function(FORWARDER, context, "forward", &params).unwrap();
+
+        assert_eq!(execution.state.balance(&caller).unwrap(), U256::from(100));
+    }
+
+    #[test]
+    fn it_rolls_back_a_reverted_call() {
+        let (context, callee) = forwarders();
+        let callee_hex = to_hex(callee);
+        let params = call_params(&callee_hex, "fail", "[]", "10", "1000000");
+        let execution = call_function(FORWARDER, context, "try-forward", &params).unwrap();
+
+        // the error is returned to the caller, which continues executing
+        assert_eq!(execution.result[0], "String");
+        assert!(execution.result[1].contains("unreachable"));
+
+        // the value sent to the callee is rolled back
+        assert!(execution.state.balances().is_empty());
+        assert_eq!(execution.state.balance(&callee).unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn a_reverted_call_can_revert_the_caller() {
+        let (context, callee) = forwarders();
+        let callee = to_hex(callee);
+        let params = call_params(&callee, "fail", "[]", "0", "1000000");
+        let response = call_function(FORWARDER, context, "forward", &params);
+
+        assert!(matches!(response, Err(RuntimeError::CallFunctionError(_))));
+    }
+
+    #[test]
+    fn it_limits_the_call_depth() {
+        let (mut context, _) = forwarders();
+        context.gas = GAS * 10;
+        let execution = call_function(FORWARDER, context, "recurse", &["U64", "0"]).unwrap();
+
+        assert_eq!(execution.result, ["U64", &MAX_CALL_DEPTH.to_string()]);
+    }
+
+    #[test]
+    fn it_limits_the_gas_passed_to_the_callee() {
+        let (context, callee) = forwarders();
+        let callee = to_hex(callee);
+        let params = call_params(&callee, "echo", r#"["String","hi"]"#, "0", "10");
+        let execution = call_function(FORWARDER, context, "try-forward", &params).unwrap();
+
+        assert!(execution.result[1].contains("fuel"));
+        assert!(execution.gas_used < GAS);
+    }
+
+    #[test]
+    fn callees_get_the_limits_their_callers_left() {
+        let (mut context, callee) = forwarders();
+        let callee = to_hex(callee);
+        let params = call_params(&callee, "echo", r#"["String","hi"]"#, "0", "1000000");
+
+        // the caller holds 18 pages of memory and 3 instances during the call
+        context.limits.memory_pages = 30;
+        let execution = call_function(FORWARDER, context.clone(), "try-forward", &params).unwrap();
+        assert!(execution.result[1].contains("Resource limit exceeded: memory of 12 pages"));
+
+        context.limits.memory_pages = 64;
+        context.limits.instances = 5;
+        let execution = call_function(FORWARDER, context.clone(), "try-forward", &params).unwrap();
+        assert!(execution.result[1].contains("Resource limit exceeded: 2 instances"));
+
+        context.limits.instances = 6;
+        let execution = call_function(FORWARDER, context, "forward", &params).unwrap();
+        assert_eq!(execution.result, ["List<String>", r#"["String","hi"]"#]);
+    }
+
+    #[test]
+    fn it_collects_debug_output_from_every_call() {
+        let (mut context, callee) = forwarders();
+        let caller = context.address;
+        let debug = DebugOutput::default();
+        context.debug = Some(debug.clone());
+        let callee_hex = to_hex(callee);
+        let params = call_params(&callee_hex, "fail", "[]", "0", "1000000");
+        call_function(FORWARDER, context, "try-forward", &params).unwrap();
+
+        let (mut context, _) = forwarders();
+        context.address = caller;
+        context.debug = Some(debug.clone());
+        call_function(FORWARDER, context, "echo", &["String", "hi"]).unwrap();
+
+        // the reverted callee's message is kept
+        assert_eq!(
+            *debug.lock().unwrap(),
+            [
+                DebugMessage::new(callee, "failing"),
+                DebugMessage::new(caller, "hi")
+            ]
+        );
+    }
+
+    #[test]
+    fn it_hashes_data() {
+        let (context, _) = forwarders();
+        let execution = call_function(FORWARDER, context, "hash", &["String", "0x"]).unwrap();
+        let empty_hash = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+        assert_eq!(execution.result, ["String", empty_hash]);
+        assert!(execution.gas_used > KECCAK256_GAS);
+    }
+
+    #[test]
+    fn it_recovers_the_signer_of_a_message() {
+        let (key, public_key) = keypair();
+        let message = b"permit";
+        let (recovery_id, signature) = sign_recovery(message, &key).unwrap().serialize_compact();
+        let (message, signature) = (hex::encode(message), hex::encode(signature));
+        let recovery_id = recovery_id.to_i32().to_string();
+        let params = [
+            "String",
+            &message,
+            "String",
+            &signature,
+            "U64",
+            &recovery_id,
+        ];
+        let (context, _) = forwarders();
+        let execution = call_function(FORWARDER, context, "recover", &params).unwrap();
+
+        assert_eq!(
+            execution.result,
+            ["String", &to_hex(public_key_address(&public_key))]
+        );
+        assert!(execution.gas_used > ECRECOVER_GAS);
+    }
+
+    #[test]
+    fn it_verifies_signatures() {
+        let (key, public_key) = keypair();
+        let (_, other_public_key) = keypair();
+        let message = hex::encode(b"approve");
+        let signature = sign(b"approve", &key).unwrap().serialize_compact();
+        let signature = hex::encode(signature);
+        let verify_with = |public_key: &str| {
+            let params = [
+                "String", &message, "String", &signature, "String", public_key,
+            ];
+            let (context, _) = forwarders();
+            call_function(FORWARDER, context, "verify-signature", &params)
+                .unwrap()
+                .result
+        };
+
+        let signer = hex::encode(public_key.serialize());
+        let other = hex::encode(other_public_key.serialize());
+
+        assert_eq!(verify_with(&signer), ["Bool", "true"]);
+        assert_eq!(verify_with(&other), ["Bool", "false"]);
+        assert_eq!(verify_with("00"), ["Bool", "false"]);
+    }
+
+    #[test]
+    fn it_errors_calling_a_non_contract() {
+        let (context, _) = forwarders();
+        let account = to_hex(Account::random());
+        let params = call_params(&account, "echo", r#"["String","hi"]"#, "0", "1000000");
+        let execution = call_function(FORWARDER, context, "try-forward", &params).unwrap();
+
+        assert!(execution.result[1].contains("not a contract"));
+    }
+
+    #[test]
+    fn it_creates_contracts() {
+        let (mut context, _) = forwarders();
+        let creator = context.address;
+        let erc20 = Bytes::from(ERC20);
+        let code_hash = format!("{:?}", H256::from(hash(&erc20)));
+        let args = r#"["String","Rust Coin","String","RustCoin"]"#;
+        context.state = State::new(Arc::new(MemoryBackend {
+            code: [
+                (creator, Bytes::from(FORWARDER)),
+                (Account::random(), erc20),
+            ]
+            .into(),
+            ..MemoryBackend::default()
+        }));
+        let params = ["String", &code_hash, "List<String>", args, "String", ""];
+        let execution = call_function(FORWARDER, context, "create", &params).unwrap();
+        let address = contract_address(&creator, U256::one());
+
+        assert_eq!(execution.result, ["String", &to_hex(address)]);
+        assert_eq!(execution.state.nonce(&creator).unwrap(), U256::one());
+        assert_eq!(execution.state.code(&address).unwrap(), Bytes::from(ERC20));
+        assert_eq!(
+            execution.state.get(&address, "name").unwrap(),
+            Some("Rust Coin".into())
+        );
+    }
+
+    #[test]
+    fn it_creates_contracts_with_a_salt_once() {
+        let mut harness = Harness::new();
+        let forwarder = harness.deploy(FORWARDER, &[]).unwrap();
+        harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let code_hash = format!("{:?}", H256::from(hash(ERC20)));
+        let salt = H256::random();
+        let args = r#"["String","Rust Coin","String","RustCoin"]"#;
+        let params = [
+            "String",
+            &code_hash,
+            "List<String>",
+            args,
+            "String",
+            &format!("{:?}", salt),
+        ];
+        let address = contract_address_with_salt(&forwarder, &salt, &Bytes::from(ERC20));
+
+        assert_eq!(
+            harness.call(forwarder, "create", &params).unwrap(),
+            ["String", &to_hex(address)]
+        );
+        assert_eq!(
+            harness.view(address, "name", &[]).unwrap(),
+            ["String", "Rust Coin"]
+        );
+        assert!(matches!(
+            harness.call(forwarder, "create", &params),
+            Err(RuntimeError::Reverted(error)) if error.contains("already exists")
+        ));
+    }
+
+    #[test]
+    fn it_errors_creating_from_unknown_code() {
+        let (context, _) = forwarders();
+        let code_hash = format!("{:?}", H256::random());
+        let params = ["String", &code_hash, "List<String>", "[]", "String", ""];
+
+        assert!(matches!(
+            call_function(FORWARDER, context, "create", &params),
+            Err(RuntimeError::Reverted(error)) if error.contains("Code not found")
+        ));
+    }
+
+    #[test]
+    fn the_admin_upgrades_a_contract() {
+        let mut harness = Harness::new();
+        let admin = harness.caller();
+        let forwarder = harness.deploy(FORWARDER, &[]).unwrap();
+        harness.deploy(ERC20, ERC20_PARAMS).unwrap();
+        let code_hash = format!("{:?}", H256::from(hash(ERC20)));
+
+        harness.set_caller(Account::random());
+        assert!(matches!(
+            harness.call(forwarder, "upgrade", &["String", &code_hash]),
+            Err(RuntimeError::Reverted(error)) if error == "caller is not the admin"
+        ));
+
+        harness.set_caller(admin);
+        harness
+            .call(forwarder, "upgrade", &["String", &code_hash])
+            .unwrap();
+
+        assert_eq!(harness.events()[0].topics, ["Upgraded", &code_hash]);
+        assert_eq!(
+            harness.view(forwarder, "symbol", &[]).unwrap(),
+            ["String", ""]
+        );
+        assert!(matches!(
+            harness.view(forwarder, "echo", &["String", "hi"]),
+            Err(RuntimeError::ExportFunctionError(_))
+        ));
+    }
+
+    #[test]
+    fn a_destroyed_contract_sends_its_balance_and_clears_its_storage() {
+        let mut harness = Harness::new();
+        let forwarder = harness.deploy(FORWARDER, &[]).unwrap();
+        let beneficiary = Account::random();
+        harness.set_balance(forwarder, U256::from(10));
+
+        harness
+            .call(forwarder, "destroy", &["String", &to_hex(beneficiary)])
+            .unwrap();
+
+        assert_eq!(harness.balance(beneficiary), U256::from(10));
+        assert_eq!(harness.balance(forwarder), U256::zero());
+        assert_eq!(harness.storage(forwarder, "admin"), None);
+        assert_eq!(
+            harness.events()[0].topics,
+            ["SelfDestructed", &to_hex(beneficiary)]
+        );
+        assert_eq!(harness.events()[0].data, ["10"]);
+        assert!(matches!(
+            harness.view(forwarder, "echo", &["String", "hi"]),
+            Err(RuntimeError::NotAContract(_))
+        ));
+    }
+}
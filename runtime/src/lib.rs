@@ -1,2 +1,10 @@
+mod cache;
+pub mod context;
 pub mod contract;
 pub mod error;
+pub mod harness;
+mod host;
+pub mod limits;
+mod module;
+pub mod state;
+pub mod validate;
@@ -0,0 +1,231 @@
+//! # Limits
+//!
+//! Gas bounds the work a contract does, these bound the resources it holds:
+//! linear memory, table elements, instances and native stack.
+//! Hitting a limit fails the call with `RuntimeError::ResourceLimitExceeded`.
+//! Contracts calling other contracts hold their resources during the call,
+//! so a callee is limited to what its callers left.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::OnceLock;
+
+use wasmtime::{ResourceLimiter, Trap};
+
+use crate::error::{Result, RuntimeError};
+
+/// The size of a page of linear memory
+pub const PAGE_SIZE: usize = 64 * 1024;
+
+/// The stack limit of the engine, which is shared by every contract
+static MAX_WASM_STACK: OnceLock<usize> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum size of the linear memories on the call stack, in 64 KiB pages
+    pub memory_pages: usize,
+
+    /// The maximum number of elements in the tables on the call stack
+    pub table_elements: u32,
+
+    /// The maximum number of core instances on the call stack
+    pub instances: usize,
+
+    /// The maximum native stack wasm code can use, in bytes.
+    /// This is set on the engine, see `set_max_wasm_stack`.
+    pub max_wasm_stack: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            memory_pages: 1024,
+            table_elements: 10_000,
+            instances: 128,
+            max_wasm_stack: 512 * 1024,
+        }
+    }
+}
+
+/// Set the stack limit of the engine before any contract runs.
+/// The engine is shared, so it can't change once it's set.
+pub fn set_max_wasm_stack(size: usize) -> Result<()> {
+    match *MAX_WASM_STACK.get_or_init(|| size) {
+        current if current == size => Ok(()),
+        current => Err(RuntimeError::EngineConfigured(format!(
+            "the maximum wasm stack is already {} bytes",
+            current
+        ))),
+    }
+}
+
+pub(crate) fn max_wasm_stack() -> usize {
+    *MAX_WASM_STACK.get_or_init(|| Limits::default().max_wasm_stack)
+}
+
+/// Enforces the limits of a store, remembering the limit that was hit
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Limiter {
+    limits: Limits,
+
+    /// The bytes of linear memory held by the store
+    memory: usize,
+
+    /// The table elements held by the store
+    table_elements: u32,
+
+    /// The core instances created in the store
+    instances: usize,
+
+    exceeded: Option<String>,
+}
+
+impl Limiter {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Count the core instances created in the store, which wasmtime doesn't report
+    pub(crate) fn instantiated(&mut self, instances: usize) {
+        self.instances += instances;
+    }
+
+    /// The limits left for a call made from the store
+    pub(crate) fn remaining(&self) -> Limits {
+        Limits {
+            memory_pages: self
+                .limits
+                .memory_pages
+                .saturating_sub(self.memory.div_ceil(PAGE_SIZE)),
+            table_elements: self
+                .limits
+                .table_elements
+                .saturating_sub(self.table_elements),
+            instances: self.limits.instances.saturating_sub(self.instances),
+            max_wasm_stack: self.limits.max_wasm_stack,
+        }
+    }
+
+    pub(crate) fn exceeded(&self) -> Option<RuntimeError> {
+        self.exceeded
+            .clone()
+            .map(RuntimeError::ResourceLimitExceeded)
+    }
+
+    /// The limit behind a failed call, if any.
+    /// Growing past a limit makes `memory.grow` return -1, so the trap that
+    /// follows is reported as the limit rather than the trap.
+    pub(crate) fn error(&self, error: &anyhow::Error) -> Option<RuntimeError> {
+        if let Some(exceeded) = self.exceeded() {
+            return Some(exceeded);
+        }
+
+        let exceeded = match error.downcast_ref::<Trap>() {
+            Some(Trap::StackOverflow) => format!("stack of {} bytes", max_wasm_stack()),
+            // instances are counted by wasmtime, which only reports the error
+            _ if error
+                .root_cause()
+                .to_string()
+                .contains("instance count too high") =>
+            {
+                format!("{} instances", self.limits.instances)
+            }
+            _ => return None,
+        };
+
+        Some(RuntimeError::ResourceLimitExceeded(exceeded))
+    }
+}
+
+impl ResourceLimiter for Limiter {
+    fn memory_growing(&mut self, current: usize, desired: usize, _maximum: Option<usize>) -> bool {
+        let memory = self.memory.saturating_add(desired - current);
+
+        if memory > self.limits.memory_pages * PAGE_SIZE {
+            self.exceeded = Some(format!("memory of {} pages", self.limits.memory_pages));
+            return false;
+        }
+
+        self.memory = memory;
+        true
+    }
+
+    fn table_growing(&mut self, current: u32, desired: u32, _maximum: Option<u32>) -> bool {
+        let table_elements = self.table_elements.saturating_add(desired - current);
+
+        if table_elements > self.limits.table_elements {
+            self.exceeded = Some(format!("table of {} elements", self.limits.table_elements));
+            return false;
+        }
+
+        self.table_elements = table_elements;
+        true
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::call_function;
+    use crate::contract::tests::context;
+    use crate::harness::MemoryBackend;
+
+    const ERC20: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+
+    fn call_with(limits: Limits) -> Result<Vec<String>> {
+        let mut context = context(MemoryBackend::default());
+        context.limits = limits;
+
+        call_function(ERC20, context, "total-supply", &[]).map(|execution| execution.result)
+    }
+
+    #[test]
+    fn contracts_run_within_the_default_limits() {
+        assert_eq!(call_with(Limits::default()).unwrap(), ["U64", "0"]);
+    }
+
+    #[test]
+    fn it_limits_memory() {
+        let limits = Limits {
+            memory_pages: 1,
+            ..Limits::default()
+        };
+
+        assert!(matches!(
+            call_with(limits),
+            Err(RuntimeError::ResourceLimitExceeded(exceeded)) if exceeded == "memory of 1 pages"
+        ));
+    }
+
+    #[test]
+    fn it_limits_instances() {
+        let limits = Limits {
+            instances: 1,
+            ..Limits::default()
+        };
+
+        assert!(matches!(
+            call_with(limits),
+            Err(RuntimeError::ResourceLimitExceeded(exceeded)) if exceeded == "1 instances"
+        ));
+    }
+
+    #[test]
+    fn the_engine_stack_cannot_change_once_set() {
+        let max_wasm_stack = max_wasm_stack();
+
+        assert!(set_max_wasm_stack(max_wasm_stack).is_ok());
+        assert!(matches!(
+            set_max_wasm_stack(max_wasm_stack + 1),
+            Err(RuntimeError::EngineConfigured(_))
+        ));
+    }
+}
@@ -0,0 +1,482 @@
+//! # Core Modules
+//!
+//! Contracts built without wit-bindgen, such as AssemblyScript, TinyGo or
+//! hand-written WAT, are plain core modules.
+//! Core modules import host functions from `env` and exchange data through
+//! their exported `memory`, passing pointers and lengths:
+//!
+//! * Exported functions take no params and return nothing.
+//! * The call data is a JSON array of typed pairs, e.g. `["String","Rust Coin","U64","10"]`.
+//!   `input_size` returns its length and `input` copies it to a pointer.
+//! * `output` sets the return data, a JSON array of typed pairs as well.
+//! * `revert` reverts the call with a UTF-8 message.
+//! * `storage_size` returns the length of a stored value, or -1 if it isn't set,
+//!   `storage_read` copies it to a pointer and `storage_write` sets it.
+//! * `log` emits an event from JSON arrays of topics and data.
+//! * Storage and `log` cost the same gas as `get`, `set` and `emit` in components.
+//! * `caller` and `address` copy the 42 byte hex address to a pointer, and
+//!   `value` returns the value sent along with the call as a u64, larger values saturate.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use ethereum_types::H256;
+use lazy_static::lazy_static;
+use types::helpers::to_hex;
+use types::interface::FunctionSignature;
+use utils::crypto::hash;
+use wasmparser::{Parser, Payload};
+use wasmtime::{Caller, Engine, Extern, Linker, Module, Store};
+
+use crate::cache::{Cache, MAX_COMPILED};
+use crate::context::Context;
+use crate::contract::missing_export;
+use crate::error::{Result, RuntimeError};
+use crate::host::{emit_gas, set_gas, GET_GAS};
+use crate::state::Event;
+
+pub(crate) const HOST: &str = "env";
+
+/// The host functions core modules can import from `env`
+pub(crate) const FUNCTIONS: &[&str] = &[
+    "input_size",
+    "input",
+    "output",
+    "revert",
+    "storage_size",
+    "storage_read",
+    "storage_write",
+    "log",
+    "caller",
+    "address",
+    "value",
+];
+
+/// The custom section wit-bindgen adds to modules that are turned into components
+const COMPONENT_TYPE: &str = "component-type";
+
+lazy_static! {
+    /// Compiled modules, keyed by the hash of the contract code
+    static ref MODULES: Mutex<Cache<Module>> = Mutex::new(Cache::new(MAX_COMPILED));
+}
+
+/// The call data and return data of a call to a core module
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallData {
+    input: Vec<u8>,
+    output: Vec<u8>,
+}
+
+/// Whether the code is a module built with wit-bindgen, which runs as a component.
+/// Other modules run as core modules.
+pub(crate) fn is_component(bytes: &[u8]) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::CustomSection(section) = payload? {
+            if section.name().starts_with(COMPONENT_TYPE) {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Compile a core module, reusing a previous compilation of the same code
+pub(crate) fn compile(engine: &Engine, bytes: &[u8]) -> Result<Module> {
+    let code_hash: H256 = hash(bytes).into();
+
+    if let Some(module) = MODULES.lock()?.get(&code_hash) {
+        return Ok(module);
+    }
+
+    let module = Module::from_binary(engine, bytes)?;
+
+    MODULES.lock()?.insert(code_hash, module.clone());
+
+    Ok(module)
+}
+
+pub(crate) fn call(
+    store: &mut Store<Context>,
+    bytes: &[u8],
+    function: &str,
+    params: &[&str],
+) -> Result<Vec<String>> {
+    let mut linker = Linker::new(store.engine());
+
+    add_to_linker(&mut linker)?;
+
+    let module = compile(store.engine(), bytes)?;
+    let instance = linker
+        .instantiate(&mut *store, &module)
+        .map_err(|e| call_error(store, function, e))?;
+
+    store.data_mut().limiter.instantiated(1);
+
+    let func = match instance.get_typed_func::<(), ()>(&mut *store, function) {
+        Ok(func) => func,
+        Err(_) => return missing_export(store, function, params),
+    };
+
+    store.data_mut().call_data = CallData {
+        input: serde_json::to_vec(params)?,
+        output: vec![],
+    };
+
+    tracing::info!("{} params {:?}", function, params);
+
+    func.call(&mut *store, ())
+        .map_err(|e| call_error(store, function, e))?;
+
+    let output = std::mem::take(&mut store.data_mut().call_data.output);
+
+    match output.is_empty() {
+        true => Ok(vec![]),
+        false => Ok(serde_json::from_slice(&output)?),
+    }
+}
+
+/// The exported functions of a core module, which take no params and return nothing
+pub(crate) fn exported_functions(bytes: &[u8]) -> Result<Vec<FunctionSignature>> {
+    let module = compile(&crate::contract::ENGINE, bytes)?;
+
+    Ok(module
+        .exports()
+        .filter_map(|export| export.ty().func().cloned().map(|ty| (export.name(), ty)))
+        .filter(|(_, ty)| ty.params().len() == 0 && ty.results().len() == 0)
+        .map(|(name, _)| FunctionSignature::new(name, vec![], None))
+        .collect())
+}
+
+/// A revert keeps its message, a resource limit is reported as such,
+/// anything else fails the call
+fn call_error(store: &Store<Context>, function: &str, error: anyhow::Error) -> RuntimeError {
+    if let Some(RuntimeError::Reverted(message)) = error.downcast_ref::<RuntimeError>() {
+        return RuntimeError::Reverted(message.to_owned());
+    }
+
+    match store.data().limiter.error(&error) {
+        Some(exceeded) => exceeded,
+        None => RuntimeError::CallFunctionError(format!("{}: {}", function, error.root_cause())),
+    }
+}
+
+fn add_to_linker(linker: &mut Linker<Context>) -> Result<()> {
+    linker.func_wrap(HOST, "input_size", |caller: Caller<'_, Context>| {
+        caller.data().call_data.input.len() as i32
+    })?;
+
+    linker.func_wrap(
+        HOST,
+        "input",
+        |mut caller: Caller<'_, Context>, ptr: i32| {
+            let input = caller.data().call_data.input.clone();
+            write(&mut caller, ptr, &input)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "output",
+        |mut caller: Caller<'_, Context>, ptr: i32, len: i32| {
+            caller.data_mut().call_data.output = read(&mut caller, ptr, len)?;
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "revert",
+        |mut caller: Caller<'_, Context>, ptr: i32, len: i32| -> anyhow::Result<()> {
+            let message = read(&mut caller, ptr, len)?;
+            Err(RuntimeError::Reverted(String::from_utf8_lossy(&message).into()).into())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "storage_size",
+        |mut caller: Caller<'_, Context>, key_ptr: i32, key_len: i32| {
+            let value = storage(&mut caller, key_ptr, key_len)?;
+            Ok(value.map_or(-1, |value| value.len() as i32))
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "storage_read",
+        |mut caller: Caller<'_, Context>, key_ptr: i32, key_len: i32, value_ptr: i32| match storage(
+            &mut caller,
+            key_ptr,
+            key_len,
+        )? {
+            Some(value) => write(&mut caller, value_ptr, value.as_bytes()),
+            None => Ok(()),
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "storage_write",
+        |mut caller: Caller<'_, Context>,
+         key_ptr: i32,
+         key_len: i32,
+         value_ptr: i32,
+         value_len: i32| {
+            let key = read_string(&mut caller, key_ptr, key_len)?;
+            let value = read_string(&mut caller, value_ptr, value_len)?;
+            caller.consume_fuel(set_gas(&key, &value))?;
+            let context = caller.data_mut();
+            context.state.set(&context.address, key, value);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "log",
+        |mut caller: Caller<'_, Context>,
+         topics_ptr: i32,
+         topics_len: i32,
+         data_ptr: i32,
+         data_len: i32| {
+            let topics: Vec<String> =
+                serde_json::from_slice(&read(&mut caller, topics_ptr, topics_len)?)?;
+            let data: Vec<String> =
+                serde_json::from_slice(&read(&mut caller, data_ptr, data_len)?)?;
+            caller.consume_fuel(emit_gas(&topics, &data))?;
+            let context = caller.data_mut();
+            let event = Event {
+                address: context.address,
+                topics,
+                data,
+            };
+            context.state.emit(event);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "caller",
+        |mut caller: Caller<'_, Context>, ptr: i32| {
+            let address = to_hex(caller.data().caller);
+            write(&mut caller, ptr, address.as_bytes())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST,
+        "address",
+        |mut caller: Caller<'_, Context>, ptr: i32| {
+            let address = to_hex(caller.data().address);
+            write(&mut caller, ptr, address.as_bytes())
+        },
+    )?;
+
+    // values are u64 across the host interface, larger values saturate
+    linker.func_wrap(HOST, "value", |caller: Caller<'_, Context>| {
+        u64::try_from(caller.data().value).unwrap_or(u64::MAX) as i64
+    })?;
+
+    Ok(())
+}
+
+fn storage(
+    caller: &mut Caller<'_, Context>,
+    key_ptr: i32,
+    key_len: i32,
+) -> anyhow::Result<Option<String>> {
+    caller.consume_fuel(GET_GAS)?;
+
+    let key = read_string(caller, key_ptr, key_len)?;
+    let context = caller.data();
+
+    Ok(context.state.get(&context.address, &key)?)
+}
+
+fn read(caller: &mut Caller<'_, Context>, ptr: i32, len: i32) -> anyhow::Result<Vec<u8>> {
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    let data = memory(caller)?
+        .data(&*caller)
+        .get(ptr..ptr + len)
+        .ok_or_else(|| anyhow!("out of bounds memory access"))?
+        .to_vec();
+
+    Ok(data)
+}
+
+fn read_string(caller: &mut Caller<'_, Context>, ptr: i32, len: i32) -> anyhow::Result<String> {
+    Ok(String::from_utf8(read(caller, ptr, len)?)?)
+}
+
+fn write(caller: &mut Caller<'_, Context>, ptr: i32, data: &[u8]) -> anyhow::Result<()> {
+    memory(caller)?.write(&mut *caller, ptr as u32 as usize, data)?;
+
+    Ok(())
+}
+
+fn memory(caller: &mut Caller<'_, Context>) -> anyhow::Result<wasmtime::Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => Err(anyhow!("the module must export its memory")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::interface;
+    use crate::contract::tests::context;
+    use crate::harness::{Harness, MemoryBackend};
+    use ethereum_types::U256;
+
+    /// A hand-written contract keeping its call data in storage
+    const CONTRACT: &str = r#"
+        (module
+          (import "env" "input_size" (func $input_size (result i32)))
+          (import "env" "input" (func $input (param i32)))
+          (import "env" "output" (func $output (param i32 i32)))
+          (import "env" "revert" (func $revert (param i32 i32)))
+          (import "env" "storage_size" (func $storage_size (param i32 i32) (result i32)))
+          (import "env" "storage_read" (func $storage_read (param i32 i32 i32)))
+          (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32)))
+          (import "env" "log" (func $log (param i32 i32 i32 i32)))
+          (import "env" "value" (func $value (result i64)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "key")
+          (data (i32.const 16) "[\"Stored\"]")
+          (data (i32.const 32) "[\"1\"]")
+          (data (i32.const 48) "not allowed")
+          (data (i32.const 64) "not saturated")
+
+          (func (export "echo")
+            (local $size i32)
+            (local.set $size (call $input_size))
+            (call $input (i32.const 1024))
+            (call $output (i32.const 1024) (local.get $size)))
+
+          (func (export "store")
+            (local $size i32)
+            (local.set $size (call $input_size))
+            (call $input (i32.const 1024))
+            (call $storage_write (i32.const 0) (i32.const 3) (i32.const 1024) (local.get $size))
+            (call $log (i32.const 16) (i32.const 10) (i32.const 32) (i32.const 5)))
+
+          (func (export "load")
+            (local $size i32)
+            (local.set $size (call $storage_size (i32.const 0) (i32.const 3)))
+            (if (i32.lt_s (local.get $size) (i32.const 0)) (then (return)))
+            (call $storage_read (i32.const 0) (i32.const 3) (i32.const 1024))
+            (call $output (i32.const 1024) (local.get $size)))
+
+          (func (export "fail")
+            (call $revert (i32.const 48) (i32.const 11)))
+
+          (func (export "spin")
+            (loop $spin (br $spin)))
+
+          (func (export "saturated")
+            (if (i64.ne (call $value) (i64.const -1))
+              (then (call $revert (i32.const 64) (i32.const 13))))))
+    "#;
+
+    fn contract() -> Vec<u8> {
+        wat::parse_str(CONTRACT).unwrap()
+    }
+
+    #[test]
+    fn it_detects_core_modules() {
+        let erc20 = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+
+        assert!(is_component(erc20).unwrap());
+        assert!(!is_component(&contract()).unwrap());
+    }
+
+    #[test]
+    fn it_calls_core_modules() {
+        let mut harness = Harness::new();
+        let contract = harness.deploy(&contract(), &[]).unwrap();
+
+        assert_eq!(
+            harness.view(contract, "echo", &["String", "hi"]).unwrap(),
+            ["String", "hi"]
+        );
+        assert!(harness.view(contract, "load", &[]).unwrap().is_empty());
+
+        harness.call(contract, "store", &["U64", "7"]).unwrap();
+        assert_eq!(harness.view(contract, "load", &[]).unwrap(), ["U64", "7"]);
+        assert_eq!(harness.events()[0].topics, ["Stored"]);
+        assert_eq!(harness.events()[0].data, ["1"]);
+    }
+
+    #[test]
+    fn core_modules_pay_for_the_bytes_they_store() {
+        let mut harness = Harness::new();
+        let contract = harness.deploy(&contract(), &[]).unwrap();
+        let long = "a".repeat(1_000);
+
+        harness.call(contract, "store", &["String", "a"]).unwrap();
+        let short_gas = harness.gas_used();
+        harness.call(contract, "store", &["String", &long]).unwrap();
+
+        assert!(short_gas > crate::host::SET_GAS + crate::host::EMIT_GAS);
+        assert!(harness.gas_used() - short_gas >= 999 * crate::host::SET_BYTE_GAS);
+    }
+
+    #[test]
+    fn core_modules_revert_and_trap() {
+        let mut harness = Harness::new();
+        let contract = harness.deploy(&contract(), &[]).unwrap();
+
+        assert!(matches!(
+            harness.call(contract, "fail", &[]),
+            Err(RuntimeError::Reverted(error)) if error == "not allowed"
+        ));
+        assert!(matches!(
+            harness.call(contract, "spin", &[]),
+            Err(RuntimeError::CallFunctionError(_))
+        ));
+        assert!(matches!(
+            harness.call(contract, "missing", &[]),
+            Err(RuntimeError::ExportFunctionError(_))
+        ));
+        assert!(matches!(
+            harness.transfer(contract),
+            Err(RuntimeError::ValueNotAccepted(_))
+        ));
+    }
+
+    #[test]
+    fn core_modules_saturate_large_values() {
+        let mut harness = Harness::new();
+        let contract = harness.deploy(&contract(), &[]).unwrap();
+        harness.set_balance(harness.caller(), U256::MAX);
+
+        harness.set_value(U256::from(u64::MAX) + 1);
+        harness.call(contract, "saturated", &[]).unwrap();
+
+        harness.set_value(U256::from(u64::MAX) - 1);
+        assert!(matches!(
+            harness.call(contract, "saturated", &[]),
+            Err(RuntimeError::Reverted(error)) if error == "not saturated"
+        ));
+    }
+
+    #[test]
+    fn it_reads_the_interface_of_core_modules() {
+        let interface = interface(&contract(), context(MemoryBackend::default()), None).unwrap();
+        let names = interface
+            .functions
+            .iter()
+            .map(|function| function.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            ["echo", "store", "load", "fail", "spin", "saturated"]
+        );
+    }
+}
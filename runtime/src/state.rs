@@ -0,0 +1,322 @@
+//! # State
+//!
+//! Contracts read the blockchain state through a backend provided by the chain.
+//! Changes made during execution, including storage writes and emitted events,
+//! are collected in an overlay on top of the backend, which the chain applies
+//! once the outermost call succeeds.
+//! A nested call runs against a copy of the overlay that is only kept if the
+//! callee succeeds. Copying clones every change made so far in the transaction,
+//! so its cost grows with the writes and events before the call.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use ethereum_types::{H256, U256};
+use types::account::Account;
+use types::bytes::Bytes;
+use utils::crypto::hash;
+
+use crate::error::{Result, RuntimeError};
+
+/// Read access to the blockchain state
+pub trait Backend: Debug {
+    /// The code of a contract account, None if the account isn't a contract
+    fn code(&self, address: &Account) -> Result<Option<Bytes>>;
+
+    /// Code deployed on the chain, by the keccak256 hash of the code
+    fn code_by_hash(&self, code_hash: &H256) -> Result<Option<Bytes>>;
+
+    /// The nonce of an account, zero if the account doesn't exist
+    fn nonce(&self, address: &Account) -> Result<U256>;
+
+    /// The balance of an account, zero if the account doesn't exist
+    fn balance(&self, address: &Account) -> Result<U256>;
+
+    /// A value in the persistent storage of a contract, None if it was never set
+    fn storage(&self, address: &Account, key: &str) -> Result<Option<String>>;
+}
+
+/// An event emitted by a contract, which becomes a log in the transaction receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// The contract that emitted the event
+    pub address: Account,
+
+    /// The indexed values of the event, starting with the event name
+    pub topics: Vec<String>,
+
+    /// The values of the event that aren't indexed
+    pub data: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    backend: Arc<dyn Backend>,
+    balances: BTreeMap<Account, U256>,
+    nonces: BTreeMap<Account, U256>,
+    storage: BTreeMap<Account, BTreeMap<String, String>>,
+    created: BTreeMap<Account, Bytes>,
+    upgraded: BTreeMap<Account, Bytes>,
+    destroyed: BTreeSet<Account>,
+    events: Vec<Event>,
+}
+
+impl State {
+    pub fn new(backend: Arc<dyn Backend>) -> Self {
+        Self {
+            backend,
+            balances: BTreeMap::new(),
+            nonces: BTreeMap::new(),
+            storage: BTreeMap::new(),
+            created: BTreeMap::new(),
+            upgraded: BTreeMap::new(),
+            destroyed: BTreeSet::new(),
+            events: vec![],
+        }
+    }
+
+    pub fn code(&self, address: &Account) -> Result<Bytes> {
+        let not_a_contract = || RuntimeError::NotAContract(address.to_string());
+
+        if self.destroyed.contains(address) {
+            return Err(not_a_contract());
+        }
+
+        match self.upgraded.get(address).or(self.created.get(address)) {
+            Some(code) => Ok(code.to_owned()),
+            None => self.backend.code(address)?.ok_or_else(not_a_contract),
+        }
+    }
+
+    pub fn is_contract(&self, address: &Account) -> Result<bool> {
+        if self.destroyed.contains(address) {
+            return Ok(false);
+        }
+
+        Ok(self.created.contains_key(address) || self.backend.code(address)?.is_some())
+    }
+
+    /// Code deployed on the chain or created during execution, by its hash
+    pub fn code_by_hash(&self, code_hash: &H256) -> Result<Bytes> {
+        let created = self
+            .created
+            .values()
+            .chain(self.upgraded.values())
+            .find(|code| H256::from(hash(code)) == *code_hash);
+
+        match created {
+            Some(code) => Ok(code.to_owned()),
+            None => self
+                .backend
+                .code_by_hash(code_hash)?
+                .ok_or_else(|| RuntimeError::CodeNotFound(code_hash.to_string())),
+        }
+    }
+
+    pub fn nonce(&self, address: &Account) -> Result<U256> {
+        match self.nonces.get(address) {
+            Some(nonce) => Ok(*nonce),
+            None => self.backend.nonce(address),
+        }
+    }
+
+    /// Increment the nonce of an account, returning the new nonce
+    pub fn increment_nonce(&mut self, address: &Account) -> Result<U256> {
+        let nonce = self.nonce(address)? + 1;
+        self.nonces.insert(*address, nonce);
+
+        Ok(nonce)
+    }
+
+    /// Add a contract created during execution, once its constructor has run
+    pub fn create(&mut self, address: Account, code: Bytes) {
+        self.created.insert(address, code);
+    }
+
+    /// Replace the code of a contract, which later calls run
+    pub fn upgrade(&mut self, address: Account, code: Bytes) {
+        self.upgraded.insert(address, code);
+    }
+
+    /// Remove a contract along with its storage, sending its balance to the beneficiary.
+    /// A contract naming itself as the beneficiary burns its balance.
+    /// Returns the balance sent.
+    pub fn destroy(&mut self, address: &Account, beneficiary: &Account) -> Result<U256> {
+        let balance = self.balance(address)?;
+
+        match beneficiary == address {
+            true => drop(self.balances.insert(*address, U256::zero())),
+            false => self.transfer(address, beneficiary, balance)?,
+        }
+        self.storage.remove(address);
+        self.upgraded.remove(address);
+        self.destroyed.insert(*address);
+
+        Ok(balance)
+    }
+
+    pub fn balance(&self, address: &Account) -> Result<U256> {
+        match self.balances.get(address) {
+            Some(balance) => Ok(*balance),
+            None => self.backend.balance(address),
+        }
+    }
+
+    pub fn transfer(&mut self, from: &Account, to: &Account, value: U256) -> Result<()> {
+        if value.is_zero() {
+            return Ok(());
+        }
+
+        let from_balance = self.balance(from)?;
+        let to_balance = self.balance(to)?;
+
+        if from_balance < value {
+            return Err(RuntimeError::InsufficientBalance(from.to_string()));
+        }
+
+        // both balances were read before writing, so a self-transfer would mint the value
+        if from == to {
+            return Ok(());
+        }
+
+        self.balances.insert(*from, from_balance - value);
+        self.balances.insert(*to, to_balance + value);
+
+        Ok(())
+    }
+
+    /// Read a value from the storage of a contract
+    pub fn get(&self, address: &Account, key: &str) -> Result<Option<String>> {
+        if self.destroyed.contains(address) {
+            return Ok(None);
+        }
+
+        match self
+            .storage
+            .get(address)
+            .and_then(|storage| storage.get(key))
+        {
+            Some(value) => Ok(Some(value.to_owned())),
+            None => self.backend.storage(address, key),
+        }
+    }
+
+    /// Write a value to the storage of a contract
+    pub fn set(&mut self, address: &Account, key: String, value: String) {
+        self.storage.entry(*address).or_default().insert(key, value);
+    }
+
+    pub fn emit(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// The balances changed during execution
+    pub fn balances(&self) -> &BTreeMap<Account, U256> {
+        &self.balances
+    }
+
+    /// The nonces changed during execution
+    pub fn nonces(&self) -> &BTreeMap<Account, U256> {
+        &self.nonces
+    }
+
+    /// The contracts created during execution, with their code
+    pub fn created(&self) -> &BTreeMap<Account, Bytes> {
+        &self.created
+    }
+
+    /// The contracts whose code was replaced during execution, with their new code
+    pub fn upgraded(&self) -> &BTreeMap<Account, Bytes> {
+        &self.upgraded
+    }
+
+    /// The contracts destroyed during execution, whose storage is cleared
+    pub fn destroyed(&self) -> &BTreeSet<Account> {
+        &self.destroyed
+    }
+
+    /// The storage of each contract changed during execution
+    pub fn storage(&self) -> &BTreeMap<Account, BTreeMap<String, String>> {
+        &self.storage
+    }
+
+    /// The events emitted during execution, in order
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::MemoryBackend;
+
+    #[test]
+    fn it_transfers_within_the_overlay() {
+        let (from, to) = (Account::random(), Account::random());
+        let mut backend = MemoryBackend::default();
+        backend.balances.insert(from, U256::from(10));
+        let mut state = State::new(Arc::new(backend));
+
+        state.transfer(&from, &to, U256::from(4)).unwrap();
+
+        assert_eq!(state.balance(&from).unwrap(), U256::from(6));
+        assert_eq!(state.balance(&to).unwrap(), U256::from(4));
+        assert_eq!(state.balances().len(), 2);
+    }
+
+    #[test]
+    fn a_self_transfer_leaves_the_balance_unchanged() {
+        let account = Account::random();
+        let mut backend = MemoryBackend::default();
+        backend.balances.insert(account, U256::from(10));
+        let mut state = State::new(Arc::new(backend));
+
+        state.transfer(&account, &account, U256::from(4)).unwrap();
+
+        assert_eq!(state.balance(&account).unwrap(), U256::from(10));
+        assert!(matches!(
+            state.transfer(&account, &account, U256::from(11)),
+            Err(RuntimeError::InsufficientBalance(_))
+        ));
+    }
+
+    #[test]
+    fn it_errors_on_insufficient_balance() {
+        let (from, to) = (Account::random(), Account::random());
+        let mut state = State::new(Arc::new(MemoryBackend::default()));
+        let response = state.transfer(&from, &to, U256::from(1));
+
+        assert!(matches!(
+            response,
+            Err(RuntimeError::InsufficientBalance(_))
+        ));
+        assert!(state.balances().is_empty());
+    }
+
+    #[test]
+    fn it_writes_storage_within_the_overlay() {
+        let (contract_1, contract_2) = (Account::random(), Account::random());
+        let mut backend = MemoryBackend::default();
+        backend
+            .storage
+            .insert((contract_1, "name".into()), "Rust Coin".into());
+        let mut state = State::new(Arc::new(backend));
+
+        state.set(&contract_1, "symbol".into(), "RustCoin".into());
+
+        assert_eq!(
+            state.get(&contract_1, "name").unwrap(),
+            Some("Rust Coin".into())
+        );
+        assert_eq!(
+            state.get(&contract_1, "symbol").unwrap(),
+            Some("RustCoin".into())
+        );
+        assert_eq!(state.get(&contract_2, "symbol").unwrap(), None);
+        assert_eq!(state.storage().len(), 1);
+    }
+}
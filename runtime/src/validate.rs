@@ -0,0 +1,142 @@
+//! # Validation
+//!
+//! Contract code is checked once when it's deployed, so every node executes it
+//! the same way and invalid code never reaches the chain.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use wasmparser::{Parser, Payload, TypeRef, Validator, WasmFeatures};
+
+use crate::contract::{compile, ENGINE};
+use crate::error::{Result, RuntimeError};
+use crate::host::{FUNCTIONS, HOST};
+use crate::module::{self, is_component};
+
+/// The maximum size of contract code in bytes
+pub const MAX_CODE_SIZE: usize = 512 * 1024;
+
+/// Features that can behave differently from one machine to another are disabled.
+/// Floats are allowed since the engine canonicalizes NaNs.
+const FEATURES: WasmFeatures = WasmFeatures {
+    mutable_global: true,
+    saturating_float_to_int: true,
+    sign_extension: true,
+    reference_types: true,
+    multi_value: true,
+    bulk_memory: true,
+    simd: true,
+    relaxed_simd: false,
+    threads: false,
+    tail_call: false,
+    floats: true,
+    multi_memory: false,
+    exceptions: false,
+    memory64: false,
+    extended_const: false,
+    component_model: false,
+    function_references: false,
+    memory_control: false,
+};
+
+/// Check that contract code can be deployed: it's under the size limit, it's a valid
+/// module using only deterministic features, it only imports host functions,
+/// and it can be compiled, as a component if it's built with wit-bindgen.
+pub fn validate(bytes: &[u8]) -> Result<()> {
+    if bytes.len() > MAX_CODE_SIZE {
+        return Err(RuntimeError::CodeTooLarge(bytes.len(), MAX_CODE_SIZE));
+    }
+
+    Validator::new_with_features(FEATURES).validate_all(bytes)?;
+
+    // core modules import the memory based host functions
+    let component = is_component(bytes)?;
+    let (host, functions) = match component {
+        true => (HOST, FUNCTIONS),
+        false => (module::HOST, module::FUNCTIONS),
+    };
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::ImportSection(section) = payload? {
+            for import in section {
+                let import = import?;
+                let is_host_function = import.module == host
+                    && functions.contains(&import.name)
+                    && matches!(import.ty, TypeRef::Func(_));
+
+                if !is_host_function {
+                    return Err(RuntimeError::ForbiddenImport(format!(
+                        "{}::{}",
+                        import.module, import.name
+                    )));
+                }
+            }
+        }
+    }
+
+    match component {
+        true => drop(compile(&ENGINE, bytes)?),
+        false => drop(module::compile(&ENGINE, bytes)?),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module with a function importing `module::name`
+    fn module_importing(module: &str, name: &str) -> Vec<u8> {
+        let module = format!(
+            "(module (import \"{}\" \"{}\" (func)) (memory (export \"memory\") 1))",
+            module, name
+        );
+        wat::parse_str(module).unwrap()
+    }
+
+    #[test]
+    fn it_validates_contracts() {
+        let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
+        validate(bytes).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_malformed_code() {
+        assert!(matches!(
+            validate(&[0, 1]),
+            Err(RuntimeError::InvalidCode(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_code_over_the_size_limit() {
+        assert!(matches!(
+            validate(&vec![0; MAX_CODE_SIZE + 1]),
+            Err(RuntimeError::CodeTooLarge(_, MAX_CODE_SIZE))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_imports_outside_the_host_interface() {
+        let wasi = module_importing("wasi_snapshot_preview1", "fd_write");
+        let unknown = module_importing(HOST, "clock");
+        let unknown_core = module_importing(module::HOST, "clock");
+
+        for bytes in [wasi, unknown, unknown_core] {
+            assert!(matches!(
+                validate(&bytes),
+                Err(RuntimeError::ForbiddenImport(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_rejects_threads() {
+        let bytes = wat::parse_str("(module (memory 1 1 shared))").unwrap();
+
+        assert!(matches!(
+            validate(&bytes),
+            Err(RuntimeError::InvalidCode(_))
+        ));
+    }
+}
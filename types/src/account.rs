@@ -9,26 +9,28 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::{Address, U256};
+use ethereum_types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 
-use crate::bytes::Bytes;
-
 pub type Account = Address;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct AccountData {
     pub nonce: U256,
     pub balance: U256,
-    pub code_hash: Option<Bytes>,
+    /// The keccak256 hash of the contract code, the code itself lives in the code store
+    pub code_hash: Option<H256>,
+    /// The root of the contract's storage trie, None until the contract writes to storage
+    pub storage_root: Option<H256>,
 }
 
 impl AccountData {
-    pub fn new(code_hash: Option<Bytes>) -> Self {
+    pub fn new(code_hash: Option<H256>) -> Self {
         Self {
             nonce: U256::zero(),
             balance: U256::zero(),
             code_hash,
+            storage_root: None,
         }
     }
 
@@ -7,27 +7,36 @@
 //! A sample block from the chain:
 //!
 //! {
-//!   "hash": "0x7a2b18ecb9565eaa511601130d8108886b5d9cb14c6f9662c1e661bbfc73523e",
 //!   "number": "0x0",
+//!   "hash": "0x1213d8c03a9c04a082bbc1a50488b1c39ddf029fc549a46f3f21f0bdf332b10b",
 //!   "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
-//!   "stateRoot": "0xd5b2d8fdfe99430dcdaa397d252d0cae3a1457c414999fbba318ba90ec0ed56b",
+//!   "author": "0x0000000000000000000000000000000000000000",
+//!   "timestamp": "0x0",
+//!   "gasLimit": "0x0",
+//!   "gasUsed": "0x0",
+//!   "difficulty": "0x0",
+//!   "extraData": "0x",
+//!   "size": "0x1ae",
 //!   "transactions": [],
 //!   "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+//!   "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+//!   "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000000"
 //! }
 //!
 //! see https://ethereum.org/en/developers/docs/blocks/
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::{H256, U64};
+use ethereum_types::{Address, H256, U256, U64};
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::ops::Deref;
 use utils::crypto::hash;
 
+use crate::bytes::Bytes;
 use crate::error::{Result, TypeError};
 use crate::helpers::hex_to_u64;
-use crate::transaction::Transaction;
+use crate::transaction::{Transaction, TransactionReceipt};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename = "block_number")]
@@ -62,16 +71,44 @@ impl TryFrom<&str> for BlockNumber {
     }
 }
 
-// TODO(ddimaria): add in `author` once we're injecting keys into nodes
+/// The fields of a block set by the node producing it
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct BlockMetadata {
+    /// The address of the node that produced the block, which receives its fees
+    pub author: Address,
+    /// Seconds since the unix epoch, the time contracts see while the block is built
+    pub timestamp: U64,
+    /// The most gas the block's transactions can use
+    pub gas_limit: U64,
+    /// The gas used by the block's transactions
+    pub gas_used: U64,
+    /// Arbitrary data from the block producer, at most 32 bytes
+    #[serde(with = "crate::bytes::hex")]
+    pub extra_data: Bytes,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all(serialize = "snake_case", deserialize = "snake_case"))]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct Block {
     pub number: U64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hash: Option<H256>,
     pub parent_hash: H256,
+    pub author: Address,
+    pub timestamp: U64,
+    pub gas_limit: U64,
+    pub gas_used: U64,
+    /// Always zero, as blocks are produced without proof of work
+    pub difficulty: U256,
+    #[serde(with = "crate::bytes::hex")]
+    pub extra_data: Bytes,
+    /// The number of bytes of the encoded block, leaving out its hash
+    pub size: U64,
     pub transactions: Vec<Transaction>,
     pub transactions_root: H256,
+    /// The root of the trie of the block's receipts, keyed by transaction hash
+    pub receipts_root: H256,
     pub state_root: H256,
 }
 
@@ -80,31 +117,138 @@ impl Block {
         number: U64,
         parent_hash: H256,
         transactions: Vec<Transaction>,
+        receipts_root: H256,
         state_root: H256,
+        metadata: BlockMetadata,
     ) -> Result<Block> {
         let transactions_root = Transaction::root_hash(&transactions)?;
         let mut block = Block {
             number,
             hash: None,
             parent_hash,
+            author: metadata.author,
+            timestamp: metadata.timestamp,
+            gas_limit: metadata.gas_limit,
+            gas_used: metadata.gas_used,
+            difficulty: U256::zero(),
+            extra_data: metadata.extra_data,
+            size: U64::zero(),
             transactions,
             transactions_root,
+            receipts_root,
             state_root,
         };
 
-        let serialized = bincode::serialize(&block)?;
-        let hash: H256 = hash(&serialized).into();
-        block.hash = Some(hash);
+        // the size is encoded as a hex string, which can grow the block when set,
+        // so it's set until it includes itself
+        loop {
+            let size = bincode::serialized_size(&block)?.into();
+
+            if size == block.size {
+                break;
+            }
+
+            block.size = size;
+        }
+        block.hash = Some(block.compute_hash()?);
 
         Ok(block)
     }
 
+    /// Hash the block's contents, leaving out its hash
+    pub fn compute_hash(&self) -> Result<H256> {
+        let block = Block {
+            hash: None,
+            ..self.clone()
+        };
+        let serialized = bincode::serialize(&block)?;
+
+        Ok(hash(&serialized).into())
+    }
+
     pub fn block_hash(&self) -> Result<H256> {
         self.hash.ok_or(TypeError::MissingBlockHash)
     }
 
     // TODO(ddimaria): add initial accounts and seed with coin (requires recalculation of the state_root)
     pub fn genesis() -> Result<Self> {
-        Self::new(U64::zero(), H256::zero(), vec![], H256::zero())
+        let receipts_root = TransactionReceipt::root_hash(&[])?;
+
+        Self::new(
+            U64::zero(),
+            H256::zero(),
+            vec![],
+            receipts_root,
+            H256::zero(),
+            BlockMetadata::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_block() -> Block {
+        let metadata = BlockMetadata {
+            author: Address::random(),
+            timestamp: U64::from(1_700_000_000),
+            gas_limit: U64::from(30_000_000),
+            gas_used: U64::from(21_000),
+            extra_data: "rust-blockchain".into(),
+        };
+
+        Block::new(
+            U64::one(),
+            H256::random(),
+            vec![],
+            H256::random(),
+            H256::random(),
+            metadata,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn the_size_includes_itself() {
+        let block = new_block();
+        let encoded = Block {
+            hash: None,
+            ..block.clone()
+        };
+
+        assert_eq!(
+            block.size,
+            bincode::serialized_size(&encoded).unwrap().into()
+        );
+    }
+
+    #[test]
+    fn the_hash_covers_the_header() {
+        let block = new_block();
+        let hash = block.block_hash().unwrap();
+        assert_eq!(block.compute_hash().unwrap(), hash);
+
+        let mut tampered = block.clone();
+        tampered.author = Address::random();
+        assert_ne!(tampered.compute_hash().unwrap(), hash);
+
+        let mut tampered = block;
+        tampered.extra_data = "forged".into();
+        assert_ne!(tampered.compute_hash().unwrap(), hash);
+    }
+
+    #[test]
+    fn extra_data_is_serialized_as_hex() {
+        let block = new_block();
+        let serialized = serde_json::to_string(&block).unwrap();
+        let deserialized: Block = serde_json::from_str(&serialized).unwrap();
+
+        assert!(serialized.contains(r#""extraData":"0x727573742d626c6f636b636861696e""#));
+        assert_eq!(deserialized.extra_data, block.extra_data);
+        assert_eq!(
+            serde_json::to_string(&BlockMetadata::default()).unwrap(),
+            r#"{"author":"0x0000000000000000000000000000000000000000","timestamp":"0x0","gasLimit":"0x0","gasUsed":"0x0","extraData":"0x"}"#
+        );
     }
 }
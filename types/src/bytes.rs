@@ -7,3 +7,23 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 pub use bytes::Bytes;
+
+/// Serialize bytes as a 0x prefixed hex string, e.g. "0x0a0b", for use with
+/// `#[serde(with = "crate::bytes::hex")]`
+pub mod hex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Bytes;
+
+    pub fn serialize<S: Serializer>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", ::hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let value = value.strip_prefix("0x").unwrap_or(&value);
+
+        ::hex::decode(value).map(Into::into).map_err(Error::custom)
+    }
+}
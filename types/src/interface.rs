@@ -0,0 +1,127 @@
+//! # Contract Interfaces
+//!
+//! The functions and events of a contract, stored alongside its code so calls can
+//! be encoded and logs decoded without the contract's source.
+//! Types are written as WIT types, e.g. `u64`, `list<string>` or `result<_, string>`.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ContractInterface {
+    pub functions: Vec<FunctionSignature>,
+    pub events: Vec<EventDefinition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub params: Vec<Param>,
+    /// The type of the returned value, None for functions returning nothing
+    pub results: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// An event logged by a contract.
+/// The name is always the first topic, followed by the `topics` fields.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventDefinition {
+    pub name: String,
+    pub topics: Vec<Param>,
+    pub data: Vec<Param>,
+}
+
+impl ContractInterface {
+    pub fn new(functions: Vec<FunctionSignature>, events: Vec<EventDefinition>) -> Self {
+        Self { functions, events }
+    }
+
+    pub fn function(&self, name: &str) -> Option<&FunctionSignature> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+}
+
+impl FunctionSignature {
+    pub fn new(name: &str, params: Vec<Param>, results: Option<&str>) -> Self {
+        Self {
+            name: name.into(),
+            params,
+            results: results.map(Into::into),
+        }
+    }
+
+    /// Whether the types of the params and results are the same, ignoring names
+    /// and whitespace
+    pub fn matches(&self, other: &FunctionSignature) -> bool {
+        let types = |function: &FunctionSignature| {
+            function
+                .params
+                .iter()
+                .map(|param| normalize(&param.ty))
+                .collect::<Vec<_>>()
+        };
+
+        self.name == other.name
+            && types(self) == types(other)
+            && self.results.as_deref().map(normalize) == other.results.as_deref().map(normalize)
+    }
+}
+
+impl Param {
+    pub fn new(name: &str, ty: &str) -> Self {
+        Self {
+            name: name.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+impl EventDefinition {
+    pub fn new(name: &str, topics: Vec<Param>, data: Vec<Param>) -> Self {
+        Self {
+            name: name.into(),
+            topics,
+            data,
+        }
+    }
+}
+
+fn normalize(ty: &str) -> String {
+    ty.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_function_signatures_by_type() {
+        let declared = FunctionSignature::new(
+            "transfer",
+            vec![Param::new("to", "string"), Param::new("amount", "u64")],
+            Some("result<_, string>"),
+        );
+        let exported = FunctionSignature::new(
+            "transfer",
+            vec![Param::new("p0", "string"), Param::new("p1", "u64")],
+            Some("result<_,string>"),
+        );
+        let different = FunctionSignature::new("transfer", vec![], Some("result<_, string>"));
+
+        assert!(declared.matches(&exported));
+        assert!(!declared.matches(&different));
+    }
+
+    #[test]
+    fn it_serializes_param_types() {
+        let param = serde_json::to_string(&Param::new("amount", "u64")).unwrap();
+        assert_eq!(param, r#"{"name":"amount","type":"u64"}"#);
+    }
+}
@@ -9,4 +9,8 @@ pub mod block;
 pub mod bytes;
 pub mod error;
 pub mod helpers;
+pub mod interface;
+pub mod proof;
+pub mod storage;
+pub mod trace;
 pub mod transaction;
@@ -0,0 +1,189 @@
+//! # Proofs
+//!
+//! Merkle proofs of an account and its storage against the state root of a block,
+//! so light clients can check balances and storage without trusting the node
+//! that served them.
+//!
+//! The account proof holds the nodes of the state trie on the path to the account,
+//! and each storage proof holds the nodes of the contract's storage trie on the
+//! path to the key.
+//! A proof for a missing account or key proves its absence.
+//!
+//! see https://eips.ethereum.org/EIPS/eip-1186
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+
+use eth_trie::{EthTrie, MemoryDB, Trie};
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountData;
+use crate::block::Block;
+use crate::bytes::Bytes;
+use crate::error::{Result, TypeError};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct AccountProof {
+    pub address: Address,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: Option<H256>,
+    /// The root of the contract's storage trie, which the storage proofs are checked against
+    pub storage_hash: Option<H256>,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    pub key: String,
+    /// None if the key isn't set
+    pub value: Option<String>,
+    pub proof: Vec<Bytes>,
+}
+
+impl AccountProof {
+    /// The account data the proof claims is in the state trie
+    pub fn account_data(&self) -> AccountData {
+        AccountData {
+            nonce: self.nonce,
+            balance: self.balance,
+            code_hash: self.code_hash,
+            storage_root: self.storage_hash,
+        }
+    }
+
+    /// Check the proof against a block header.
+    /// The block's hash must match its contents, and the account and every
+    /// storage value must be proven by the block's state root.
+    pub fn verify(&self, block: &Block) -> Result<()> {
+        if Some(block.compute_hash()?) != block.hash {
+            return Err(invalid("the block hash doesn't match the block"));
+        }
+
+        self.verify_state_root(block.state_root)
+    }
+
+    /// Check the proof against a state root
+    pub fn verify_state_root(&self, state_root: H256) -> Result<()> {
+        let account = verify_proof(state_root, self.address.as_bytes(), &self.account_proof)?;
+        let account_data = match account {
+            Some(account) => bincode::deserialize::<AccountData>(&account)?,
+            None => AccountData::new(None),
+        };
+
+        if account_data != self.account_data() {
+            return Err(invalid(&format!("account {:?}", self.address)));
+        }
+
+        for storage_proof in &self.storage_proof {
+            let value = match self.storage_hash {
+                Some(storage_hash) => verify_proof(
+                    storage_hash,
+                    storage_proof.key.as_bytes(),
+                    &storage_proof.proof,
+                )?,
+                None => None,
+            };
+
+            if value.as_deref() != storage_proof.value.as_ref().map(String::as_bytes) {
+                return Err(invalid(&format!("storage key {}", storage_proof.key)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StorageProof {
+    pub fn new(key: &str, value: Option<String>, proof: Vec<Bytes>) -> Self {
+        Self {
+            key: key.into(),
+            value,
+            proof,
+        }
+    }
+}
+
+/// The value the proof proves is at the key of the trie with the root,
+/// None if it proves the key is absent
+fn verify_proof(root: H256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>> {
+    let trie = EthTrie::new(Arc::new(MemoryDB::new(true)));
+    let proof = proof.iter().map(|node| node.to_vec()).collect();
+
+    trie.verify_proof(root.0.into(), key, proof)
+        .map_err(|e| invalid(&e.to_string()))
+}
+
+fn invalid(message: &str) -> TypeError {
+    TypeError::InvalidProof(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prove(accounts: &[(Address, AccountData)], address: Address) -> (H256, AccountProof) {
+        let mut trie = EthTrie::new(Arc::new(MemoryDB::new(true)));
+
+        for (address, account_data) in accounts {
+            let account_data = bincode::serialize(account_data).unwrap();
+            trie.insert(address.as_bytes(), &account_data).unwrap();
+        }
+
+        let root = H256::from_slice(trie.root_hash().unwrap().as_bytes());
+        let account_proof = trie.get_proof(address.as_bytes()).unwrap();
+        let account_data = accounts
+            .iter()
+            .find(|(key, _)| *key == address)
+            .map_or_else(|| AccountData::new(None), |(_, data)| data.clone());
+        let proof = AccountProof {
+            address,
+            balance: account_data.balance,
+            nonce: account_data.nonce,
+            code_hash: account_data.code_hash,
+            storage_hash: account_data.storage_root,
+            account_proof: account_proof.into_iter().map(Bytes::from).collect(),
+            storage_proof: vec![],
+        };
+
+        (root, proof)
+    }
+
+    fn accounts() -> Vec<(Address, AccountData)> {
+        (1..=3)
+            .map(|balance| {
+                let mut account_data = AccountData::new(None);
+                account_data.balance = U256::from(balance);
+                (Address::random(), account_data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_verifies_an_account() {
+        let accounts = accounts();
+        let (root, proof) = prove(&accounts, accounts[1].0);
+        assert!(proof.verify_state_root(root).is_ok());
+
+        let mut tampered = proof;
+        tampered.balance = U256::from(1_000);
+        assert!(matches!(
+            tampered.verify_state_root(root),
+            Err(TypeError::InvalidProof(_))
+        ));
+    }
+
+    #[test]
+    fn it_verifies_a_missing_account() {
+        let (root, proof) = prove(&accounts(), Address::random());
+        assert!(proof.verify_state_root(root).is_ok());
+
+        let mut tampered = proof;
+        tampered.balance = U256::from(1);
+        assert!(tampered.verify_state_root(root).is_err());
+    }
+}
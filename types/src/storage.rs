@@ -0,0 +1,22 @@
+//! # Storage
+//!
+//! Views of the persistent storage of contracts.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use serde::{Deserialize, Serialize};
+
+/// A page of the keys a contract has written to its storage, in key order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct StorageKeys {
+    pub keys: Vec<String>,
+    /// The key the next page starts at, None on the last page
+    pub next_key: Option<String>,
+}
+
+impl StorageKeys {
+    pub fn new(keys: Vec<String>, next_key: Option<String>) -> Self {
+        Self { keys, next_key }
+    }
+}
@@ -0,0 +1,46 @@
+//! # Traces
+//!
+//! What happened while a transaction executed, beyond what's in its receipt.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use ethereum_types::{H160, H256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct TransactionTrace {
+    pub transaction_hash: H256,
+    /// The messages printed by contracts, in the order they were printed.
+    /// Only nodes running in debug mode keep them.
+    pub debug: Vec<DebugMessage>,
+    /// The contracts created by other contracts during the transaction
+    pub created: Vec<H160>,
+}
+
+/// A message printed by a contract through the `debug-print` host function
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugMessage {
+    /// The contract that printed the message
+    pub address: H160,
+    pub message: String,
+}
+
+impl TransactionTrace {
+    pub fn new(transaction_hash: H256, debug: Vec<DebugMessage>, created: Vec<H160>) -> Self {
+        Self {
+            transaction_hash,
+            debug,
+            created,
+        }
+    }
+}
+
+impl DebugMessage {
+    pub fn new(address: H160, message: &str) -> Self {
+        Self {
+            address,
+            message: message.into(),
+        }
+    }
+}
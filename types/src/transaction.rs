@@ -23,6 +23,7 @@ use crate::account::Account;
 use crate::block::BlockNumber;
 use crate::bytes::Bytes;
 use crate::error::{Result, TypeError};
+use crate::interface::ContractInterface;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
@@ -44,8 +45,45 @@ pub struct Transaction {
 ///   * Execution of a contract: a transaction that interacts with a deployed smart contract. In this case, 'to' address is the smart contract address.
 pub enum TransactionKind {
     Regular(Address, Address, U256),
-    ContractDeployment(Address, Bytes),
-    ContractExecution(Address, Address, Bytes),
+    ContractDeployment(Address, DeploymentData),
+    ContractExecution(Address, Address, U256, Bytes),
+}
+
+/// The data field of a contract deployment transaction.
+/// The args are passed to the contract's constructor, which runs once at deployment.
+/// Without a salt, the contract address is derived from the sender and the nonce (CREATE).
+/// With a salt, the contract address is derived from the sender, salt and code (CREATE2).
+/// A declared interface is checked against the contract's exports and stored with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeploymentData {
+    pub code: Bytes,
+    pub args: Vec<String>,
+    pub salt: Option<H256>,
+    pub interface: Option<ContractInterface>,
+}
+
+impl DeploymentData {
+    pub fn new(code: Bytes, args: Vec<String>, salt: Option<H256>) -> Self {
+        Self {
+            code,
+            args,
+            salt,
+            interface: None,
+        }
+    }
+
+    pub fn with_interface(mut self, interface: ContractInterface) -> Self {
+        self.interface = Some(interface);
+        self
+    }
+
+    pub fn encode(&self) -> Result<Bytes> {
+        Ok(bincode::serialize(&self)?.into())
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(data)?)
+    }
 }
 
 impl Transaction {
@@ -87,8 +125,13 @@ impl Transaction {
     pub fn kind(self) -> Result<TransactionKind> {
         match (self.from, self.to, self.data) {
             (from, Some(to), None) => Ok(TransactionKind::Regular(from, to, self.value)),
-            (from, None, Some(data)) => Ok(TransactionKind::ContractDeployment(from, data)),
-            (from, Some(to), Some(data)) => Ok(TransactionKind::ContractExecution(from, to, data)),
+            (from, None, Some(data)) => Ok(TransactionKind::ContractDeployment(
+                from,
+                DeploymentData::decode(&data)?,
+            )),
+            (from, Some(to), Some(data)) => Ok(TransactionKind::ContractExecution(
+                from, to, self.value, data,
+            )),
             _ => Err(TypeError::InvalidTransaction("kind".into())),
         }
     }
@@ -238,7 +281,12 @@ impl TryInto<Transaction> for TransactionRequest {
     fn try_into(self) -> Result<Transaction> {
         let value = self.value.unwrap_or(U256::zero());
         let from = self.from.unwrap_or(H160::zero());
-        Transaction::new(from, self.to, value, self.nonce, self.data)
+        let mut transaction = Transaction::new(from, self.to, value, self.nonce, self.data)?;
+        transaction.gas = self.gas;
+        transaction.gas_price = self.gas_price;
+        transaction.hash()?;
+
+        Ok(transaction)
     }
 }
 
@@ -249,21 +297,79 @@ pub struct TransactionReceipt {
     pub block_number: Option<BlockNumber>,
     pub contract_address: Option<H160>,
     pub transaction_hash: H256,
+    /// 1 if the transaction succeeded, 0 if contract execution failed
+    pub status: U64,
+    /// The gas used by contracts, failed contract executions use all of their gas
+    pub gas_used: U64,
+    /// The events emitted by contracts during the transaction
+    pub logs: Vec<Log>,
+    /// Why contract execution failed, e.g. a revert or invalid contract code
+    pub error: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl TransactionReceipt {
+    /// The receipt without the block it's in, which is what the receipts trie holds,
+    /// as the block hash commits to the receipts root
+    pub fn without_block(&self) -> Self {
+        let mut receipt = self.clone();
+        receipt.block_hash = None;
+        receipt.block_number = None;
+
+        for log in receipt.logs.iter_mut() {
+            log.block_hash = None;
+            log.block_number = None;
+        }
+
+        receipt
+    }
+
+    fn to_trie(receipts: &[TransactionReceipt]) -> Result<EthTrie<MemoryDB>> {
+        let memdb = Arc::new(MemoryDB::new(true));
+        let mut trie = EthTrie::new(memdb);
+
+        receipts.iter().try_for_each(|receipt| {
+            trie.insert(
+                receipt.transaction_hash.as_bytes(),
+                bincode::serialize(&receipt.without_block())?.as_slice(),
+            )
+            .map_err(|e| TypeError::TrieError(format!("Error inserting receipts: {}", e)))
+        })?;
+
+        Ok(trie)
+    }
+
+    pub fn root_hash(receipts: &[TransactionReceipt]) -> Result<H256> {
+        let mut trie = Self::to_trie(receipts)?;
+        let root_hash = trie
+            .root_hash()
+            .map_err(|e| TypeError::TrieError(format!("Error calculating root hash: {}", e)))?;
+
+        Ok(H256::from_slice(root_hash.as_bytes()))
+    }
+}
+
+/// Logs keep their `snake_case` field names on the wire, the aliases let
+/// receipts returned by the node be read back
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all(serialize = "snake_case", deserialize = "camelCase"))]
 pub struct Log {
     pub address: H160,
+    #[serde(alias = "block_hash")]
     pub block_hash: Option<H256>,
+    #[serde(alias = "block_number")]
     pub block_number: Option<U64>,
     pub data: Bytes,
+    #[serde(alias = "log_index")]
     pub log_index: Option<U256>,
+    #[serde(alias = "log_type")]
     pub log_type: Option<String>,
     pub removed: Option<bool>,
     pub topics: Vec<H256>,
+    #[serde(alias = "transaction_hash")]
     pub transaction_hash: Option<H256>,
+    #[serde(alias = "transaction_index")]
     pub transaction_index: Option<String>,
+    #[serde(alias = "transaction_log_index")]
     pub transaction_log_index: Option<U256>,
 }
 
@@ -307,10 +413,56 @@ mod tests {
     fn root_hash() {
         let transaction_1 = new_transaction();
         let transaction_2 = new_transaction();
-        let root = Transaction::root_hash(&vec![transaction_1, transaction_2]).unwrap();
+        let root = Transaction::root_hash(&[transaction_1, transaction_2]).unwrap();
         let expected =
             H256::from_str("0xa3b8c35bab6501806ed681220afe26a0d46774a6aa56d044b0f6aef0f3f0d682")
                 .unwrap();
         assert_eq!(root, expected);
     }
+
+    #[test]
+    fn receipts_root_hash_leaves_out_the_block() {
+        let receipt = TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: None,
+            transaction_hash: new_transaction().transaction_hash().unwrap(),
+            status: U64::one(),
+            gas_used: U64::zero(),
+            logs: vec![],
+            error: None,
+        };
+        let root = TransactionReceipt::root_hash(std::slice::from_ref(&receipt)).unwrap();
+
+        let mut in_block = receipt.clone();
+        in_block.block_hash = Some(H256::random());
+        in_block.block_number = Some(BlockNumber(U64::one()));
+        assert_eq!(TransactionReceipt::root_hash(&[in_block]).unwrap(), root);
+
+        let mut failed = receipt;
+        failed.status = U64::zero();
+        assert_ne!(TransactionReceipt::root_hash(&[failed]).unwrap(), root);
+    }
+
+    #[test]
+    fn logs_serialize_snake_case_and_read_back() {
+        let log = Log {
+            address: H160::random(),
+            block_hash: Some(H256::random()),
+            block_number: Some(U64::one()),
+            data: Bytes::from(vec![1, 2, 3]),
+            log_index: Some(U256::zero()),
+            log_type: None,
+            removed: Some(false),
+            topics: vec![H256::random()],
+            transaction_hash: Some(H256::random()),
+            transaction_index: None,
+            transaction_log_index: Some(U256::zero()),
+        };
+        let serialized = serde_json::to_string(&log).unwrap();
+        assert!(serialized.contains("\"block_hash\""));
+
+        let deserialized: Log = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, log);
+    }
 }
@@ -124,6 +124,51 @@ pub fn private_key_address(key: &SecretKey) -> H160 {
     public_key_address(&public_key)
 }
 
+/// Derive the address of a contract deployed with CREATE.
+/// The address is the last 20 bytes of the hash of the RLP encoded sender and nonce.
+///
+/// ```rust
+/// use ethereum_types::{H160, U256};
+/// use std::str::FromStr;
+/// use utils::crypto::contract_address;
+///
+/// let sender = H160::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+/// let address = contract_address(&sender, U256::zero());
+/// assert_eq!(address, H160::from_str("0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap());
+/// ```
+pub fn contract_address(sender: &Address, nonce: U256) -> H160 {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(sender);
+    stream.append(&nonce);
+
+    let hash = hash(&stream.out());
+    Address::from_slice(&hash[12..])
+}
+
+/// Derive the address of a contract deployed with CREATE2.
+/// The address is the last 20 bytes of the hash of `0xff ++ sender ++ salt ++ hash(init_code)`,
+/// so it only depends on the sender, the salt and the code (not the nonce).
+///
+/// ```rust
+/// use ethereum_types::{H160, H256};
+/// use std::str::FromStr;
+/// use utils::crypto::contract_address_with_salt;
+///
+/// let sender = H160::from_str("0xdeadbeef00000000000000000000000000000000").unwrap();
+/// let address = contract_address_with_salt(&sender, &H256::zero(), &[0]);
+/// assert_eq!(address, H160::from_str("0xb928f69bb1d91cd65274e3c79d8986362984fda3").unwrap());
+/// ```
+pub fn contract_address_with_salt(sender: &Address, salt: &H256, init_code: &[u8]) -> H160 {
+    let mut bytes = Vec::with_capacity(85);
+    bytes.push(0xff);
+    bytes.extend_from_slice(sender.as_bytes());
+    bytes.extend_from_slice(salt.as_bytes());
+    bytes.extend_from_slice(&hash(init_code));
+
+    let hash = hash(&bytes);
+    Address::from_slice(&hash[12..])
+}
+
 /// Create a hash
 ///
 /// ```rust
@@ -271,6 +316,7 @@ pub fn rlp_encode<T: Encodable>(items: Vec<T>, signature: Option<&Signature>) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
 
     #[test]
     fn private_and_public_key_addresses_match() {
@@ -325,6 +371,38 @@ mod tests {
         assert!(verified);
     }
 
+    #[test]
+    fn it_derives_contract_addresses() {
+        let sender = H160::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        let expected = [
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91",
+        ];
+
+        expected.iter().enumerate().for_each(|(nonce, address)| {
+            let derived = contract_address(&sender, U256::from(nonce));
+            assert_eq!(derived, H160::from_str(address).unwrap());
+        });
+    }
+
+    #[test]
+    fn it_derives_contract_addresses_with_salt() {
+        let sender = H160::zero();
+        let derived = contract_address_with_salt(&sender, &H256::zero(), &[0]);
+        let expected = H160::from_str("0x4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38").unwrap();
+        assert_eq!(derived, expected);
+
+        let sender = H160::from_str("0x00000000000000000000000000000000deadbeef").unwrap();
+        let salt =
+            H256::from_str("0x00000000000000000000000000000000000000000000000000000000cafebabe")
+                .unwrap();
+        let init_code = [0xde, 0xad, 0xbe, 0xef];
+        let derived = contract_address_with_salt(&sender, &salt, &init_code);
+        let expected = H160::from_str("0x60f3f640a8508fc6a86d45df051962668e1e8ac7").unwrap();
+        assert_eq!(derived, expected);
+    }
+
     #[test]
     fn it_rlp_encodes() {
         let items = vec!["a", "b", "c", "d", "e", "f"];
@@ -11,6 +11,7 @@ use jsonrpsee::rpc_params;
 use types::account::Account;
 use types::block::BlockNumber;
 use types::helpers::to_hex;
+use types::proof::AccountProof;
 use types::transaction::{SignedTransaction, Transaction};
 use utils::crypto::SecretKey;
 
@@ -62,6 +63,34 @@ impl Web3 {
         Ok(balance)
     }
 
+    /// Retrieve Merkle proofs of an account and the given keys of its storage
+    /// against the state root of a block.
+    /// Verify the proof against the block to trust the values without trusting the node.
+    ///
+    /// See https://eips.ethereum.org/EIPS/eip-1186
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let block_number = web3.get_block_number().await.unwrap();
+    /// let proof = web3.get_proof(account, &[], Some(block_number)).await.unwrap();
+    /// let block = web3.get_block(*block_number).await.unwrap();
+    /// assert!(proof.verify(&block).is_ok());
+    /// ```
+    pub async fn get_proof(
+        &self,
+        address: Account,
+        keys: &[&str],
+        block_number: Option<BlockNumber>,
+    ) -> Result<AccountProof> {
+        let block_number = Web3::get_hex_blocknumber(block_number);
+        let params = rpc_params![to_hex(address), keys, block_number];
+        let response = self.send_rpc("eth_getProof", params).await?;
+        let proof: AccountProof = serde_json::from_value(response)?;
+
+        Ok(proof)
+    }
+
     pub fn sign_transaction(
         &self,
         transaction: Transaction,
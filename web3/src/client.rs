@@ -0,0 +1,449 @@
+//! # Contract Clients
+//!
+//! Call contract functions through typed values instead of typed pairs.
+//!
+//! `ContractClient` is what the structs generated by `web3::contract_client!` wrap:
+//! view functions go through `eth_call`, and state changing functions are signed
+//! and sent as raw transactions, returning a `PendingTransaction`.
+//! Contracts deployed through a generated client declare the interface of their WIT world.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::time::Duration;
+
+pub use ethereum_types::{Address, H256, U256};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+pub use types::interface::{ContractInterface, EventDefinition, FunctionSignature, Param};
+use types::transaction::{DeploymentData, Transaction, TransactionReceipt};
+use utils::crypto::private_key_address;
+pub use utils::crypto::SecretKey;
+
+use crate::error::{Result, Web3Error};
+use crate::Web3;
+
+/// How often a pending transaction polls for its receipt
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many times a pending transaction polls for its receipt before giving up
+const RECEIPT_POLL_ATTEMPTS: usize = 40;
+
+const GAS: u64 = 1_000_000;
+
+/// A contract on the chain, signing transactions with a secret key
+pub struct ContractClient<'a> {
+    web3: &'a Web3,
+    address: Address,
+    key: SecretKey,
+    value: U256,
+    nonce: Mutex<Option<U256>>,
+}
+
+impl<'a> ContractClient<'a> {
+    pub fn new(web3: &'a Web3, address: Address, key: SecretKey) -> Self {
+        Self {
+            web3,
+            address,
+            key,
+            value: U256::zero(),
+            nonce: Mutex::new(None),
+        }
+    }
+
+    /// Send a value along with every transaction
+    pub fn with_value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// The address of the contract
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The address of the account signing transactions
+    pub fn from(&self) -> Address {
+        private_key_address(&self.key)
+    }
+
+    /// Deploy a contract, passing the typed pairs to its constructor.
+    /// A declared interface is checked against the contract's exports.
+    /// The address of the contract is in the receipt.
+    pub async fn deploy(
+        web3: &'a Web3,
+        key: SecretKey,
+        code: &[u8],
+        args: Vec<String>,
+        interface: Option<ContractInterface>,
+    ) -> Result<PendingTransaction<'a>> {
+        let mut deployment = DeploymentData::new(code.to_vec().into(), args, None);
+
+        if let Some(interface) = interface {
+            deployment = deployment.with_interface(interface);
+        }
+
+        let data = deployment
+            .encode()
+            .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+        let from = private_key_address(&key);
+        let nonce = web3.get_transaction_count(from).await? + 1;
+        let transaction = Transaction::new(from, None, U256::zero(), Some(nonce), Some(data))
+            .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+
+        send_signed(web3, transaction, key).await
+    }
+
+    /// Call a view function against the latest state, returning its typed pairs
+    pub async fn call(&self, function: &str, params: Vec<String>) -> Result<Vec<String>> {
+        let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+
+        self.web3
+            .call(self.from(), self.address, function, &params)
+            .await
+    }
+
+    /// Sign and send a transaction calling a function.
+    /// Nonces are tracked locally after the first transaction, so several
+    /// transactions can be sent within a block.
+    pub async fn send(
+        &self,
+        function: &str,
+        params: Vec<String>,
+    ) -> Result<PendingTransaction<'a>> {
+        let data = bincode::serialize(&(function, params))
+            .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+        let from = self.from();
+        let mut nonce = self.nonce.lock().await;
+        let next = match *nonce {
+            Some(nonce) => nonce + 1,
+            None => self.web3.get_transaction_count(from).await? + 1,
+        };
+        let transaction = Transaction::new(
+            from,
+            Some(self.address),
+            self.value,
+            Some(next),
+            Some(data.into()),
+        )
+        .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+        let pending = send_signed(self.web3, transaction, self.key).await?;
+
+        *nonce = Some(next);
+
+        Ok(pending)
+    }
+}
+
+async fn send_signed<'a>(
+    web3: &'a Web3,
+    mut transaction: Transaction,
+    key: SecretKey,
+) -> Result<PendingTransaction<'a>> {
+    transaction.gas = U256::from(GAS);
+
+    let signed_transaction = web3.sign_transaction(transaction, key)?;
+    let encoded = bincode::serialize(&signed_transaction)
+        .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+    let hash = web3.send_raw(encoded.into()).await?;
+
+    Ok(PendingTransaction::new(web3, hash))
+}
+
+/// A sent transaction that may not be in a block yet
+pub struct PendingTransaction<'a> {
+    web3: &'a Web3,
+    hash: H256,
+}
+
+impl<'a> PendingTransaction<'a> {
+    pub fn new(web3: &'a Web3, hash: H256) -> Self {
+        Self { web3, hash }
+    }
+
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Poll for the receipt until the transaction is in a block
+    pub async fn receipt(&self) -> Result<TransactionReceipt> {
+        for _ in 0..RECEIPT_POLL_ATTEMPTS {
+            if let Ok(receipt) = self.web3.transaction_receipt(self.hash).await {
+                return Ok(receipt);
+            }
+
+            sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+
+        Err(Web3Error::ReceiptTimeout(self.hash))
+    }
+}
+
+/// Encode a parameter as a typed pair
+pub trait EncodeParam {
+    fn encode(&self) -> [String; 2];
+}
+
+impl EncodeParam for str {
+    fn encode(&self) -> [String; 2] {
+        ["String".into(), self.into()]
+    }
+}
+
+impl EncodeParam for u64 {
+    fn encode(&self) -> [String; 2] {
+        ["U64".into(), self.to_string()]
+    }
+}
+
+impl EncodeParam for bool {
+    fn encode(&self) -> [String; 2] {
+        ["Bool".into(), self.to_string()]
+    }
+}
+
+impl EncodeParam for [String] {
+    fn encode(&self) -> [String; 2] {
+        ["List<String>".into(), serde_json::json!(self).to_string()]
+    }
+}
+
+impl EncodeParam for [u64] {
+    fn encode(&self) -> [String; 2] {
+        ["List<U64>".into(), serde_json::json!(self).to_string()]
+    }
+}
+
+/// Decode the typed pairs returned from a function
+pub trait DecodeResult: Sized {
+    fn decode(results: &[String]) -> Result<Self>;
+}
+
+impl DecodeResult for () {
+    fn decode(_results: &[String]) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl DecodeResult for String {
+    fn decode(results: &[String]) -> Result<Self> {
+        Ok(value(results, "String")?.into())
+    }
+}
+
+impl DecodeResult for u64 {
+    fn decode(results: &[String]) -> Result<Self> {
+        value(results, "U64")?
+            .parse()
+            .map_err(|_| decoding_error(results))
+    }
+}
+
+impl DecodeResult for bool {
+    fn decode(results: &[String]) -> Result<Self> {
+        value(results, "Bool")?
+            .parse()
+            .map_err(|_| decoding_error(results))
+    }
+}
+
+impl DecodeResult for Vec<String> {
+    fn decode(results: &[String]) -> Result<Self> {
+        Ok(serde_json::from_str(value(results, "List<String>")?)?)
+    }
+}
+
+impl DecodeResult for Vec<u64> {
+    fn decode(results: &[String]) -> Result<Self> {
+        Ok(serde_json::from_str(value(results, "List<U64>")?)?)
+    }
+}
+
+/// A missing value is returned as no typed pairs
+impl<T: DecodeResult> DecodeResult for Option<T> {
+    fn decode(results: &[String]) -> Result<Self> {
+        match results.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(T::decode(results)?)),
+        }
+    }
+}
+
+fn value<'r>(results: &'r [String], kind: &str) -> Result<&'r str> {
+    match results {
+        [result_kind, value] if result_kind == kind => Ok(value),
+        _ => Err(decoding_error(results)),
+    }
+}
+
+fn decoding_error(results: &[String]) -> Web3Error {
+    Web3Error::DecodingError(format!("unexpected results {:?}", results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_client;
+    use crate::helpers::tests::{
+        fund, get_amm_contract, get_contract, get_multisig_contract, web3, ACCOUNT_1,
+    };
+    use ethereum_types::U64;
+    use types::account::Account;
+    use types::helpers::to_hex;
+    use utils::crypto::{keypair, public_key_address};
+
+    contract_client!("erc20" in "../contracts/wit");
+    contract_client!("multisig" in "../contracts/wit");
+    contract_client!("amm" in "../contracts/wit");
+
+    #[test]
+    fn it_encodes_params_and_decodes_results() {
+        assert_eq!(10_u64.encode(), ["U64", "10"]);
+        assert_eq!(["a".to_string()][..].encode(), ["List<String>", "[\"a\"]"]);
+        assert_eq!(String::decode(&["String".into(), "a".into()]).unwrap(), "a");
+        assert_eq!(
+            Vec::<u64>::decode(&["List<U64>".into(), "[1,2]".into()]).unwrap(),
+            [1, 2]
+        );
+        assert_eq!(Option::<u64>::decode(&[]).unwrap(), None);
+        assert!(u64::decode(&["String".into(), "a".into()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_calls_a_contract_through_generated_bindings() {
+        let web3 = web3();
+        let (key, public_key) = keypair();
+        let account = public_key_address(&public_key);
+        fund(&web3, account, 10).await;
+
+        let receipt = Erc20::deploy(&web3, key, &get_contract(), "Rust Coin", "RustCoin")
+            .await
+            .unwrap()
+            .receipt()
+            .await
+            .unwrap();
+        let erc20 = Erc20::new(&web3, receipt.contract_address.unwrap(), key);
+        let interface = web3
+            .get_contract_interface(erc20.address(), None)
+            .await
+            .unwrap();
+        assert_eq!(interface, Erc20::interface());
+
+        let mint = erc20.mint(&to_hex(account), 10).await.unwrap();
+        let transfer = erc20.transfer(&to_hex(*ACCOUNT_1), 20).await.unwrap();
+        assert_eq!(mint.receipt().await.unwrap().status, U64::one());
+        assert_eq!(transfer.receipt().await.unwrap().status, U64::zero());
+
+        assert_eq!(erc20.call_name().await.unwrap(), "Rust Coin");
+        assert_eq!(erc20.call_balance_of(&to_hex(account)).await.unwrap(), 10);
+        assert_eq!(erc20.call_total_supply().await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn it_executes_a_multisig_proposal_through_the_node() {
+        let web3 = web3();
+        let (key_1, public_key_1) = keypair();
+        let (key_2, public_key_2) = keypair();
+        let owner_1 = public_key_address(&public_key_1);
+        let owner_2 = public_key_address(&public_key_2);
+        let recipient = Account::random();
+        fund(&web3, owner_1, 100).await;
+        fund(&web3, owner_2, 10).await;
+
+        let owners = [to_hex(owner_1), to_hex(owner_2)];
+        let receipt = Multisig::deploy(&web3, key_1, &get_multisig_contract(), &owners, 2)
+            .await
+            .unwrap()
+            .receipt()
+            .await
+            .unwrap();
+        let address = receipt.contract_address.unwrap();
+        let deposit = Multisig::new(&web3, address, key_1)
+            .with_value(U256::from(100))
+            .receive()
+            .await
+            .unwrap();
+        assert_eq!(deposit.receipt().await.unwrap().status, U64::one());
+        assert_eq!(web3.get_balance(address).await.unwrap(), U256::from(100));
+
+        // the proposal is confirmed by the submitter, but needs both owners
+        let multisig_1 = Multisig::new(&web3, address, key_1);
+        let submit = multisig_1
+            .submit(&to_hex(recipient), 60, "", &[])
+            .await
+            .unwrap();
+        assert_eq!(submit.receipt().await.unwrap().status, U64::one());
+        assert_eq!(multisig_1.call_proposal_count().await.unwrap(), 1);
+        let execute = multisig_1.execute(0).await.unwrap();
+        assert_eq!(execute.receipt().await.unwrap().status, U64::zero());
+
+        let multisig_2 = Multisig::new(&web3, address, key_2);
+        let confirm = multisig_2.confirm(0).await.unwrap();
+        assert_eq!(confirm.receipt().await.unwrap().status, U64::one());
+        assert!(multisig_2
+            .call_is_confirmed(0, &to_hex(owner_2))
+            .await
+            .unwrap());
+        let execute = multisig_2.execute(0).await.unwrap();
+        assert_eq!(execute.receipt().await.unwrap().status, U64::one());
+
+        assert_eq!(web3.get_balance(recipient).await.unwrap(), U256::from(60));
+        assert_eq!(web3.get_balance(address).await.unwrap(), U256::from(40));
+    }
+
+    #[tokio::test]
+    async fn it_swaps_through_an_amm_on_the_node() {
+        let web3 = web3();
+        let (key, public_key) = keypair();
+        let (trader_key, trader_public_key) = keypair();
+        let provider = public_key_address(&public_key);
+        let trader = public_key_address(&trader_public_key);
+        fund(&web3, provider, 10_000).await;
+        fund(&web3, trader, 1_000).await;
+
+        let receipt = Amm::deploy(&web3, key, &get_amm_contract(), 30)
+            .await
+            .unwrap()
+            .receipt()
+            .await
+            .unwrap();
+        let address = receipt.contract_address.unwrap();
+        let amm = Amm::new(&web3, address, key);
+        let mint = amm.mint(&to_hex(provider), 40_000).await.unwrap();
+        assert_eq!(mint.receipt().await.unwrap().status, U64::one());
+        let liquidity = Amm::new(&web3, address, key)
+            .with_value(U256::from(10_000))
+            .add_liquidity(40_000, 0)
+            .await
+            .unwrap();
+        assert_eq!(liquidity.receipt().await.unwrap().status, U64::one());
+        assert_eq!(amm.call_reserves().await.unwrap(), [10_000, 40_000]);
+
+        // 1000 * 9970 * 40000 / (10000 * 10000 + 1000 * 9970)
+        let swap = Amm::new(&web3, address, trader_key)
+            .with_value(U256::from(1_000))
+            .swap_coin_for_token(3_627)
+            .await
+            .unwrap();
+        assert_eq!(swap.receipt().await.unwrap().status, U64::zero());
+        let swap = Amm::new(&web3, address, trader_key)
+            .with_value(U256::from(1_000))
+            .swap_coin_for_token(3_626)
+            .await
+            .unwrap();
+        assert_eq!(swap.receipt().await.unwrap().status, U64::one());
+        assert_eq!(
+            amm.call_token_balance_of(&to_hex(trader)).await.unwrap(),
+            3_626
+        );
+        assert_eq!(amm.call_reserves().await.unwrap(), [11_000, 36_374]);
+
+        // 3626 * 9970 * 11000 / (36374 * 10000 + 3626 * 9970)
+        let swap = Amm::new(&web3, address, trader_key)
+            .swap_token_for_coin(3_626, 0)
+            .await
+            .unwrap();
+        assert_eq!(swap.receipt().await.unwrap().status, U64::one());
+        assert_eq!(amm.call_reserves().await.unwrap(), [10_006, 40_000]);
+        assert_eq!(web3.get_balance(trader).await.unwrap(), U256::from(994));
+    }
+}
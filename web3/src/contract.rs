@@ -10,34 +10,48 @@ use ethereum_types::Address;
 use ethereum_types::{H256, U256};
 use jsonrpsee::rpc_params;
 use types::block::BlockNumber;
-use types::bytes::Bytes;
 use types::helpers::to_hex;
-use types::transaction::TransactionRequest;
+use types::interface::ContractInterface;
+use types::storage::StorageKeys;
+use types::transaction::{DeploymentData, TransactionRequest};
 
-use crate::error::Result;
+use crate::error::{Result, Web3Error};
 use crate::Web3;
 
 impl Web3 {
     /// Deploy a contract to the chain.
     ///
+    /// The args are typed pairs passed to the contract's constructor (e.g. `["String", "Rust Coin"]`).
+    /// A constructor that traps fails the deployment, leaving no contract behind.
+    ///
+    /// Without a salt, the contract address is derived from the owner and nonce (CREATE).
+    /// With a salt, the contract address is derived from the owner, salt and code (CREATE2).
+    /// Either address can be predicted before sending using `utils::crypto::contract_address`
+    /// or `utils::crypto::contract_address_with_salt`.
+    ///
     /// # Examples
     ///
     /// ```ignore
     /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
     /// let account = web3.get_all_accounts().await.unwrap()[0];
     /// let contract = include_bytes!("./../../contracts/artifacts/contracts/ERC20.sol/RustCoinToken.json").to_vec();
-    /// let tx_hash = web3.deploy(account, &contract).await;
+    /// let tx_hash = web3.deploy(account, &contract, &["String", "Rust Coin", "String", "RustCoin"], None, None).await;
     /// assert!(tx_hash.is_ok());
     /// ```
-    pub async fn deploy<'a>(
+    pub async fn deploy(
         &self,
         owner: Address,
-        abi: &'a [u8],
+        abi: &[u8],
+        args: &[&str],
+        salt: Option<H256>,
         nonce: Option<U256>,
     ) -> Result<H256> {
         let gas = U256::from(1_000_000);
         let gas_price = U256::from(1_000_000);
-        let data: Bytes = abi.to_vec().into();
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        let data = DeploymentData::new(abi.to_vec().into(), args, salt)
+            .encode()
+            .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
         let transaction_request = TransactionRequest {
             from: Some(owner),
             to: None,
@@ -53,6 +67,48 @@ impl Web3 {
         self.send(transaction_request).await
     }
 
+    /// Call a contract function against the latest state without sending a transaction,
+    /// so any changes made by the function are discarded.
+    ///
+    /// The params and returned values are typed pairs (e.g. `["String", "0x4a0d..."]`).
+    /// An error is returned if the function reverts.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_call
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let balance = web3.call(account, contract, "balance-of", &["String", &to_hex(account)]).await;
+    /// assert_eq!(balance.unwrap(), ["U64", "0"]);
+    /// ```
+    pub async fn call(
+        &self,
+        from: Address,
+        to: Address,
+        function: &str,
+        params: &[&str],
+    ) -> Result<Vec<String>> {
+        let data = bincode::serialize(&(function, params))
+            .map_err(|e| Web3Error::EncodingError(e.to_string()))?;
+        let transaction_request = TransactionRequest {
+            from: Some(from),
+            to: Some(to),
+            value: Some(U256::zero()),
+            gas: U256::from(1_000_000),
+            gas_price: U256::zero(),
+            data: Some(data.into()),
+            nonce: None,
+            r: None,
+            s: None,
+        };
+        let params = rpc_params![transaction_request, "latest"];
+        let response = self.send_rpc("eth_call", params).await?;
+        let result: Vec<String> = serde_json::from_value(response)?;
+
+        Ok(result)
+    }
+
     /// Get the contract code for an address
     ///
     /// See https://eth.wiki/json-rpc/API#eth_getCode
@@ -62,7 +118,7 @@ impl Web3 {
     /// ```ignore
     /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
     /// let contract = include_bytes!("./../../contracts/artifacts/contracts/ERC20.sol/RustCoinToken.json").to_vec();
-    /// let tx_hash = web3.deploy(account, &contract).await.unwrap();
+    /// let tx_hash = web3.deploy(account, &contract, &[], None, None).await.unwrap();
     /// let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
     /// let code = web3.code(receipt.contract_address.unwrap(), None).await.unwrap();
     /// assert!(code.is_ok());
@@ -79,23 +135,105 @@ impl Web3 {
 
         Ok(code)
     }
+
+    /// Get a value from the storage of a contract, None if the key isn't set.
+    /// Reading storage directly checks values, such as balances, without relying
+    /// on the contract's own functions.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getStorageAt
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let key = format!("balance:{}", to_hex(account));
+    /// let balance = web3.get_storage_at(contract, &key, None).await.unwrap();
+    /// assert_eq!(balance.as_deref(), Some("10"));
+    /// ```
+    pub async fn get_storage_at(
+        &self,
+        address: Address,
+        key: &str,
+        block_number: Option<BlockNumber>,
+    ) -> Result<Option<String>> {
+        let block_number = Web3::get_hex_blocknumber(block_number);
+        let params = rpc_params![to_hex(address), key, block_number];
+        let response = self.send_rpc("eth_getStorageAt", params).await?;
+        let value: Option<String> = serde_json::from_value(response)?;
+
+        Ok(value)
+    }
+
+    /// List the keys a contract has written to its storage, in key order.
+    /// Pass the `next_key` of a page as the start of the next page.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let page = web3.get_storage_keys(contract, None, None, 100).await.unwrap();
+    /// let next = web3.get_storage_keys(contract, None, page.next_key, 100).await.unwrap();
+    /// ```
+    pub async fn get_storage_keys(
+        &self,
+        address: Address,
+        block_number: Option<BlockNumber>,
+        start: Option<String>,
+        limit: usize,
+    ) -> Result<StorageKeys> {
+        let block_number = Web3::get_hex_blocknumber(block_number);
+        let params = rpc_params![to_hex(address), block_number, start, limit];
+        let response = self.send_rpc("debug_storageKeys", params).await?;
+        let keys: StorageKeys = serde_json::from_value(response)?;
+
+        Ok(keys)
+    }
+
+    /// Get the interface of a contract at a block, with the signatures of its
+    /// functions and the events declared when its code was deployed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let interface = web3.get_contract_interface(contract, None).await.unwrap();
+    /// let balance_of = interface.function("balance-of").unwrap();
+    /// assert_eq!(balance_of.results.as_deref(), Some("u64"));
+    /// ```
+    pub async fn get_contract_interface(
+        &self,
+        address: Address,
+        block_number: Option<BlockNumber>,
+    ) -> Result<ContractInterface> {
+        let block_number = Web3::get_hex_blocknumber(block_number);
+        let params = rpc_params![to_hex(address), block_number];
+        let response = self.send_rpc("eth_getContractInterface", params).await?;
+        let interface: ContractInterface = serde_json::from_value(response)?;
+
+        Ok(interface)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::helpers::tests::{deploy_contract, web3};
+    use super::*;
+    use crate::helpers::tests::{
+        deploy_contract, get_contract, increment_account_1_nonce, web3, ACCOUNT_1, ERC20_ARGS,
+    };
+    use ethereum_types::U64;
     use std::time::Duration;
     use tokio::time::sleep;
+    use utils::crypto::{contract_address, contract_address_with_salt};
 
     #[tokio::test]
     async fn it_deploys_a_contract() {
-        let response = deploy_contract(true).await;
+        deploy_contract().await;
     }
 
     #[tokio::test]
     async fn it_gets_a_contract_code() {
         let web3 = web3();
-        let tx_hash = deploy_contract(true).await;
+        let tx_hash = deploy_contract().await;
 
         // TODO(ddimaria): use polling or callbacks instead of waiting
         sleep(Duration::from_millis(1000)).await;
@@ -104,6 +242,176 @@ mod tests {
         let response = web3.code(receipt.contract_address.unwrap(), None).await;
 
         // ensure the code matches what was deployed
-        assert_eq!(response.unwrap(), [0, 1]);
+        assert_eq!(response.unwrap(), get_contract());
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_contract_interface() {
+        let web3 = web3();
+        let tx_hash = deploy_contract().await;
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        let interface = web3
+            .get_contract_interface(receipt.contract_address.unwrap(), None)
+            .await
+            .unwrap();
+        let transfer = interface.function("transfer").unwrap();
+
+        assert_eq!(transfer.params.len(), 2);
+        assert_eq!(transfer.results.as_deref(), Some("result<_, string>"));
+    }
+
+    #[tokio::test]
+    async fn it_gets_contract_storage() {
+        let web3 = web3();
+        let tx_hash = deploy_contract().await;
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        let contract = receipt.contract_address.unwrap();
+        let name = web3.get_storage_at(contract, "name", None).await.unwrap();
+        let unset = web3.get_storage_at(contract, "unset", None).await.unwrap();
+        let page = web3
+            .get_storage_keys(contract, None, None, 2)
+            .await
+            .unwrap();
+        let next = web3
+            .get_storage_keys(contract, None, page.next_key.clone(), 10)
+            .await
+            .unwrap();
+
+        assert_eq!(name.as_deref(), Some("Rust Coin"));
+        assert_eq!(unset, None);
+        assert_eq!(page.keys.len(), 2);
+        assert!([page.keys, next.keys].concat().contains(&"symbol".into()));
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_verifiable_proof() {
+        let web3 = web3();
+        let tx_hash = deploy_contract().await;
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        let contract = receipt.contract_address.unwrap();
+        let block_number = web3.get_block_number().await.unwrap();
+        let block = web3.get_block(*block_number).await.unwrap();
+        let proof = web3
+            .get_proof(contract, &["name", "unset"], Some(block_number))
+            .await
+            .unwrap();
+
+        assert_eq!(proof.storage_proof[0].value.as_deref(), Some("Rust Coin"));
+        assert_eq!(proof.storage_proof[1].value, None);
+        assert!(proof.verify(&block).is_ok());
+
+        let mut tampered = proof;
+        tampered.storage_proof[0].value = Some("Fake Coin".into());
+        assert!(tampered.verify(&block).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_predicts_a_contract_address() {
+        let web3 = web3();
+        let nonce = increment_account_1_nonce().await;
+        let expected = contract_address(&ACCOUNT_1, nonce);
+        let tx_hash = web3
+            .deploy(*ACCOUNT_1, &get_contract(), ERC20_ARGS, None, Some(nonce))
+            .await
+            .unwrap();
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        assert_eq!(receipt.contract_address, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn it_predicts_a_contract_address_with_salt() {
+        let web3 = web3();
+        let nonce = increment_account_1_nonce().await;
+        let salt = H256::random();
+        let code = get_contract();
+        let expected = contract_address_with_salt(&ACCOUNT_1, &salt, &code);
+        let tx_hash = web3
+            .deploy(*ACCOUNT_1, &code, ERC20_ARGS, Some(salt), Some(nonce))
+            .await
+            .unwrap();
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        assert_eq!(receipt.contract_address, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn a_trapped_constructor_fails_the_deployment() {
+        let web3 = web3();
+        let nonce = increment_account_1_nonce().await;
+        let args = &["String", "", "String", ""];
+        let tx_hash = web3
+            .deploy(*ACCOUNT_1, &get_contract(), args, None, Some(nonce))
+            .await
+            .unwrap();
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        let code = web3.code(contract_address(&ACCOUNT_1, nonce), None).await;
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(receipt.contract_address, None);
+        assert!(receipt.error.is_some());
+        assert!(code.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_calls_a_contract_function() {
+        let web3 = web3();
+        let tx_hash = deploy_contract().await;
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let contract = web3
+            .transaction_receipt(tx_hash)
+            .await
+            .unwrap()
+            .contract_address
+            .unwrap();
+        let account_1 = to_hex(*ACCOUNT_1);
+        let data = bincode::serialize(&("mint", vec!["String", &account_1, "U64", "10"])).unwrap();
+        let transaction_request = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(contract),
+            value: Some(U256::zero()),
+            gas: U256::from(1_000_000),
+            gas_price: U256::from(1_000_000),
+            data: Some(data.into()),
+            nonce: Some(increment_account_1_nonce().await),
+            r: None,
+            s: None,
+        };
+        let tx_hash = web3.send(transaction_request).await.unwrap();
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(1000)).await;
+
+        let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
+        let balance = web3
+            .call(*ACCOUNT_1, contract, "balance-of", &["String", &account_1])
+            .await;
+        assert_eq!(receipt.status, U64::one());
+        assert_eq!(receipt.logs.len(), 1);
+        assert_eq!(balance.unwrap(), ["U64", "10"]);
     }
 }
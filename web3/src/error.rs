@@ -13,12 +13,21 @@ pub enum Web3Error {
     #[error("Error creating a new HTTP JSON-RPC client: {0}")]
     ClientError(String),
 
+    #[error("Error decoding data: {0}")]
+    DecodingError(String),
+
+    #[error("Error encoding data: {0}")]
+    EncodingError(String),
+
     #[error("Error serializing or deserializing JSON data: {0}")]
     JsonParseError(String),
 
     #[error("Error sending a HTTP JSON-RPC call: {0}")]
     RpcRequestError(String),
 
+    #[error("No receipt for transaction {0:?} after polling")]
+    ReceiptTimeout(ethereum_types::H256),
+
     #[error("Error receiving a HTTP JSON-RPC response: {0}")]
     RpcResponseError(String),
 
@@ -8,11 +8,13 @@
 pub(crate) mod tests {
     use std::str::FromStr;
 
+    use crate::client::PendingTransaction;
     use crate::Web3;
     use ethereum_types::{H160, H256, U256};
     use lazy_static::lazy_static;
     use tokio::sync::Mutex;
     use types::account::Account;
+    use types::transaction::TransactionRequest;
 
     lazy_static! {
         pub(crate) static ref ACCOUNT_1: Account =
@@ -21,6 +23,8 @@ pub(crate) mod tests {
         pub(crate) static ref ACCOUNT_1_NONCE: Mutex<U256> = Mutex::new(U256::zero());
     }
 
+    pub(crate) const ERC20_ARGS: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
+
     pub fn web3() -> Web3 {
         Web3::new("http://127.0.0.1:8545").unwrap()
     }
@@ -29,21 +33,48 @@ pub(crate) mod tests {
         include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm").to_vec()
     }
 
+    pub fn get_multisig_contract() -> Vec<u8> {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/multisig_wit.wasm").to_vec()
+    }
+
+    pub fn get_amm_contract() -> Vec<u8> {
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/amm_wit.wasm").to_vec()
+    }
+
     pub async fn increment_account_1_nonce() -> U256 {
         let nonce = *ACCOUNT_1_NONCE.lock().await + U256::from(1);
         *ACCOUNT_1_NONCE.lock().await = nonce;
         nonce
     }
 
-    pub async fn deploy_contract(simple: bool) -> H256 {
+    pub async fn deploy_contract() -> H256 {
         let web3 = web3();
         let from = *ACCOUNT_1;
         let nonce = increment_account_1_nonce().await;
-        let data = if simple {
-            [0, 1].to_vec()
-        } else {
-            get_contract()
+        let data = get_contract();
+        web3.deploy(from, &data, ERC20_ARGS, None, Some(nonce))
+            .await
+            .unwrap()
+    }
+
+    /// Send a value from ACCOUNT_1, waiting for the receipt
+    pub async fn fund(web3: &Web3, account: Account, value: u64) {
+        let funding = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(account),
+            value: Some(U256::from(value)),
+            gas: U256::from(1_000_000),
+            gas_price: U256::zero(),
+            data: None,
+            nonce: Some(increment_account_1_nonce().await),
+            r: None,
+            s: None,
         };
-        web3.deploy(from, &data, Some(nonce)).await.unwrap()
+        let tx_hash = web3.send(funding).await.unwrap();
+
+        PendingTransaction::new(web3, tx_hash)
+            .receipt()
+            .await
+            .unwrap();
     }
 }
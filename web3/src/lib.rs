@@ -13,8 +13,14 @@ use serde_json::Value;
 
 use crate::error::{Result, Web3Error};
 
+// lets the code generated by `contract_client!` refer to `web3::` within this crate
+extern crate self as web3;
+
+pub use proc_macros::contract_client;
+
 pub mod account;
 pub mod block;
+pub mod client;
 pub mod contract;
 pub mod error;
 mod helpers;
@@ -10,6 +10,7 @@ use ethereum_types::H256;
 use jsonrpsee::rpc_params;
 use serde_json::to_value;
 use types::bytes::Bytes;
+use types::trace::TransactionTrace;
 use types::transaction::{TransactionReceipt, TransactionRequest};
 
 use crate::error::Result;
@@ -118,18 +119,34 @@ impl Web3 {
 
         Ok(receipt)
     }
+
+    /// Retrieve the trace of a processed transaction.
+    /// The messages printed by contracts are only kept by nodes running in debug mode.
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545")?;
+    /// let trace = web3.trace_transaction(tx_hash).await;
+    /// ```
+    pub async fn trace_transaction(&self, tx_hash: H256) -> Result<TransactionTrace> {
+        let tx_hash = to_value(tx_hash)?;
+        let params = rpc_params![tx_hash];
+        let response = self.send_rpc("debug_traceTransaction", params).await?;
+        let trace = serde_json::from_value(response)?;
+
+        Ok(trace)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::helpers::tests::{
-        deploy_contract, increment_account_1_nonce, web3, ACCOUNT_1, ACCOUNT_1_NONCE, ACCOUNT_2,
+        deploy_contract, increment_account_1_nonce, web3, ACCOUNT_1, ACCOUNT_2,
     };
     use ethereum_types::U256;
     use std::time::Duration;
     use tokio::time::sleep;
-    use types::{account::Account, transaction::Transaction};
+    use types::{account::Account, helpers::to_hex, transaction::Transaction};
     use utils::crypto::keypair;
 
     async fn transaction() -> Transaction {
@@ -146,14 +163,17 @@ pub mod tests {
 
     async fn function_call_transaction(contract_account: Account, data: Bytes) -> Transaction {
         let nonce = increment_account_1_nonce().await;
-        Transaction::new(
+        let mut transaction = Transaction::new(
             *ACCOUNT_1,
             Some(contract_account),
             U256::from(10),
             Some(nonce),
             Some(data),
         )
-        .unwrap()
+        .unwrap();
+        transaction.gas = U256::from(1_000_000);
+        transaction.hash().unwrap();
+        transaction
     }
 
     pub async fn send_transaction() -> Result<H256> {
@@ -178,6 +198,18 @@ pub mod tests {
         assert!(response.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_gets_a_transaction_trace() {
+        let tx_hash = send_transaction().await.unwrap();
+
+        // TODO(ddimaria): use polling or callbacks instead of waiting
+        sleep(Duration::from_millis(2000)).await;
+
+        let trace = web3().trace_transaction(tx_hash).await.unwrap();
+        assert_eq!(trace.transaction_hash, tx_hash);
+        assert!(trace.debug.is_empty());
+    }
+
     #[tokio::test]
     async fn it_sends_a_raw_transfer_transaction() {
         let (secret_key, _) = keypair();
@@ -191,18 +223,16 @@ pub mod tests {
     #[tokio::test]
     async fn it_sends_a_raw_contract_call_transaction() {
         let (secret_key, _) = keypair();
-        let tx_hash = deploy_contract(false).await;
+        let tx_hash = deploy_contract().await;
 
         // TODO(ddimaria): use polling or callbacks instead of waiting
         sleep(Duration::from_millis(1000)).await;
 
         let receipt = web3().transaction_receipt(tx_hash).await.unwrap();
         let contract_address = receipt.contract_address.unwrap();
-        let function_call = bincode::serialize(&(
-            "construct",
-            vec!["String", "Rust Coin 1", "String", "RustCoin1"],
-        ))
-        .unwrap();
+        let function_call =
+            bincode::serialize(&("mint", vec!["String", &to_hex(*ACCOUNT_2), "U64", "10"]))
+                .unwrap();
         let transaction = function_call_transaction(contract_address, function_call.into()).await;
         let signed_transaction = web3().sign_transaction(transaction, secret_key).unwrap();
         let encoded = bincode::serialize(&signed_transaction).unwrap();